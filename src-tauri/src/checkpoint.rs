@@ -0,0 +1,332 @@
+// src-tauri/src/checkpoint.rs
+// Checkpoint lưu bản sao các file trước khi bị sửa, để hoàn tác cả một lượt thay đổi.
+// `apply_change_set` áp dụng nhiều thao tác file theo kiểu tất cả hoặc không gì cả:
+// lỗi ở bất kỳ thao tác nào sẽ khôi phục lại mọi file từ checkpoint.
+use crate::file_writer;
+use crate::models::FileOperation;
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+pub const STAGED_CHANGES_FILE: &str = "staged_changes.json";
+// Các file chưa tồn tại lúc tạo checkpoint, sẽ bị xóa khi hoàn tác
+const CREATED_FILES_FILE: &str = "created_files.json";
+// Các thư mục chưa tồn tại lúc tạo checkpoint, sẽ bị xóa (nếu rỗng) khi hoàn tác
+const CREATED_DIRS_FILE: &str = "created_dirs.json";
+// Bản sao các file nằm trong thư mục con riêng, để không lẫn với các file thông tin của checkpoint
+const FILES_DIR: &str = "files";
+
+/// Sao chép các file đang tồn tại trong `files` (đường dẫn tương đối) vào checkpoint.
+pub fn backup_files(checkpoint_path: &Path, project_root: &Path, files: &[String]) -> Result<(), String> {
+    let files_dir = checkpoint_path.join(FILES_DIR);
+    fs::create_dir_all(&files_dir)
+        .map_err(|e| format!("Không thể tạo thư mục checkpoint: {}", e))?;
+    for rel_path_str in files {
+        let source_path = project_root.join(rel_path_str);
+        if source_path.exists() {
+            let dest_path = files_dir.join(rel_path_str);
+            if let Some(parent) = dest_path.parent() {
+                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            fs::copy(&source_path, &dest_path).map_err(|e| e.to_string())?;
+        }
+        // If the source file doesn't exist (e.g., it's a new file to be created),
+        // we simply don't back it up. Reverting will involve deleting it.
+    }
+    Ok(())
+}
+
+/// Khôi phục các file đã sao lưu, xóa các file được tạo sau checkpoint (`created_files` cùng với
+/// danh sách đã ghi trong checkpoint) và các thư mục rỗng do checkpoint tạo ra.
+/// Không xóa thư mục checkpoint.
+pub fn restore_files(checkpoint_path: &Path, project_root: &Path, created_files: &[String]) -> Result<(), String> {
+    let files_dir = checkpoint_path.join(FILES_DIR);
+    // Checkpoint cũ lưu bản sao ngay tại thư mục gốc của checkpoint
+    let legacy_layout = !files_dir.is_dir();
+    let backup_root = if legacy_layout { checkpoint_path } else { files_dir.as_path() };
+    for entry in WalkDir::new(backup_root).min_depth(1).into_iter().filter_map(|e| e.ok()) {
+        let src_path = entry.path();
+        if !src_path.is_file() {
+            continue;
+        }
+        let Ok(rel_path) = src_path.strip_prefix(backup_root) else {
+            continue;
+        };
+        if legacy_layout
+            && (rel_path == Path::new(STAGED_CHANGES_FILE) || rel_path == Path::new(CREATED_FILES_FILE))
+        {
+            continue;
+        }
+        let dest_path = project_root.join(rel_path);
+        if let Some(parent) = dest_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        fs::copy(src_path, &dest_path).map_err(|e| e.to_string())?;
+    }
+
+    let mut to_delete: Vec<String> = created_files.to_vec();
+    if let Ok(recorded) = fs::read_to_string(checkpoint_path.join(CREATED_FILES_FILE)) {
+        to_delete.extend(serde_json::from_str::<Vec<String>>(&recorded).unwrap_or_default());
+    }
+    for rel_path_str in to_delete {
+        let file_to_delete = project_root.join(rel_path_str);
+        if file_to_delete.is_file() {
+            let _ = fs::remove_file(file_to_delete);
+        }
+    }
+
+    // Thư mục con được ghi sau thư mục cha nên xóa theo thứ tự ngược lại;
+    // `remove_dir` bỏ qua thư mục còn chứa file
+    if let Ok(recorded) = fs::read_to_string(checkpoint_path.join(CREATED_DIRS_FILE)) {
+        let created_dirs = serde_json::from_str::<Vec<String>>(&recorded).unwrap_or_default();
+        for rel_path_str in created_dirs.iter().rev() {
+            let _ = fs::remove_dir(project_root.join(rel_path_str));
+        }
+    }
+    Ok(())
+}
+
+/// Tạo checkpoint tại `checkpoint_path` cho mọi file bị ảnh hưởng rồi áp dụng `operations`.
+/// Khi có lỗi, mọi file được khôi phục và checkpoint bị xóa. `resolve` chuyển đường dẫn tương đối
+/// thành đường dẫn tuyệt đối đã được kiểm tra (xem `path_guard`).
+pub fn apply_change_set(
+    checkpoint_path: &Path,
+    project_root: &Path,
+    operations: &[FileOperation],
+    resolve: impl Fn(&str) -> Result<PathBuf, String>,
+) -> Result<(), String> {
+    let canonical_root = project_root
+        .canonicalize()
+        .map_err(|e| format!("Không thể truy cập thư mục dự án: {}", e))?;
+    let relative_to_root = |full_path: &Path| -> String {
+        full_path
+            .strip_prefix(&canonical_root)
+            .unwrap_or(full_path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    };
+
+    // 1. Kiểm tra toàn bộ thao tác trên trạng thái mô phỏng trước khi chạm vào file nào
+    let mut resolved: Vec<Vec<PathBuf>> = Vec::new();
+    let mut simulated: HashMap<PathBuf, bool> = HashMap::new();
+    let mut affected: BTreeSet<PathBuf> = BTreeSet::new();
+    for (index, operation) in operations.iter().enumerate() {
+        let fail = |e: String| format!("Thao tác #{} ({}): {}", index + 1, describe(operation), e);
+        let paths = operation
+            .paths()
+            .iter()
+            .map(|path| resolve(path))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(fail)?;
+        let mut exists = |path: &PathBuf| *simulated.entry(path.clone()).or_insert_with(|| path.is_file());
+        let (expect_existing, expect_missing) = match operation {
+            FileOperation::Create { .. } => (None, Some(&paths[0])),
+            FileOperation::Modify { .. } | FileOperation::Delete { .. } => (Some(&paths[0]), None),
+            FileOperation::Rename { .. } => (Some(&paths[0]), Some(&paths[1])),
+        };
+        if let Some(path) = expect_existing {
+            if !exists(path) {
+                return Err(fail(format!("file '{}' không tồn tại.", relative_to_root(path))));
+            }
+        }
+        if let Some(path) = expect_missing {
+            if exists(path) {
+                return Err(fail(format!("file '{}' đã tồn tại.", relative_to_root(path))));
+            }
+        }
+        match operation {
+            FileOperation::Create { .. } | FileOperation::Modify { .. } => {
+                simulated.insert(paths[0].clone(), true);
+            }
+            FileOperation::Delete { .. } => {
+                simulated.insert(paths[0].clone(), false);
+            }
+            FileOperation::Rename { .. } => {
+                simulated.insert(paths[0].clone(), false);
+                simulated.insert(paths[1].clone(), true);
+            }
+        }
+        affected.extend(paths.iter().cloned());
+        resolved.push(paths);
+    }
+
+    // 2. Sao lưu các file đang có và ghi lại các file, thư mục sẽ được tạo mới
+    let affected_files: Vec<String> = affected.iter().map(|p| relative_to_root(p)).collect();
+    let created_files: Vec<String> =
+        affected.iter().filter(|p| !p.exists()).map(|p| relative_to_root(p)).collect();
+    let mut created_dirs: BTreeSet<PathBuf> = BTreeSet::new();
+    for path in affected.iter().filter(|p| !p.exists()) {
+        let mut dir = path.parent();
+        while let Some(current) = dir.filter(|d| d.starts_with(&canonical_root) && !d.exists()) {
+            created_dirs.insert(current.to_path_buf());
+            dir = current.parent();
+        }
+    }
+    // BTreeSet sắp thư mục cha trước thư mục con
+    let created_dirs: Vec<String> = created_dirs.iter().map(|p| relative_to_root(p)).collect();
+    let checkpoint_result = backup_files(checkpoint_path, &canonical_root, &affected_files).and_then(|_| {
+        for (file_name, paths) in [(CREATED_FILES_FILE, &created_files), (CREATED_DIRS_FILE, &created_dirs)] {
+            let json = serde_json::to_string(paths).map_err(|e| e.to_string())?;
+            fs::write(checkpoint_path.join(file_name), json)
+                .map_err(|e| format!("Không thể lưu checkpoint: {}", e))?;
+        }
+        Ok(())
+    });
+    if let Err(e) = checkpoint_result {
+        let _ = fs::remove_dir_all(checkpoint_path);
+        return Err(e);
+    }
+
+    // 3. Áp dụng lần lượt; lỗi đầu tiên khiến toàn bộ được hoàn tác
+    for (index, (operation, paths)) in operations.iter().zip(&resolved).enumerate() {
+        if let Err(e) = apply_operation(operation, paths) {
+            let rollback = restore_files(checkpoint_path, &canonical_root, &[]);
+            let _ = fs::remove_dir_all(checkpoint_path);
+            let message = format!("Thao tác #{} ({}) thất bại: {}", index + 1, describe(operation), e);
+            return Err(match rollback {
+                Ok(()) => format!("{}. Mọi thay đổi đã được hoàn tác.", message),
+                Err(rollback_error) => format!("{}. Không thể hoàn tác: {}", message, rollback_error),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn apply_operation(operation: &FileOperation, paths: &[PathBuf]) -> Result<(), String> {
+    match operation {
+        FileOperation::Create { content, .. } => file_writer::write_text_preserving(&paths[0], content, None),
+        FileOperation::Modify { content, expected_mtime, .. } => {
+            file_writer::write_text_preserving(&paths[0], content, *expected_mtime)
+        }
+        FileOperation::Delete { .. } => {
+            fs::remove_file(&paths[0]).map_err(|e| format!("Không thể xóa file: {}", e))
+        }
+        FileOperation::Rename { .. } => {
+            if let Some(parent) = paths[1].parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Không thể tạo thư mục cha: {}", e))?;
+            }
+            fs::rename(&paths[0], &paths[1]).map_err(|e| format!("Không thể đổi tên file: {}", e))
+        }
+    }
+}
+
+fn describe(operation: &FileOperation) -> String {
+    match operation {
+        FileOperation::Create { path, .. } => format!("tạo {}", path),
+        FileOperation::Modify { path, .. } => format!("sửa {}", path),
+        FileOperation::Delete { path } => format!("xóa {}", path),
+        FileOperation::Rename { from, to } => format!("đổi tên {} thành {}", from, to),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FileMtime;
+
+    fn setup() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path().join("project");
+        let checkpoint = workspace.path().join("checkpoints/turn-1");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod a;\n").unwrap();
+        fs::write(root.join("src/a.rs"), "pub fn a() {}\n").unwrap();
+        fs::write(root.join("src/old.rs"), "pub fn old() {}\n").unwrap();
+        (workspace, root, checkpoint)
+    }
+
+    fn resolver(root: &Path) -> impl Fn(&str) -> Result<PathBuf, String> + '_ {
+        move |rel| crate::path_guard::resolve_project_path(root, rel, None)
+    }
+
+    fn snapshot(root: &Path) -> Vec<(String, String)> {
+        let mut files: Vec<(String, String)> = WalkDir::new(root)
+            .into_iter()
+            .filter_map(Result::ok)
+            .filter(|e| e.path().is_file())
+            .map(|e| {
+                let rel = e.path().strip_prefix(root).unwrap().to_string_lossy().to_string();
+                (rel, fs::read_to_string(e.path()).unwrap())
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn change_set_applies_all_operations_and_reverts_from_checkpoint() {
+        let (_workspace, root, checkpoint) = setup();
+        let before = snapshot(&root);
+        let operations = vec![
+            FileOperation::Create { path: "src/b.rs".into(), content: "pub fn b() {}\n".into() },
+            FileOperation::Modify { path: "src/lib.rs".into(), content: "pub mod a;\npub mod b;\n".into(), expected_mtime: None },
+            FileOperation::Delete { path: "src/a.rs".into() },
+            FileOperation::Rename { from: "src/old.rs".into(), to: "src/legacy/old.rs".into() },
+            // Thao tác sau có thể dựa vào kết quả của thao tác trước
+            FileOperation::Modify { path: "src/b.rs".into(), content: "pub fn b() -> u8 { 1 }\n".into(), expected_mtime: None },
+        ];
+        apply_change_set(&checkpoint, &root, &operations, resolver(&root)).unwrap();
+
+        assert_eq!(
+            snapshot(&root),
+            [
+                ("src/b.rs".to_string(), "pub fn b() -> u8 { 1 }\n".to_string()),
+                ("src/legacy/old.rs".to_string(), "pub fn old() {}\n".to_string()),
+                ("src/lib.rs".to_string(), "pub mod a;\npub mod b;\n".to_string()),
+            ]
+        );
+
+        restore_files(&checkpoint, &root, &[]).unwrap();
+        assert_eq!(snapshot(&root), before);
+        assert!(!root.join("src/legacy").exists());
+    }
+
+    #[test]
+    fn change_set_rolls_back_on_first_failure() {
+        let (_workspace, root, checkpoint) = setup();
+        let before = snapshot(&root);
+
+        // Lỗi khi kiểm tra: không file nào bị chạm tới, không tạo checkpoint
+        let invalid = vec![
+            FileOperation::Delete { path: "src/a.rs".into() },
+            FileOperation::Modify { path: "src/a.rs".into(), content: "x".into(), expected_mtime: None },
+        ];
+        let error = apply_change_set(&checkpoint, &root, &invalid, resolver(&root)).unwrap_err();
+        assert!(error.starts_with("Thao tác #2"), "{}", error);
+        let escaping = vec![FileOperation::Create { path: "../evil.rs".into(), content: String::new() }];
+        assert!(apply_change_set(&checkpoint, &root, &escaping, resolver(&root)).is_err());
+        assert!(!checkpoint.exists());
+
+        // Lỗi khi ghi (file đã bị sửa từ bên ngoài): các thao tác trước được hoàn tác
+        let stale = FileMtime { secs: 1, nanos: 0 };
+        let operations = vec![
+            FileOperation::Create { path: "src/new/b.rs".into(), content: "pub fn b() {}\n".into() },
+            FileOperation::Delete { path: "src/old.rs".into() },
+            FileOperation::Modify { path: "src/lib.rs".into(), content: "pub mod b;\n".into(), expected_mtime: Some(stale) },
+        ];
+        let error = apply_change_set(&checkpoint, &root, &operations, resolver(&root)).unwrap_err();
+        assert!(error.contains("#3") && error.contains(file_writer::MODIFIED_ON_DISK), "{}", error);
+        assert_eq!(snapshot(&root), before);
+        // Thư mục do change set tạo ra cũng bị xóa
+        assert!(!root.join("src/new").exists());
+        assert!(!checkpoint.exists());
+    }
+
+    #[test]
+    fn project_files_named_like_checkpoint_metadata_are_restored() {
+        let (_workspace, root, checkpoint) = setup();
+        fs::write(root.join(CREATED_FILES_FILE), "[\"keep\"]\n").unwrap();
+        fs::write(root.join(STAGED_CHANGES_FILE), "{}\n").unwrap();
+        let before = snapshot(&root);
+
+        let stale = FileMtime { secs: 1, nanos: 0 };
+        let operations = vec![
+            FileOperation::Modify { path: CREATED_FILES_FILE.into(), content: "[]\n".into(), expected_mtime: None },
+            FileOperation::Delete { path: STAGED_CHANGES_FILE.into() },
+            FileOperation::Modify { path: "src/lib.rs".into(), content: "x".into(), expected_mtime: Some(stale) },
+        ];
+        assert!(apply_change_set(&checkpoint, &root, &operations, resolver(&root)).is_err());
+        assert_eq!(snapshot(&root), before);
+    }
+}
@@ -0,0 +1,340 @@
+// src-tauri/src/cli.rs
+// Chế độ dòng lệnh (headless): quét dự án và xuất ngữ cảnh mà không cần mở GUI.
+// Dùng chung project_data.json trong thư mục cấu hình của ứng dụng với bản GUI.
+use crate::commands::{self, utils};
+use crate::mcp_server::McpServer;
+use crate::models::{ExportFormat, RedactionReport, TokenBudgetReport};
+use crate::{file_cache, project_scanner, symbol_index};
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "\
+Usage: master-context <command> [options]
+
+Commands:
+  scan <project>                    Scan the project and update its cached data
+  groups <project>                  List the groups of a scanned project
+  export project <project>          Export the whole project context
+  export group <project> <group>    Export a group context (group id or name)
+  export commit <project> <sha>     Export the context of a commit
+  map <project>                     Print the symbol map (functions, types, ...) of the project
+  mcp <project>                     Serve the project over MCP (stdio)
+  help                              Show this message
+
+Options:
+  -o, --output <file>               Write the context to a file instead of stdout
+      --format <format>             Output format: plain, xml, markdown, json or jsonl
+                                    (defaults to the project's export setting)
+      --template <file>             Render exports with a context template file
+      --outline                     Keep only declarations (imports, types, signatures)
+      --part-tokens <n>             Split a project export into parts of at most n tokens
+                                    (written as <file>.part01.txt, ... with --output)
+      --config-dir <dir>            Use another app config directory
+  -v, --verbose                     Print scan progress to stderr
+
+Run without arguments to start the desktop app.";
+
+const SUBCOMMANDS: &[&str] = &["scan", "groups", "export", "map", "mcp", "help", "--help", "-h"];
+
+struct CliOptions {
+    positional: Vec<String>,
+    output: Option<PathBuf>,
+    config_dir: Option<PathBuf>,
+    part_tokens: Option<usize>,
+    format: Option<ExportFormat>,
+    template: Option<String>,
+    outline: bool,
+    verbose: bool,
+}
+
+/// Trả về `true` nếu tham số dòng lệnh yêu cầu chế độ CLI thay vì mở GUI.
+pub fn is_cli_invocation(args: &[String]) -> bool {
+    args.first()
+        .is_some_and(|first| SUBCOMMANDS.contains(&first.as_str()))
+}
+
+/// Chạy CLI với các tham số (không gồm tên chương trình) và trả về exit code.
+pub fn run(args: Vec<String>) -> i32 {
+    match parse_options(args).and_then(|options| execute(&options)) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+fn parse_options(args: Vec<String>) -> Result<CliOptions, String> {
+    let mut options = CliOptions {
+        positional: Vec::new(),
+        output: None,
+        config_dir: None,
+        part_tokens: None,
+        format: None,
+        template: None,
+        outline: false,
+        verbose: false,
+    };
+    let mut iter = args.into_iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "-o" | "--output" => {
+                let value = iter.next().ok_or("missing value for --output")?;
+                options.output = Some(PathBuf::from(value));
+            }
+            "--config-dir" => {
+                let value = iter.next().ok_or("missing value for --config-dir")?;
+                options.config_dir = Some(PathBuf::from(value));
+            }
+            "--part-tokens" => {
+                let value = iter.next().ok_or("missing value for --part-tokens")?;
+                let tokens = value
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|n| *n > 0)
+                    .ok_or_else(|| format!("invalid value '{}' for --part-tokens", value))?;
+                options.part_tokens = Some(tokens);
+            }
+            "--format" => {
+                let value = iter.next().ok_or("missing value for --format")?;
+                let format = serde_json::from_value(serde_json::Value::String(value.clone()))
+                    .map_err(|_| format!("unknown format '{}'", value))?;
+                options.format = Some(format);
+            }
+            "--template" => {
+                let value = iter.next().ok_or("missing value for --template")?;
+                let template = std::path::absolute(&value)
+                    .map_err(|e| format!("invalid template path '{}': {}", value, e))?;
+                options.template = Some(template.to_string_lossy().to_string());
+            }
+            "--outline" => options.outline = true,
+            "-v" | "--verbose" => options.verbose = true,
+            _ if arg.starts_with('-') && !matches!(arg.as_str(), "-h" | "--help") => {
+                return Err(format!("unknown option '{}'\n\n{}", arg, USAGE));
+            }
+            _ => options.positional.push(arg),
+        }
+    }
+    Ok(options)
+}
+
+fn execute(options: &CliOptions) -> Result<(), String> {
+    let args: Vec<&str> = options.positional.iter().map(String::as_str).collect();
+    let config_dir = match &options.config_dir {
+        Some(dir) => dir.clone(),
+        None => file_cache::default_app_config_dir()?,
+    };
+
+    match args.as_slice() {
+        ["scan", project] => scan(&config_dir, &normalize_project_path(project)?, options.verbose),
+        ["groups", project] => list_groups(&config_dir, &normalize_project_path(project)?, options),
+        ["export", "project", project] => {
+            let project_path = normalize_project_path(project)?;
+            let data = load_scanned_project(&config_dir, &project_path, options)?;
+            match options.part_tokens {
+                Some(tokens_per_part) => {
+                    let (parts, redactions) =
+                        utils::build_project_context_chunks(&project_path, &data, tokens_per_part)?;
+                    print_redaction_report(&redactions);
+                    write_parts(options.output.as_deref(), &parts)
+                }
+                None => {
+                    let (context, redactions) = utils::build_project_context(&project_path, &data)?;
+                    print_redaction_report(&redactions);
+                    write_output(options.output.as_deref(), &context)
+                }
+            }
+        }
+        ["export", "group", project, group] => {
+            let project_path = normalize_project_path(project)?;
+            let data = load_scanned_project(&config_dir, &project_path, options)?;
+            let group = data
+                .groups
+                .iter()
+                .find(|g| g.id == *group || g.name == *group)
+                .ok_or_else(|| format!("group '{}' not found", group))?;
+            let (context, budget, redactions) = utils::build_group_context(&project_path, &data, group)?;
+            if let Some(report) = budget {
+                print_budget_report(&report);
+            }
+            print_redaction_report(&redactions);
+            write_output(options.output.as_deref(), &context)
+        }
+        ["export", "commit", project, sha] => {
+            let project_path = normalize_project_path(project)?;
+            let exported = commands::build_commit_context(&config_dir, &project_path, sha)?;
+            print_redaction_report(&exported.redactions);
+            write_output(options.output.as_deref(), &exported.context)
+        }
+        ["map", project] => {
+            let project_path = normalize_project_path(project)?;
+            load_scanned_project(&config_dir, &project_path, options)?;
+            let index = utils::load_symbol_index(&config_dir, &project_path)?;
+            write_output(
+                options.output.as_deref(),
+                &symbol_index::render_repo_map(&index, &Default::default()),
+            )
+        }
+        ["mcp", project] => {
+            McpServer::new(config_dir, normalize_project_path(project)?).serve()
+        }
+        ["help"] | ["--help"] | ["-h"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("invalid arguments\n\n{}", USAGE)),
+    }
+}
+
+/// Chuẩn hóa đường dẫn dự án giống như GUI lưu (tuyệt đối, không có dấu `/` ở cuối),
+/// vì thư mục cấu hình của dự án được xác định bằng hash của chuỗi đường dẫn này.
+fn normalize_project_path(project: &str) -> Result<String, String> {
+    let absolute = std::path::absolute(project)
+        .map_err(|e| format!("invalid project path '{}': {}", project, e))?;
+    if !absolute.is_dir() {
+        return Err(format!("'{}' is not a directory", absolute.display()));
+    }
+    let path_str = absolute.to_string_lossy().to_string();
+    let trimmed = path_str.trim_end_matches(['/', '\\']);
+    Ok(if trimmed.is_empty() { path_str } else { trimmed.to_string() })
+}
+
+// --format, --template và --outline ghi đè cài đặt xuất đã lưu của dự án (chỉ trong lần chạy này)
+fn load_scanned_project(
+    config_dir: &Path,
+    project_path: &str,
+    options: &CliOptions,
+) -> Result<crate::models::CachedProjectData, String> {
+    let mut data = file_cache::load_project_data(config_dir, project_path)?;
+    if options.format.is_some() {
+        data.export_format = options.format;
+    }
+    if options.template.is_some() {
+        data.export_template_path = options.template.clone();
+        for group in &mut data.groups {
+            group.template_path = None;
+        }
+    }
+    if options.outline {
+        data.export_outline = Some(true);
+        for group in &mut data.groups {
+            group.outline = None;
+        }
+    }
+    if data.file_tree.is_none() {
+        return Err(format!(
+            "project '{}' has not been scanned yet, run `master-context scan` first",
+            project_path
+        ));
+    }
+    Ok(data)
+}
+
+fn scan(config_dir: &Path, project_path: &str, verbose: bool) -> Result<(), String> {
+    let old_data = file_cache::load_project_data(config_dir, project_path)?;
+    let app_settings = file_cache::load_app_settings(config_dir).unwrap_or_default();
+
+    let print_progress = |event: &str, payload: &str| {
+        if verbose && event == "scan_progress" {
+            eprintln!("{}", payload);
+        }
+    };
+
+    let (scanned, _) = project_scanner::perform_smart_scan_and_rebuild(
+        &print_progress,
+        project_path,
+        old_data,
+        project_scanner::ScanOptions {
+            user_non_analyzable_extensions: app_settings.non_analyzable_extensions,
+            tokenizer: app_settings.tokenizer.unwrap_or_default(),
+        },
+    )?;
+    let new_data = project_scanner::save_scan_result(config_dir, project_path, scanned)?;
+
+    if new_data.sync_enabled.unwrap_or(false) && new_data.sync_path.is_some() {
+        utils::perform_auto_export(project_path, &new_data);
+    }
+
+    println!(
+        "Scanned {}: {} files, {} directories, {} tokens",
+        project_path,
+        new_data.stats.total_files,
+        new_data.stats.total_dirs,
+        new_data.stats.total_tokens
+    );
+    Ok(())
+}
+
+fn list_groups(config_dir: &Path, project_path: &str, options: &CliOptions) -> Result<(), String> {
+    let data = load_scanned_project(config_dir, project_path, options)?;
+    if data.groups.is_empty() {
+        println!("No groups.");
+        return Ok(());
+    }
+    for group in &data.groups {
+        println!(
+            "{}\t{}\t{} files\t{} tokens",
+            group.id, group.name, group.stats.total_files, group.stats.token_count
+        );
+    }
+    Ok(())
+}
+
+// In ra stderr để không lẫn vào ngữ cảnh được ghi ra stdout
+fn print_budget_report(report: &TokenBudgetReport) {
+    eprintln!(
+        "Token limit {}: {} -> {} tokens",
+        report.token_limit, report.original_token_count, report.final_token_count
+    );
+    for cut in &report.cut_files {
+        eprintln!(
+            "  {:?}\t{}\t{} -> {} tokens",
+            cut.action, cut.path, cut.original_tokens, cut.final_tokens
+        );
+    }
+}
+
+fn print_redaction_report(report: &RedactionReport) {
+    if report.secrets.is_empty() {
+        return;
+    }
+    eprintln!("Redacted {} secret(s):", report.secrets.len());
+    eprint!("{}", utils::format_redaction_report(report));
+}
+
+// Với --output, mỗi phần được ghi vào một file riêng cạnh file đích;
+// nếu không, các phần được in nối tiếp (mỗi phần đã có dòng "PART i OF N").
+fn write_parts(output: Option<&Path>, parts: &[String]) -> Result<(), String> {
+    let Some(path) = output else {
+        return write_output(None, &parts.join("\n"));
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let extension = path.extension().map(|e| e.to_string_lossy()).unwrap_or("txt".into());
+    for (i, part) in parts.iter().enumerate() {
+        let part_path = path.with_file_name(utils::chunk_file_name(&stem, &extension, i + 1));
+        write_output(Some(&part_path), part)?;
+    }
+    eprintln!("Wrote {} part file(s)", parts.len());
+    Ok(())
+}
+
+fn write_output(output: Option<&Path>, content: &str) -> Result<(), String> {
+    match output {
+        Some(path) => {
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("cannot create '{}': {}", parent.display(), e))?;
+            }
+            fs::write(path, content)
+                .map_err(|e| format!("cannot write '{}': {}", path.display(), e))
+        }
+        None => {
+            // Bỏ qua lỗi broken pipe khi output được pipe vào `head`, `less`...
+            match std::io::stdout().lock().write_all(content.as_bytes()) {
+                Err(e) if e.kind() != ErrorKind::BrokenPipe => Err(e.to_string()),
+                _ => Ok(()),
+            }
+        }
+    }
+}
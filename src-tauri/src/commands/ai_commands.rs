@@ -1,5 +1,6 @@
 // src-tauri/src/commands/ai_commands.rs
 use crate::{file_cache, models};
+use super::utils::storage_root;
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
@@ -10,7 +11,7 @@ fn get_chats_dir(
     app: &AppHandle,
     project_path: &str,
 ) -> Result<PathBuf, String> {
-    let project_dir = file_cache::get_project_config_dir(app, project_path)?;
+    let project_dir = file_cache::get_project_config_dir(&storage_root(app)?, project_path)?;
     let chats_dir = project_dir.join("chats");
     fs::create_dir_all(&chats_dir)
         .map_err(|e| format!("Không thể tạo thư mục chats: {}", e))?;
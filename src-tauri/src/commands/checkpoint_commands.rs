@@ -1,17 +1,18 @@
 // src-tauri/src/commands/checkpoint_commands.rs
-use crate::file_cache;
+use crate::{checkpoint, file_cache};
+use crate::models::FileOperation;
+use super::utils::{resolve_project_file, storage_root};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::{command, AppHandle};
 use uuid::Uuid;
-use walkdir::WalkDir;
 
 // Helper to get the base directory for a profile's checkpoints
-fn get_checkpoints_dir(
+pub(super) fn get_checkpoints_dir(
     app: &AppHandle,
     project_path: &str,
 ) -> Result<PathBuf, String> {
-    let project_dir = file_cache::get_project_config_dir(app, project_path)?;
+    let project_dir = file_cache::get_project_config_dir(&storage_root(app)?, project_path)?;
     let checkpoints_dir = project_dir.join("checkpoints");
     fs::create_dir_all(&checkpoints_dir)
         .map_err(|e| format!("Không thể tạo thư mục checkpoints: {}", e))?;
@@ -34,26 +35,13 @@ pub fn create_checkpoint(
 
     if let Some(json_data) = staged_changes_json {
         if !json_data.is_empty() && json_data != "[]" {
-            let staged_changes_path = checkpoint_path.join("staged_changes.json");
+            let staged_changes_path = checkpoint_path.join(checkpoint::STAGED_CHANGES_FILE);
             fs::write(staged_changes_path, json_data)
                 .map_err(|e| format!("Không thể lưu staged changes: {}", e))?;
         }
     }
 
-    let project_root = Path::new(&project_path);
-
-    for rel_path_str in files_to_backup {
-        let source_path = project_root.join(&rel_path_str);
-        if source_path.exists() {
-            let dest_path = checkpoint_path.join(&rel_path_str);
-            if let Some(parent) = dest_path.parent() {
-                fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-            }
-            fs::copy(&source_path, &dest_path).map_err(|e| e.to_string())?;
-        }
-        // If the source file doesn't exist (e.g., it's a new file to be created),
-        // we simply don't back it up. Reverting will involve deleting it.
-    }
+    checkpoint::backup_files(&checkpoint_path, Path::new(&project_path), &files_to_backup)?;
 
     Ok(checkpoint_id)
 }
@@ -72,51 +60,41 @@ pub fn revert_to_checkpoint(
         return Err(format!("Checkpoint '{}' không tồn tại.", checkpoint_id));
     }
 
-    let project_root = Path::new(&project_path);
-
-    // 1. Restore backed-up files
-    for entry in WalkDir::new(&checkpoint_path)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        let src_path = entry.path();
-        if src_path.is_file() {
-            // Bỏ qua file staged_changes.json
-            if src_path.file_name().and_then(|s| s.to_str()) == Some("staged_changes.json") {
-                continue;
-            }
-            if let Ok(rel_path) = src_path.strip_prefix(&checkpoint_path) {
-                let dest_path = project_root.join(rel_path);
-                if let Some(parent) = dest_path.parent() {
-                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-                }
-                fs::copy(src_path, &dest_path).map_err(|e| e.to_string())?;
-            }
-        }
-    }
+    // 1. Restore backed-up files and delete files that were newly created during the turn
+    checkpoint::restore_files(&checkpoint_path, Path::new(&project_path), &created_files_in_turn)?;
 
     // Read staged changes if they exist
-    let staged_changes_path = checkpoint_path.join("staged_changes.json");
+    let staged_changes_path = checkpoint_path.join(checkpoint::STAGED_CHANGES_FILE);
     let staged_changes_content = if staged_changes_path.exists() {
         Some(fs::read_to_string(staged_changes_path).map_err(|e| e.to_string())?)
     } else {
         None
     };
 
-    // 2. Delete files that were newly created during the turn
-    for rel_path_str in created_files_in_turn {
-        let file_to_delete = project_root.join(rel_path_str);
-        if file_to_delete.exists() {
-            let _ = fs::remove_file(file_to_delete);
-        }
-    }
-
-    // 3. Clean up the checkpoint directory
+    // 2. Clean up the checkpoint directory
     fs::remove_dir_all(&checkpoint_path).map_err(|e| e.to_string())?;
 
     Ok(staged_changes_content)
 }
 
+/// Áp dụng nhiều thao tác file (tạo/sửa/xóa/đổi tên) theo kiểu tất cả hoặc không gì cả.
+/// Các file bị ảnh hưởng được lưu vào một checkpoint mới trước khi sửa; trả về id của checkpoint
+/// để có thể hoàn tác bằng `revert_to_checkpoint`.
+#[command]
+pub fn apply_change_set(
+    app: AppHandle,
+    project_path: String,
+    operations: Vec<FileOperation>,
+) -> Result<String, String> {
+    let storage_root = storage_root(&app)?;
+    let checkpoint_id = Uuid::new_v4().to_string();
+    let checkpoint_path = get_checkpoints_dir(&app, &project_path)?.join(&checkpoint_id);
+    checkpoint::apply_change_set(&checkpoint_path, Path::new(&project_path), &operations, |path| {
+        resolve_project_file(&storage_root, &project_path, path)
+    })?;
+    Ok(checkpoint_id)
+}
+
 #[command]
 pub fn delete_checkpoint(
     app: AppHandle,
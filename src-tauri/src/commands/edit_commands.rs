@@ -0,0 +1,126 @@
+// src-tauri/src/commands/edit_commands.rs
+// Chỉnh sửa từng phần của file (search/replace, unified diff) thay vì ghi lại toàn bộ nội dung.
+use crate::edit_applier::{self, FilePatch};
+use crate::models::{FileEdit, FileMtime, FileOperation};
+use crate::{checkpoint, file_writer};
+use super::checkpoint_commands::get_checkpoints_dir;
+use super::utils::{resolve_project_file, storage_root};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use uuid::Uuid;
+
+// Một file sẽ được ghi: mtime lúc đọc dùng để từ chối ghi nếu file bị sửa từ bên ngoài
+struct PlannedEdit {
+    mtime: Option<FileMtime>,
+    edit: FileEdit,
+}
+
+#[command]
+pub fn apply_search_replace(
+    app: AppHandle,
+    root_path_str: String,
+    file_rel_path: String,
+    search: String,
+    replace: String,
+    expected_mtime: Option<FileMtime>,
+) -> Result<FileEdit, String> {
+    let full_path = resolve_project_file(&storage_root(&app)?, &root_path_str, &file_rel_path)?;
+    let mtime = file_writer::file_mtime(&full_path)?;
+    let (original, _) = file_writer::read_text(&full_path)?;
+    let updated = edit_applier::apply_search_replace(&original, &search, &replace)
+        .map_err(|e| format!("{}: {}", file_rel_path, e))?;
+    file_writer::write_text_preserving(&full_path, &updated, Some(expected_mtime.unwrap_or(mtime)))?;
+    Ok(FileEdit { path: file_rel_path, original_content: Some(original), new_content: Some(updated) })
+}
+
+/// Áp dụng một unified diff (một hoặc nhiều file). Mọi hunk được kiểm tra trước khi ghi,
+/// và các file được ghi qua `checkpoint::apply_change_set`, nên lỗi ở bất kỳ file nào
+/// (hunk không khớp, file bị sửa từ bên ngoài...) sẽ không để lại file nào bị sửa dở.
+#[command]
+pub fn apply_unified_diff(
+    app: AppHandle,
+    root_path_str: String,
+    diff: String,
+) -> Result<Vec<FileEdit>, String> {
+    let storage_root = storage_root(&app)?;
+    let mut planned: Vec<PlannedEdit> = Vec::new();
+    for patch in edit_applier::parse_unified_diff(&diff)? {
+        plan_patch(&mut planned, patch, |path| resolve_project_file(&storage_root, &root_path_str, path))?;
+    }
+
+    let operations: Vec<FileOperation> = planned
+        .iter()
+        .filter_map(|p| {
+            let path = p.edit.path.clone();
+            match (&p.edit.original_content, &p.edit.new_content) {
+                (None, Some(content)) => Some(FileOperation::Create { path, content: content.clone() }),
+                (Some(_), Some(content)) => {
+                    Some(FileOperation::Modify { path, content: content.clone(), expected_mtime: p.mtime })
+                }
+                (Some(_), None) => Some(FileOperation::Delete { path }),
+                // Được tạo rồi xóa trong cùng một diff: không có gì để ghi
+                (None, None) => None,
+            }
+        })
+        .collect();
+    // Checkpoint chỉ dùng để hoàn tác khi lỗi; frontend tự quản lý checkpoint của lượt chỉnh sửa
+    let checkpoint_path = get_checkpoints_dir(&app, &root_path_str)?.join(Uuid::new_v4().to_string());
+    checkpoint::apply_change_set(&checkpoint_path, Path::new(&root_path_str), &operations, |path| {
+        resolve_project_file(&storage_root, &root_path_str, path)
+    })?;
+    let _ = fs::remove_dir_all(&checkpoint_path);
+    Ok(planned.into_iter().map(|p| p.edit).collect())
+}
+
+// Tính nội dung mới của file trong `patch`; file đã có trong `planned` được sửa tiếp trên kết quả trước đó
+fn plan_patch(
+    planned: &mut Vec<PlannedEdit>,
+    patch: FilePatch,
+    resolve: impl Fn(&str) -> Result<PathBuf, String>,
+) -> Result<(), String> {
+    let path = match (&patch.old_path, &patch.new_path) {
+        (Some(old), Some(new)) if old != new => {
+            return Err(format!("Diff đổi tên '{}' thành '{}' chưa được hỗ trợ.", old, new))
+        }
+        (Some(path), _) | (None, Some(path)) => path.clone(),
+        (None, None) => return Err("Diff có cả hai đường dẫn là /dev/null.".to_string()),
+    };
+    let existing = planned.iter().position(|p| p.edit.path == path);
+    let current = match existing {
+        Some(index) => planned[index].edit.new_content.clone(),
+        None => {
+            let full_path = resolve(&path)?;
+            if full_path.exists() {
+                let mtime = file_writer::file_mtime(&full_path)?;
+                let (content, _) = file_writer::read_text(&full_path)?;
+                planned.push(PlannedEdit {
+                    mtime: Some(mtime),
+                    edit: FileEdit { path: path.clone(), original_content: Some(content.clone()), new_content: None },
+                });
+                Some(content)
+            } else {
+                planned.push(PlannedEdit {
+                    mtime: None,
+                    edit: FileEdit { path: path.clone(), original_content: None, new_content: None },
+                });
+                None
+            }
+        }
+    };
+
+    let new_content = match (patch.old_path.is_some(), patch.new_path.is_some(), current) {
+        // Tạo file mới
+        (false, _, None) => Some(edit_applier::new_file_content(&patch.hunks)),
+        (false, _, Some(_)) => return Err(format!("Không thể tạo '{}': file đã tồn tại.", path)),
+        (true, _, None) => return Err(format!("Không tìm thấy file '{}'.", path)),
+        // Xóa file
+        (true, false, Some(_)) => None,
+        (true, true, Some(content)) => Some(
+            edit_applier::apply_hunks(&content, &patch.hunks).map_err(|e| format!("{}: {}", path, e))?,
+        ),
+    };
+    let index = existing.unwrap_or(planned.len() - 1);
+    planned[index].edit.new_content = new_content;
+    Ok(())
+}
@@ -1,8 +1,11 @@
 // src-tauri/src/commands/git_commands.rs
 
-use crate::models::{self, FsEntry, GitStatus};
+use super::utils;
+use crate::file_cache;
+use crate::models::{self, ExportedContext, FsEntry, GitStatus};
+use crate::secret_redactor::SecretRedactor;
 use std::path::Path;
-use tauri::command;
+use tauri::{command, AppHandle};
 use std::collections::BTreeMap;
 use git2::{Repository, StatusOptions, Status};
 use std::fmt::Write as FmtWrite;
@@ -97,12 +100,39 @@ pub fn get_git_commits(
     Ok(commits)
 }
 
+// Bộ che bí mật theo cài đặt đã lưu của dự án (None nếu người dùng đã tắt)
+fn project_redactor(storage_root: &Path, path: &str) -> Result<Option<SecretRedactor>, String> {
+    let data = file_cache::load_project_data(storage_root, path)?;
+    Ok(SecretRedactor::from_project_data(&data))
+}
+
+// Che bí mật trong phần nội dung của từng file, dùng đường dẫn thật để các luật theo tên file có hiệu lực
+fn redact_sections(redactor: Option<&SecretRedactor>, sections: Vec<(String, String)>) -> ExportedContext {
+    let Some(redactor) = redactor else {
+        return ExportedContext {
+            context: sections.into_iter().map(|(_, section)| section).collect(),
+            ..Default::default()
+        };
+    };
+    let context = sections
+        .iter()
+        .map(|(path, section)| redactor.redact(section, path))
+        .collect();
+    ExportedContext { context, redactions: redactor.take_report() }
+}
+
 #[command]
-pub fn get_commit_diff(path: String, commit_sha: String) -> Result<String, String> {
-    let repo_path = Path::new(&path);
+pub fn get_commit_diff(app: AppHandle, path: String, commit_sha: String) -> Result<ExportedContext, String> {
+    build_commit_diff(&utils::storage_root(&app)?, &path, &commit_sha)
+}
+
+/// Diff của commit, đã che bí mật theo cài đặt xuất của dự án.
+pub fn build_commit_diff(storage_root: &Path, path: &str, commit_sha: &str) -> Result<ExportedContext, String> {
+    let redactor = project_redactor(storage_root, path)?;
+    let repo_path = Path::new(path);
     let repo = git2::Repository::open(repo_path).map_err(|e| e.to_string())?;
 
-    let oid = git2::Oid::from_str(&commit_sha).map_err(|e| e.to_string())?;
+    let oid = git2::Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
     let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
 
     let parent = if commit.parent_count() > 0 {
@@ -118,10 +148,20 @@ pub fn get_commit_diff(path: String, commit_sha: String) -> Result<String, Strin
         .diff_tree_to_tree(parent_tree.as_ref(), Some(&commit_tree), None)
         .map_err(|e| e.to_string())?;
 
-    let mut diff_output = String::new();
+    // Mỗi phần tử là (đường dẫn, phần diff của file đó)
+    let mut sections: Vec<(String, String)> = Vec::new();
 
     diff.print(git2::DiffFormat::Patch, |delta, _hunk, line| {
-        let path = delta.new_file().path().unwrap_or_else(|| Path::new(""));
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .unwrap_or_else(|| Path::new(""));
+        let path_str = path.to_string_lossy().replace('\\', "/");
+        if sections.last().is_none_or(|(last, _)| *last != path_str) {
+            sections.push((path_str, String::new()));
+        }
+        let diff_output = &mut sections.last_mut().unwrap().1;
 
         // Custom header to be more explicit than standard git diff
         if line.origin_value() == git2::DiffLineType::FileHeader {
@@ -144,7 +184,7 @@ pub fn get_commit_diff(path: String, commit_sha: String) -> Result<String, Strin
     })
     .map_err(|e| e.to_string())?;
 
-    Ok(diff_output)
+    Ok(redact_sections(redactor.as_ref(), sections))
 }
 
 // --- HELPERS FOR TREE BUILDING (ADAPTED FROM context_generator.rs) ---
@@ -195,11 +235,18 @@ fn build_and_format_tree(paths: &[String]) -> String {
 }
 
 #[command]
-pub fn generate_commit_context(path: String, commit_sha: String) -> Result<String, String> {
-    let repo_path = Path::new(&path);
+pub fn generate_commit_context(app: AppHandle, path: String, commit_sha: String) -> Result<ExportedContext, String> {
+    build_commit_context(&utils::storage_root(&app)?, &path, &commit_sha)
+}
+
+/// Ngữ cảnh của commit (cây file thay đổi và nội dung có đánh dấu diff), đã che bí mật
+/// theo cài đặt xuất của dự án.
+pub fn build_commit_context(storage_root: &Path, path: &str, commit_sha: &str) -> Result<ExportedContext, String> {
+    let redactor = project_redactor(storage_root, path)?;
+    let repo_path = Path::new(path);
     let repo = git2::Repository::open(repo_path).map_err(|e| e.to_string())?;
 
-    let oid = git2::Oid::from_str(&commit_sha).map_err(|e| e.to_string())?;
+    let oid = git2::Oid::from_str(commit_sha).map_err(|e| e.to_string())?;
     let commit = repo.find_commit(oid).map_err(|e| e.to_string())?;
     let commit_tree = commit.tree().map_err(|e| e.to_string())?;
 
@@ -289,9 +336,10 @@ pub fn generate_commit_context(path: String, commit_sha: String) -> Result<Strin
 
     let tree_structure = build_and_format_tree(&changed_files);
 
-    let mut final_content_string = String::new();
+    let mut sections = Vec::new();
 
     for (path, content_lines) in file_contents_map {
+        let mut final_content_string = String::new();
         let _ = write!(final_content_string, "\n================================================\nFILE: {}\n================================================\n", path.replace("\\", "/"));
 
         if let Some(hunks) = diff_hunks_map.get(&path) {
@@ -333,11 +381,13 @@ pub fn generate_commit_context(path: String, commit_sha: String) -> Result<Strin
                 line_num += 1;
             }
         }
-    }
 
-    let final_context = format!("Directory structure of changed files:\n{}\n{}", tree_structure, final_content_string);
+        sections.push((path, final_content_string));
+    }
 
-    Ok(final_context)
+    let mut exported = redact_sections(redactor.as_ref(), sections);
+    exported.context = format!("Directory structure of changed files:\n{}\n{}", tree_structure, exported.context);
+    Ok(exported)
 }
 
 #[command]
@@ -1,10 +1,16 @@
 // src-tauri/src/commands/group_commands.rs
-use crate::{context_generator, file_cache, group_updater, models};
-use std::collections::{HashMap, HashSet};
+use crate::{context_generator, dependency_analyzer, file_cache, group_updater, models};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use tauri::{command, AppHandle, Emitter, Window};
-use super::utils::{perform_auto_export, sanitize_group_name};
+use super::utils::{
+    apply_template, build_group_context, build_group_context_with_options, group_repo_map,
+    measure_group_context, perform_auto_export, sanitize_group_name, storage_root,
+};
+use crate::context_generator::ExportOptions;
+use crate::secret_redactor::SecretRedactor;
+use std::sync::Arc;
 use crate::models::AIGroupUpdateResult;
 
 #[command]
@@ -13,7 +19,10 @@ pub fn update_groups_in_project_data(
     path: String,
     groups: Vec<models::Group>,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
+    let storage_root = storage_root(&app)?;
+    // Giữ khóa tới khi lưu xong để không ghi đè cập nhật của watcher
+    let _lock = file_cache::lock_project_data(&storage_root, &path)?;
+    let mut project_data = file_cache::load_project_data(&storage_root, &path)?;
     let old_groups = project_data.groups.clone();
 
     if project_data.sync_enabled.unwrap_or(false) {
@@ -56,7 +65,7 @@ pub fn update_groups_in_project_data(
         perform_auto_export(&path, &project_data);
     }
 
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::save_project_data(&storage_root, &path, &project_data)
 }
 
 #[command]
@@ -65,7 +74,7 @@ pub fn calculate_group_stats_from_cache(
     root_path_str: String,
     paths: Vec<String>,
 ) -> Result<models::GroupStats, String> {
-    let project_data = file_cache::load_project_data(&app, &root_path_str)?;
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &root_path_str)?;
     let root_path = Path::new(&root_path_str);
     Ok(group_updater::recalculate_stats_for_paths(
         &paths,
@@ -88,18 +97,18 @@ pub fn start_group_update(
             calculate_group_stats_from_cache(app_clone, root_path_str.clone(), paths.clone());
         match result {
             Ok(new_stats) => {
-                if let Ok(mut project_data) =
-                    file_cache::load_project_data(&app, &root_path_str)
-                {
-                    if let Some(group) = project_data.groups.iter_mut().find(|g| g.id == group_id) {
-                        group.paths = paths.clone();
-                        group.stats = new_stats;
-
-                        if project_data.sync_enabled.unwrap_or(false) && project_data.sync_path.is_some() {
-                            perform_auto_export(&root_path_str, &project_data);
+                if let Ok(storage_root) = storage_root(&app) {
+                    let _ = file_cache::update_project_data(&storage_root, &root_path_str, |project_data| {
+                        if let Some(group) = project_data.groups.iter_mut().find(|g| g.id == group_id) {
+                            group.paths = paths.clone();
+                            group.stats = new_stats;
+
+                            if project_data.sync_enabled.unwrap_or(false) && project_data.sync_path.is_some() {
+                                perform_auto_export(&root_path_str, project_data);
+                            }
                         }
-                    }
-                    let _ = file_cache::save_project_data(&app, &root_path_str, &project_data);
+                        Ok(())
+                    });
                 }
                 let _ = window.emit(
                     "group_update_complete",
@@ -121,48 +130,28 @@ pub fn start_group_export(
     root_path_str: String,
 ) {
     std::thread::spawn(move || {
-        let result: Result<String, String> = (|| {
-            let project_data = file_cache::load_project_data(&app, &root_path_str)?;
-            let use_full_tree = project_data.export_use_full_tree.unwrap_or(false);
-            let with_line_numbers = project_data.export_with_line_numbers.unwrap_or(true);
-            let without_comments = project_data.export_without_comments.unwrap_or(false);
-            let remove_debug_logs = project_data.export_remove_debug_logs.unwrap_or(false);
-            let super_compressed = project_data.export_super_compressed.unwrap_or(false);
-            let always_apply_text = project_data.always_apply_text;
-            let exclude_extensions = project_data.export_exclude_extensions;
-            let root_path = Path::new(&root_path_str);
+        let result: Result<
+            (String, Option<models::TokenBudgetReport>, models::RedactionReport),
+            String,
+        > = (|| {
+            let project_data = file_cache::load_project_data(&storage_root(&app)?, &root_path_str)?;
             let group = project_data
                 .groups
                 .iter()
                 .find(|g| g.id == group_id)
                 .ok_or_else(|| "group.not_found".to_string())?;
-            let expanded_files = context_generator::expand_group_paths_to_files(
-                &group.paths,
-                &project_data.file_metadata_cache,
-                root_path,
-            );
-            if expanded_files.is_empty() {
-                return Err("group.export_no_files".to_string());
-            }
-            context_generator::generate_context_from_files(
-                &root_path_str,
-                &expanded_files,
-                use_full_tree,
-                &project_data.file_tree,
-                with_line_numbers,
-                without_comments,
-                remove_debug_logs,
-                super_compressed,
-                &always_apply_text,
-                &exclude_extensions,
-                &project_data.file_metadata_cache,
-            )
+            build_group_context(&root_path_str, &project_data, group)
         })();
         match result {
-            Ok(context) => {
+            Ok((context, budget, redactions)) => {
                 let _ = window.emit(
                     "group_export_complete",
-                    serde_json::json!({ "groupId": group_id, "context": context }),
+                    serde_json::json!({
+                        "groupId": group_id,
+                        "context": context,
+                        "budget": budget,
+                        "redactions": redactions,
+                    }),
                 );
             }
             Err(e) => {
@@ -183,36 +172,26 @@ pub fn generate_group_context(
     remove_debug_logs: bool,
     super_compressed: bool,
 ) -> Result<String, String> {
-    let project_data = file_cache::load_project_data(&app, &root_path_str)?;
-    let always_apply_text = project_data.always_apply_text;
-    let exclude_extensions = project_data.export_exclude_extensions;
-    let root_path = Path::new(&root_path_str);
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &root_path_str)?;
     let group = project_data
         .groups
         .iter()
         .find(|g| g.id == group_id)
         .ok_or_else(|| "group.not_found".to_string())?;
-    let expanded_files = context_generator::expand_group_paths_to_files(
-        &group.paths,
-        &project_data.file_metadata_cache,
-        root_path,
-    );
-    if expanded_files.is_empty() {
-        return Err("group.generate_context_no_files".to_string());
-    }
-    context_generator::generate_context_from_files(
-        &root_path_str,
-        &expanded_files,
+    let mut options = ExportOptions {
         use_full_tree,
-        &project_data.file_tree,
         with_line_numbers,
         without_comments,
         remove_debug_logs,
         super_compressed,
-        &always_apply_text,
-        &exclude_extensions,
-        &project_data.file_metadata_cache,
-    )
+        outline: group.outline.unwrap_or(project_data.export_outline.unwrap_or(false)),
+        repo_map: group_repo_map(&root_path_str, &project_data, group),
+        ..ExportOptions::from_project_data(&project_data)
+    };
+    apply_template(&mut options, &root_path_str, &project_data, Some(group))?;
+    build_group_context_with_options(&root_path_str, &project_data, group, &options)
+        .map(|(context, _)| context)
+        .map_err(to_generate_context_error)
 }
 
 #[command]
@@ -221,37 +200,43 @@ pub fn generate_group_context_for_ai(
     group_id: String,
     root_path_str: String,
 ) -> Result<String, String> {
-    let project_data = file_cache::load_project_data(&app, &root_path_str)?;
-    let root_path = Path::new(&root_path_str);
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &root_path_str)?;
     let group = project_data
         .groups
         .iter()
         .find(|g| g.id == group_id)
         .ok_or_else(|| "group.not_found".to_string())?;
 
-    let expanded_files = context_generator::expand_group_paths_to_files(
-        &group.paths,
-        &project_data.file_metadata_cache,
-        root_path,
-    );
-    if expanded_files.is_empty() {
-        return Err("group.generate_context_no_files".to_string());
-    }
-
     // Generate context with specific, non-configurable settings for AI
-    context_generator::generate_context_from_files(
-        &root_path_str,
-        &expanded_files,
-        false, // use_full_tree: false (minimal tree)
-        &project_data.file_tree,
-        false, // with_line_numbers: false
-        false, // without_comments: false
-        false, // remove_debug_logs: false
-        false, // super_compressed: false
-        &None, // always_apply_text: None
-        &project_data.export_exclude_extensions, // Keep user's exclude extensions
-        &project_data.file_metadata_cache,
-    )
+    let options = ExportOptions {
+        use_full_tree: false, // minimal tree
+        with_line_numbers: false,
+        without_comments: false,
+        remove_debug_logs: false,
+        super_compressed: false,
+        outline: false,
+        always_apply_text: None,
+        exclude_extensions: project_data.export_exclude_extensions.clone(), // Keep user's exclude extensions
+        format: models::ExportFormat::Plain,
+        template: None,
+        template_vars: Default::default(),
+        repo_map: None,
+        // Ngữ cảnh gửi cho AI cũng phải được che bí mật
+        redactor: SecretRedactor::from_project_data(&project_data).map(Arc::new),
+        tokenizer: project_data.stats.tokenizer,
+    };
+    build_group_context_with_options(&root_path_str, &project_data, group, &options)
+        .map(|(context, _)| context)
+        .map_err(to_generate_context_error)
+}
+
+// Giữ mã lỗi riêng của các lệnh generate_* mà frontend đang dùng
+fn to_generate_context_error(e: String) -> String {
+    if e == "group.export_no_files" {
+        "group.generate_context_no_files".to_string()
+    } else {
+        e
+    }
 }
 
 #[command]
@@ -262,7 +247,9 @@ pub fn update_group_paths_from_ai(
     paths_to_add: Vec<String>,
     paths_to_remove: Vec<String>,
 ) -> Result<AIGroupUpdateResult, String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
+    let storage_root = storage_root(&app)?;
+    let _lock = file_cache::lock_project_data(&storage_root, &path)?;
+    let mut project_data = file_cache::load_project_data(&storage_root, &path)?;
     let root_path = Path::new(&path);
     let metadata_cache_clone = project_data.file_metadata_cache.clone();
 
@@ -316,7 +303,7 @@ pub fn update_group_paths_from_ai(
         );
 
         // Now that the borrow on `group` is finished, we can save the entire `project_data`.
-        file_cache::save_project_data(&app, &path, &project_data)?;
+        file_cache::save_project_data(&storage_root, &path, &project_data)?;
 
         Ok(AIGroupUpdateResult {
             updated_group: updated_group_clone,
@@ -327,13 +314,75 @@ pub fn update_group_paths_from_ai(
     }
 }
 
+// Số token/byte thực của ngữ cảnh nhóm khi xuất với cài đặt hiện tại, kèm chi tiết từng file
+#[command]
+pub fn get_group_context_size(
+    app: AppHandle,
+    path: String,
+    group_id: String,
+) -> Result<models::ContextMeasurement, String> {
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &path)?;
+    let group = project_data
+        .groups
+        .iter()
+        .find(|g| g.id == group_id)
+        .ok_or_else(|| "group.not_found".to_string())?;
+    measure_group_context(&path, &project_data, group)
+}
+
+// Mở rộng nhóm thành bao đóng bắc cầu các file mà nhóm import, tới độ sâu `depth` (None = không giới hạn)
+#[command]
+pub fn expand_group_dependencies(
+    app: AppHandle,
+    path: String,
+    group_id: String,
+    depth: Option<usize>,
+) -> Result<AIGroupUpdateResult, String> {
+    add_related_files_to_group(app, path, group_id, depth, dependency_analyzer::dependency_closure)
+}
+
+// Thêm vào nhóm các file import file của nhóm (trực tiếp hoặc gián tiếp), tới độ sâu `depth`
+#[command]
+pub fn add_group_dependents(
+    app: AppHandle,
+    path: String,
+    group_id: String,
+    depth: Option<usize>,
+) -> Result<AIGroupUpdateResult, String> {
+    add_related_files_to_group(app, path, group_id, depth, dependency_analyzer::dependents_closure)
+}
+
+type ClosureFn = fn(&[String], &BTreeMap<String, models::FileMetadata>, Option<usize>) -> Vec<String>;
+
+fn add_related_files_to_group(
+    app: AppHandle,
+    path: String,
+    group_id: String,
+    depth: Option<usize>,
+    closure: ClosureFn,
+) -> Result<AIGroupUpdateResult, String> {
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &path)?;
+    let group = project_data
+        .groups
+        .iter()
+        .find(|g| g.id == group_id)
+        .ok_or_else(|| "group.not_found".to_string())?;
+    let group_files = context_generator::expand_group_paths_to_files(
+        &group.paths,
+        &project_data.file_metadata_cache,
+        Path::new(&path),
+    );
+    let related = closure(&group_files, &project_data.file_metadata_cache, depth);
+    update_group_paths_from_ai(app, path, group_id, related, vec![])
+}
+
 #[command]
 pub fn get_expanded_files_for_group(
     app: AppHandle,
     path: String,
     group_id: String,
 ) -> Result<Vec<String>, String> {
-    let project_data = file_cache::load_project_data(&app, &path)?;
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &path)?;
     let root_path = Path::new(&path);
 
     if let Some(group) = project_data.groups.iter().find(|g| g.id == group_id) {
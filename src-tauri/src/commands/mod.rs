@@ -1,15 +1,17 @@
 // src-tauri/src/commands/mod.rs
 mod ai_commands;
 mod checkpoint_commands;
+mod edit_commands;
 mod git_commands;
 mod group_commands;
 mod project_commands;
 mod settings_commands;
-mod utils;
+pub(crate) mod utils;
 mod watcher_commands;
 
 pub use ai_commands::*;
 pub use checkpoint_commands::*;
+pub use edit_commands::*;
 pub use git_commands::*;
 pub use group_commands::*;
 pub use project_commands::*;
@@ -1,8 +1,15 @@
 // src-tauri/src/commands/project_commands.rs
-use crate::{context_generator, file_cache, models, project_scanner};
+use crate::{
+    context_generator, dependency_analyzer, file_cache, file_writer, models, project_scanner,
+    symbol_index,
+};
 use tauri::{command, AppHandle, Emitter, Manager, Window}; // Add models
 use super::start_file_watching;
-use super::utils::perform_auto_export;
+use super::utils::{
+    apply_template, build_project_context, build_project_context_chunks, load_symbol_index,
+    measure_project_context, perform_auto_export, read_project_file_lines, resolve_project_file,
+    storage_root,
+};
 use std::fs;
 use ignore::WalkBuilder;
 use std::collections::BTreeMap;
@@ -17,33 +24,44 @@ pub fn scan_project(window: Window, path: String) {
     let app = window.app_handle().clone();
 
     std::thread::spawn(move || {
-        let old_data = file_cache::load_project_data(&app, &path).unwrap_or_default();
+        let storage_root = match storage_root(&app) {
+            Ok(root) => root,
+            Err(e) => {
+                let _ = window.emit_to(window.label(), "scan_error", e);
+                return;
+            }
+        };
+        let old_data = file_cache::load_project_data(&storage_root, &path).unwrap_or_default();
         let should_start_watching = old_data.is_watching_files.unwrap_or(false);
 
         // --- THÊM LOGIC ĐỌC CÀI ĐẶT ---
         // Lấy cài đặt ứng dụng để truyền vào scanner
-        let app_settings = super::settings_commands::get_app_settings(app.clone()).unwrap_or_default();
+        let app_settings = file_cache::load_app_settings(&storage_root).unwrap_or_default();
         
         match project_scanner::perform_smart_scan_and_rebuild(
-            &window, 
+            &window,
             &path, 
             old_data,
             project_scanner::ScanOptions {
                 user_non_analyzable_extensions: app_settings.non_analyzable_extensions,
+                tokenizer: app_settings.tokenizer.unwrap_or_default(),
             }
         ) {
-            Ok((new_data, is_first_scan)) => { // <-- Nhận thêm cờ is_first_scan
-                if let Err(e) = file_cache::save_project_data(&app, &path, &new_data) {
-                    let _ = window.emit("scan_error", e);
-                    return;
-                }
+            Ok((scanned, is_first_scan)) => { // <-- Nhận thêm cờ is_first_scan
+                let new_data = match project_scanner::save_scan_result(&storage_root, &path, scanned) {
+                    Ok(data) => data,
+                    Err(e) => {
+                        let _ = window.emit_to(window.label(), "scan_error", e);
+                        return;
+                    }
+                };
 
                 if new_data.sync_enabled.unwrap_or(false) && new_data.sync_path.is_some() {
                     perform_auto_export(&path, &new_data);
                 }
                 
                 // --- GỬI PAYLOAD MỚI VỀ FRONTEND ---
-                let _ = window.emit("scan_complete", serde_json::json!({
+                let _ = window.emit_to(window.label(), "scan_complete", serde_json::json!({
                     "projectData": new_data,
                     "isFirstScan": is_first_scan
                 }));
@@ -55,7 +73,7 @@ pub fn scan_project(window: Window, path: String) {
                 }
             }
             Err(e) => {
-                let _ = window.emit("scan_error", e);
+                let _ = window.emit_to(window.label(), "scan_error", e);
             }
         }
     });
@@ -64,35 +82,16 @@ pub fn scan_project(window: Window, path: String) {
 #[command]
 pub fn start_project_export(window: Window, app: AppHandle, path: String) {
     std::thread::spawn(move || {
-        let result: Result<String, String> = (|| {
-            let project_data = file_cache::load_project_data(&app, &path)?;
-            let with_line_numbers = project_data.export_with_line_numbers.unwrap_or(true);
-            let without_comments = project_data.export_without_comments.unwrap_or(false);
-            let remove_debug_logs = project_data.export_remove_debug_logs.unwrap_or(false);
-            let super_compressed = project_data.export_super_compressed.unwrap_or(false);
-            let always_apply_text = project_data.always_apply_text;
-            let exclude_extensions = project_data.export_exclude_extensions;
-            let all_files: Vec<String> = project_data.file_metadata_cache.keys().cloned().collect();
-            if all_files.is_empty() {
-                return Err("project.export_no_files".to_string());
-            }
-            context_generator::generate_context_from_files(
-                &path,
-                &all_files,
-                true,
-                &project_data.file_tree,
-                with_line_numbers,
-                without_comments,
-                remove_debug_logs,
-                super_compressed,
-                &always_apply_text,
-                &exclude_extensions,
-                &project_data.file_metadata_cache,
-            )
+        let result: Result<(String, models::RedactionReport), String> = (|| {
+            let project_data = file_cache::load_project_data(&storage_root(&app)?, &path)?;
+            build_project_context(&path, &project_data)
         })();
         match result {
-            Ok(context) => {
-                let _ = window.emit("project_export_complete", context);
+            Ok((context, redactions)) => {
+                let _ = window.emit(
+                    "project_export_complete",
+                    serde_json::json!({ "context": context, "redactions": redactions }),
+                );
             }
             Err(e) => {
                 let _ = window.emit("project_export_error", e);
@@ -103,31 +102,97 @@ pub fn start_project_export(window: Window, app: AppHandle, path: String) {
 
 #[command]
 pub fn generate_project_context(app: AppHandle, path: String, with_line_numbers: bool, without_comments: bool, remove_debug_logs: bool, super_compressed: bool) -> Result<String, String> {
-    let project_data = file_cache::load_project_data(&app, &path)?;
-    let always_apply_text = project_data.always_apply_text;
-    let exclude_extensions = project_data.export_exclude_extensions;
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &path)?;
     let all_files: Vec<String> = project_data.file_metadata_cache.keys().cloned().collect();
     if all_files.is_empty() {
         return Err("project.generate_context_no_files".to_string());
     }
-    context_generator::generate_context_from_files(
-        &path,
-        &all_files,
-        true,
-        &project_data.file_tree,
+    let mut options = context_generator::ExportOptions {
+        use_full_tree: true,
         with_line_numbers,
         without_comments,
         remove_debug_logs,
         super_compressed,
-        &always_apply_text,
-        &exclude_extensions,
+        ..context_generator::ExportOptions::from_project_data(&project_data)
+    };
+    apply_template(&mut options, &path, &project_data, None)?;
+    context_generator::generate_context_from_files(
+        &path,
+        &all_files,
+        &project_data.file_tree,
+        &options,
         &project_data.file_metadata_cache,
     )
 }
 
+#[command]
+pub fn generate_project_context_chunks(
+    app: AppHandle,
+    path: String,
+    tokens_per_part: usize,
+) -> Result<Vec<String>, String> {
+    if tokens_per_part == 0 {
+        return Err("project.invalid_chunk_token_limit".to_string());
+    }
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &path)?;
+    build_project_context_chunks(&path, &project_data, tokens_per_part).map(|(parts, _)| parts)
+}
+
+// Số token/byte thực của ngữ cảnh toàn dự án khi xuất với cài đặt hiện tại, kèm chi tiết từng file
+#[command]
+pub fn get_project_context_size(
+    app: AppHandle,
+    path: String,
+) -> Result<models::ContextMeasurement, String> {
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &path)?;
+    measure_project_context(&path, &project_data)
+}
+
+// Quét các file đã phân tích để tìm bí mật trước khi chia sẻ ngữ cảnh.
+// Tiến trình được gửi qua sự kiện `secret_scan_progress`.
+#[command]
+pub fn scan_project_for_secrets(
+    window: Window,
+    path: String,
+) -> Result<Vec<models::SecretFinding>, String> {
+    let project_data = file_cache::load_project_data(&storage_root(window.app_handle())?, &path)?;
+    project_scanner::scan_for_secrets(&window, &path, &project_data)
+}
+
+/// Làm mới chỉ mục ký hiệu (chỉ trích xuất lại các file có size/mtime thay đổi) và lưu lại.
+#[command]
+pub fn build_symbol_index(
+    app: AppHandle,
+    path: String,
+) -> Result<BTreeMap<String, models::FileSymbols>, String> {
+    load_symbol_index(&storage_root(&app)?, &path)
+}
+
+#[command]
+pub fn generate_repo_map(app: AppHandle, path: String) -> Result<String, String> {
+    let index = build_symbol_index(app, path)?;
+    Ok(symbol_index::render_repo_map(&index, &Default::default()))
+}
+
+// Các file import `file` trực tiếp hoặc gián tiếp, tối đa `depth` bước (None = không giới hạn)
+#[command]
+pub fn get_dependents(
+    app: AppHandle,
+    path: String,
+    file: String,
+    depth: Option<usize>,
+) -> Result<Vec<String>, String> {
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &path)?;
+    Ok(dependency_analyzer::dependents_closure(
+        &[file],
+        &project_data.file_metadata_cache,
+        depth,
+    ))
+}
+
 #[command]
 pub fn delete_project_data(app: AppHandle, path: String) -> Result<(), String> {
-    let project_config_dir = file_cache::get_project_config_dir(&app, &path)?;
+    let project_config_dir = file_cache::get_project_config_dir(&storage_root(&app)?, &path)?;
     if project_config_dir.exists() {
         fs::remove_dir_all(&project_config_dir)
             .map_err(|e| format!("Không thể xóa dữ liệu dự án: {}", e))?;
@@ -136,63 +201,58 @@ pub fn delete_project_data(app: AppHandle, path: String) -> Result<(), String> {
 }
 
 #[command]
-pub fn get_file_content(root_path_str: String, file_rel_path: String) -> Result<String, String> {
-    let root_path = std::path::Path::new(&root_path_str);
-    let full_path = root_path.join(file_rel_path);
-    fs::read_to_string(full_path).map_err(|e| format!("Không thể đọc file: {}", e))
+pub fn get_file_content(
+    app: AppHandle,
+    root_path_str: String,
+    file_rel_path: String,
+) -> Result<String, String> {
+    let full_path = resolve_project_file(&storage_root(&app)?, &root_path_str, &file_rel_path)?;
+    file_writer::read_text(&full_path).map(|(content, _)| content)
+}
+
+// Nội dung kèm mtime lúc đọc, để truyền lại cho `save_file_content` khi ghi
+#[command]
+pub fn read_file_snapshot(
+    app: AppHandle,
+    root_path_str: String,
+    file_rel_path: String,
+) -> Result<models::FileSnapshot, String> {
+    let full_path = resolve_project_file(&storage_root(&app)?, &root_path_str, &file_rel_path)?;
+    // Lấy mtime trước khi đọc: nếu file đổi trong lúc đọc, lần ghi sau sẽ bị từ chối
+    let mtime = file_writer::file_mtime(&full_path)?;
+    let (content, _) = file_writer::read_text(&full_path)?;
+    Ok(models::FileSnapshot { content, mtime })
 }
 
 #[command]
 pub fn read_file_with_lines(
+    app: AppHandle,
     root_path_str: String,
     file_rel_path: String,
     start_line: Option<usize>,
     end_line: Option<usize>,
 ) -> Result<String, String> {
-    let root_path = std::path::Path::new(&root_path_str);
-    let full_path = root_path.join(&file_rel_path);
-    let content = fs::read_to_string(full_path)
-        .map_err(|e| format!("Không thể đọc file '{}': {}", file_rel_path, e))?;
-
-    if start_line.is_none() && end_line.is_none() {
-        return Ok(content);
-    }
-
-    let lines: Vec<&str> = content.lines().collect();
-    let total_lines = lines.len();
-
-    // Line numbers from AI are 1-based, convert to 0-based index
-    let start_index = start_line.map_or(0, |n| n.saturating_sub(1));
-    let end_index = end_line.map_or(total_lines, |n| n).min(total_lines);
-
-    if start_index >= end_index {
-        return Ok("".to_string());
-    }
-
-    Ok(lines[start_index..end_index].join("\n"))
+    read_project_file_lines(&storage_root(&app)?, &root_path_str, &file_rel_path, start_line, end_line)
 }
 
 #[command]
 pub fn save_file_content(
+    app: AppHandle,
     root_path_str: String,
     file_rel_path: String,
     content: String,
+    expected_mtime: Option<models::FileMtime>,
 ) -> Result<(), String> {
-    let root_path = std::path::Path::new(&root_path_str);
-    let full_path = root_path.join(file_rel_path);
-    if let Some(parent_dir) = full_path.parent() {
-        fs::create_dir_all(parent_dir)
-            .map_err(|e| format!("Không thể tạo thư mục cha: {}", e))?;
-    }
-    fs::write(full_path, content).map_err(|e| format!("Không thể ghi file: {}", e))
+    let full_path = resolve_project_file(&storage_root(&app)?, &root_path_str, &file_rel_path)?;
+    file_writer::write_text_preserving(&full_path, &content, expected_mtime)
 }
 #[command]
 pub fn generate_directory_tree(
+    app: AppHandle,
     root_path_str: String,
     dir_rel_path: String,
 ) -> Result<String, String> {
-    let root_path = Path::new(&root_path_str);
-    let full_dir_path = root_path.join(&dir_rel_path);
+    let full_dir_path = resolve_project_file(&storage_root(&app)?, &root_path_str, &dir_rel_path)?;
 
     if !full_dir_path.is_dir() {
         return Err(format!("'{}' không phải là một thư mục.", dir_rel_path));
@@ -258,23 +318,22 @@ pub fn generate_directory_tree(
 }
 #[command]
 pub fn create_file(
+    app: AppHandle,
     root_path_str: String,
     file_rel_path: String,
     content: String,
 ) -> Result<(), String> {
-    let root_path = std::path::Path::new(&root_path_str);
-    let full_path = root_path.join(&file_rel_path);
-    if let Some(parent_dir) = full_path.parent() {
-        fs::create_dir_all(parent_dir)
-            .map_err(|e| format!("Không thể tạo thư mục cha: {}", e))?;
-    }
-    fs::write(full_path, content).map_err(|e| format!("Không thể tạo file: {}", e))
+    let full_path = resolve_project_file(&storage_root(&app)?, &root_path_str, &file_rel_path)?;
+    file_writer::write_text_preserving(&full_path, &content, None)
 }
 
 #[command]
-pub fn delete_file(root_path_str: String, file_rel_path: String) -> Result<(), String> {
-    let root_path = std::path::Path::new(&root_path_str);
-    let full_path = root_path.join(file_rel_path);
+pub fn delete_file(
+    app: AppHandle,
+    root_path_str: String,
+    file_rel_path: String,
+) -> Result<(), String> {
+    let full_path = resolve_project_file(&storage_root(&app)?, &root_path_str, &file_rel_path)?;
     if full_path.exists() {
         fs::remove_file(full_path).map_err(|e| format!("Không thể xóa file: {}", e))
     } else {
@@ -288,7 +347,9 @@ pub fn update_file_exclusions(
     file_rel_path: String,
     ranges: Vec<(usize, usize)>,
 ) -> Result<models::FileMetadata, String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
+    let storage_root = storage_root(&app)?;
+    let _lock = file_cache::lock_project_data(&storage_root, &path)?;
+    let mut project_data = file_cache::load_project_data(&storage_root, &path)?;
 
     let updated_metadata: models::FileMetadata;
 
@@ -304,7 +365,7 @@ pub fn update_file_exclusions(
         ));
     }
 
-    file_cache::save_project_data(&app, &path, &project_data)?;
+    file_cache::save_project_data(&storage_root, &path, &project_data)?;
 
     Ok(updated_metadata)
 }
\ No newline at end of file
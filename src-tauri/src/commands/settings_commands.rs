@@ -1,32 +1,11 @@
 // src-tauri/src/commands/settings_commands.rs
-use crate::{file_cache, models};
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::PathBuf;
-use tauri::{command, AppHandle, Manager};
-use super::utils::perform_auto_export;
-
-fn get_app_settings_path(app: &AppHandle) -> Result<PathBuf, String> {
-    let config_dir = app
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("Không thể lấy thư mục cấu hình: {}", e))?;
-    if !config_dir.exists() {
-        fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Không thể tạo thư mục cấu hình: {}", e))?;
-    }
-    Ok(config_dir.join("app_settings.json"))
-}
+use crate::{file_cache, models, secret_redactor::SecretRedactor};
+use tauri::{command, AppHandle};
+use super::utils::{perform_auto_export, storage_root};
 
 #[command]
 pub fn get_app_settings(app: AppHandle) -> Result<models::AppSettings, String> {
-    let path = get_app_settings_path(&app)?;
-    if !path.exists() {
-        return Ok(models::AppSettings::default());
-    }
-    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
-    if contents.is_empty() { return Ok(models::AppSettings::default()); }
-    serde_json::from_str(&contents).map_err(|e| e.to_string())
+    file_cache::load_app_settings(&storage_root(&app)?)
 }
 
 #[command]
@@ -36,15 +15,16 @@ pub fn update_sync_settings(
     enabled: bool,
     sync_path: Option<String>,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.sync_enabled = Some(enabled);
-    project_data.sync_path = sync_path;
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.sync_enabled = Some(enabled);
+        project_data.sync_path = sync_path;
 
-    if enabled && project_data.sync_path.is_some() {
-        perform_auto_export(&path, &project_data);
-    }
+        if enabled && project_data.sync_path.is_some() {
+            perform_auto_export(&path, project_data);
+        }
 
-    file_cache::save_project_data(&app, &path, &project_data)
+        Ok(())
+    })
 }
 
 #[command]
@@ -53,9 +33,10 @@ pub fn update_custom_ignore_patterns(
     path: String,
     patterns: Vec<String>,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.custom_ignore_patterns = Some(patterns);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.custom_ignore_patterns = Some(patterns);
+        Ok(())
+    })
 }
 
 #[command]
@@ -64,9 +45,22 @@ pub fn set_file_watching_setting(
     path: String,
     enabled: bool,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.is_watching_files = Some(enabled);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.is_watching_files = Some(enabled);
+        Ok(())
+    })
+}
+
+#[command]
+pub fn set_watcher_debounce_setting(
+    app: AppHandle,
+    path: String,
+    debounce_ms: Option<u64>,
+) -> Result<(), String> {
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.watcher_debounce_ms = debounce_ms.filter(|ms| *ms > 0);
+        Ok(())
+    })
 }
 
 #[command]
@@ -75,9 +69,10 @@ pub fn set_export_use_full_tree_setting(
     path: String,
     enabled: bool,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.export_use_full_tree = Some(enabled);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_use_full_tree = Some(enabled);
+        Ok(())
+    })
 }
 
 #[command]
@@ -86,9 +81,10 @@ pub fn set_export_with_line_numbers_setting(
     path: String,
     enabled: bool,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.export_with_line_numbers = Some(enabled);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_with_line_numbers = Some(enabled);
+        Ok(())
+    })
 }
 
 #[command]
@@ -97,9 +93,10 @@ pub fn set_export_without_comments_setting(
     path: String,
     enabled: bool,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.export_without_comments = Some(enabled);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_without_comments = Some(enabled);
+        Ok(())
+    })
 }
 
 #[command]
@@ -108,9 +105,22 @@ pub fn set_export_remove_debug_logs_setting(
     path: String,
     enabled: bool,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.export_remove_debug_logs = Some(enabled);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_remove_debug_logs = Some(enabled);
+        Ok(())
+    })
+}
+
+#[command]
+pub fn set_export_format_setting(
+    app: AppHandle,
+    path: String,
+    format: models::ExportFormat,
+) -> Result<(), String> {
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_format = Some(format);
+        Ok(())
+    })
 }
 
 #[command]
@@ -119,9 +129,26 @@ pub fn set_export_super_compressed_setting(
     path: String,
     enabled: bool,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.export_super_compressed = Some(enabled);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_super_compressed = Some(enabled);
+        Ok(())
+    })
+}
+
+#[command]
+pub fn set_export_outline_setting(app: AppHandle, path: String, enabled: bool) -> Result<(), String> {
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_outline = Some(enabled);
+        Ok(())
+    })
+}
+
+#[command]
+pub fn set_export_repo_map_setting(app: AppHandle, path: String, enabled: bool) -> Result<(), String> {
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_repo_map = Some(enabled);
+        Ok(())
+    })
 }
 
 #[command]
@@ -130,11 +157,56 @@ pub fn set_export_exclude_extensions_setting(
     path: String,
     extensions: Vec<String>,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.export_exclude_extensions = Some(extensions);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_exclude_extensions = Some(extensions);
+        Ok(())
+    })
 }
 
+#[command]
+pub fn set_secret_redaction_setting(
+    app: AppHandle,
+    path: String,
+    enabled: bool,
+    patterns: Vec<String>,
+) -> Result<(), String> {
+    // Kiểm tra các biểu thức chính quy trước khi lưu
+    SecretRedactor::new(&patterns)?;
+    let patterns: Vec<String> = patterns
+        .into_iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_redact_secrets = Some(enabled);
+        project_data.secret_patterns = if patterns.is_empty() { None } else { Some(patterns) };
+        Ok(())
+    })
+}
+
+#[command]
+pub fn set_export_chunk_token_limit_setting(
+    app: AppHandle,
+    path: String,
+    limit: Option<usize>,
+) -> Result<(), String> {
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_chunk_token_limit = limit.filter(|l| *l > 0);
+        Ok(())
+    })
+}
+
+#[command]
+pub fn set_export_template_setting(
+    app: AppHandle,
+    path: String,
+    template_path: Option<String>,
+) -> Result<(), String> {
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.export_template_path = template_path.filter(|p| !p.trim().is_empty());
+        Ok(())
+    })
+}
 
 #[command]
 pub fn set_always_apply_text_setting(
@@ -142,20 +214,18 @@ pub fn set_always_apply_text_setting(
     path: String,
     text: String,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.always_apply_text = Some(text);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.always_apply_text = Some(text);
+        Ok(())
+    })
 }
 
 #[command]
 pub fn set_recent_paths(app: AppHandle, paths: Vec<String>) -> Result<(), String> {
-    let settings_path = get_app_settings_path(&app)?;
-    let mut settings = get_app_settings(app).unwrap_or_default();
+    let storage_root = storage_root(&app)?;
+    let mut settings = file_cache::load_app_settings(&storage_root).unwrap_or_default();
     settings.recent_paths = paths;
-    let json_string = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    let mut file = File::create(settings_path).map_err(|e| e.to_string())?;
-    file.write_all(json_string.as_bytes())
-        .map_err(|e| e.to_string())
+    file_cache::save_app_settings(&storage_root, &settings)
 }
 
 #[command]
@@ -164,19 +234,15 @@ pub fn set_git_export_mode_setting(
     path: String,
     enabled: bool,
 ) -> Result<(), String> {
-    let mut project_data = file_cache::load_project_data(&app, &path)?;
-    project_data.git_export_mode_is_context = Some(enabled);
-    file_cache::save_project_data(&app, &path, &project_data)
+    file_cache::update_project_data(&storage_root(&app)?, &path, |project_data| {
+        project_data.git_export_mode_is_context = Some(enabled);
+        Ok(())
+    })
 }
 
 #[command]
 pub fn update_app_settings(app: AppHandle, settings: models::AppSettings) -> Result<(), String> {
-    let settings_path = get_app_settings_path(&app)?;
-    let json_string = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
-    let mut file = File::create(settings_path).map_err(|e| e.to_string())?;
-    file.write_all(json_string.as_bytes())
-        .map_err(|e| e.to_string())?;
-    Ok(())
+    file_cache::save_app_settings(&storage_root(&app)?, &settings)
 }
 
 // src-tauri/src/commands/settings_commands.rs
\ No newline at end of file
@@ -1,7 +1,83 @@
 // src-tauri/src/commands/utils.rs
-use crate::{context_generator, models};
+use crate::context_generator::{ExportOptions, TokenBudget};
+use crate::context_template::ContextTemplate;
+use crate::progress::ProgressReporter;
+use crate::secret_redactor::SecretRedactor;
+use crate::{context_generator, file_cache, file_writer, git_utils, models, path_guard, symbol_index};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, Runtime, Window};
+
+const REDACTION_REPORT_FILENAME: &str = "_REDACTION_REPORT.txt";
+
+/// Thư mục lưu dữ liệu của ứng dụng (project_data.json, app_settings.json, ...).
+pub fn storage_root(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map_err(|e| format!("Không thể xác định thư mục cấu hình ứng dụng: {}", e))
+}
+
+// Chuyển tiếp các sự kiện tiến trình của lõi xử lý thành event gửi về frontend
+// Tiến trình chỉ gửi tới cửa sổ đã yêu cầu, để nhiều cửa sổ dự án không nhận nhầm của nhau
+impl<R: Runtime> ProgressReporter for Window<R> {
+    fn report(&self, event: &str, payload: &str) {
+        let _ = self.emit_to(self.label(), event, payload);
+    }
+}
+
+/// Đường dẫn tuyệt đối của một file trong dự án, theo các mẫu loại trừ của dự án.
+/// Trả về `path.outside_project` hoặc `path.ignored` nếu đường dẫn không được phép truy cập.
+pub fn resolve_project_file(
+    storage_root: &Path,
+    root_path_str: &str,
+    file_rel_path: &str,
+) -> Result<PathBuf, String> {
+    let project_data = file_cache::load_project_data(storage_root, root_path_str).unwrap_or_default();
+    path_guard::resolve_project_path(
+        Path::new(root_path_str),
+        file_rel_path,
+        project_data.custom_ignore_patterns.as_deref(),
+    )
+}
+
+/// Đọc một file trong dự án để gửi cho AI, chỉ lấy các dòng `start_line..=end_line`
+/// (bắt đầu từ 1) nếu có. Bí mật được che theo cài đặt xuất của dự án.
+pub fn read_project_file_lines(
+    storage_root: &Path,
+    root_path_str: &str,
+    file_rel_path: &str,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+) -> Result<String, String> {
+    let full_path = resolve_project_file(storage_root, root_path_str, file_rel_path)?;
+    let (content, _) = file_writer::read_text(&full_path)
+        .map_err(|e| format!("{} ('{}')", e, file_rel_path))?;
+    // Che trên toàn bộ file trước khi cắt dòng để khóa nhiều dòng vẫn được nhận diện
+    let project_data = file_cache::load_project_data(storage_root, root_path_str).unwrap_or_default();
+    let content = match SecretRedactor::from_project_data(&project_data) {
+        Some(redactor) => redactor.redact(&content, file_rel_path),
+        None => content,
+    };
+
+    if start_line.is_none() && end_line.is_none() {
+        return Ok(content);
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let total_lines = lines.len();
+
+    // Line numbers from AI are 1-based, convert to 0-based index
+    let start_index = start_line.map_or(0, |n| n.saturating_sub(1));
+    let end_index = end_line.map_or(total_lines, |n| n).min(total_lines);
+
+    if start_index >= end_index {
+        return Ok("".to_string());
+    }
+
+    Ok(lines[start_index..end_index].join("\n"))
+}
 
 pub fn sanitize_group_name(name: &str) -> String {
     name.replace(|c: char| !c.is_alphanumeric(), "_")
@@ -15,59 +91,354 @@ fn save_context_to_path_internal(path: String, content: String) -> Result<(), St
     fs::write(file_path, content).map_err(|e| format!("Không thể ghi vào file: {}", e))
 }
 
-pub fn perform_auto_export(project_path: &str, data: &models::CachedProjectData) {
-    let sync_path_base = PathBuf::from(data.sync_path.as_ref().unwrap());
-    let use_full_tree = data.export_use_full_tree.unwrap_or(false);
-    let with_line_numbers = data.export_with_line_numbers.unwrap_or(true);
-    let without_comments = data.export_without_comments.unwrap_or(false);
-    let remove_debug_logs = data.export_remove_debug_logs.unwrap_or(false);
-    let super_compressed = data.export_super_compressed.unwrap_or(false);
-    let always_apply_text = &data.always_apply_text;
-    let exclude_extensions = &data.export_exclude_extensions;
+/// Nạp template (của nhóm nếu có, nếu không thì của dự án) và các biến đi kèm vào `options`.
+pub fn apply_template(
+    options: &mut ExportOptions,
+    project_path: &str,
+    data: &models::CachedProjectData,
+    group: Option<&models::Group>,
+) -> Result<(), String> {
+    let template_path = group
+        .and_then(|g| g.template_path.as_deref())
+        .or(data.export_template_path.as_deref())
+        .filter(|p| !p.trim().is_empty());
+    let Some(template_path) = template_path else {
+        return Ok(());
+    };
+
+    let full_path = Path::new(project_path).join(template_path);
+    let source = fs::read_to_string(&full_path)
+        .map_err(|e| format!("Không thể đọc template '{}': {}", full_path.display(), e))?;
+    let template = ContextTemplate::parse(&source)
+        .map_err(|e| format!("Template '{}' không hợp lệ: {}", template_path, e))?;
+
+    let vars = &mut options.template_vars;
+    let project_name = Path::new(project_path)
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    vars.insert("project.name".to_string(), project_name);
+    vars.insert("project.path".to_string(), project_path.to_string());
+    if let Some(group) = group {
+        vars.insert("group.id".to_string(), group.id.clone());
+        vars.insert("group.name".to_string(), group.name.clone());
+    }
+    if let Some(branch) = git_utils::current_branch(project_path) {
+        vars.insert("git.branch".to_string(), branch);
+    }
+    if let Some(sha) = git_utils::head_commit_sha(project_path) {
+        vars.insert("git.commit".to_string(), sha);
+    }
+    options.template = Some(Arc::new(template));
+    Ok(())
+}
+
+// Cài đặt xuất toàn dự án: luôn dùng cây thư mục đầy đủ và template của dự án
+fn project_export_options(
+    project_path: &str,
+    data: &models::CachedProjectData,
+) -> Result<ExportOptions, String> {
+    let mut options = ExportOptions {
+        use_full_tree: true,
+        ..ExportOptions::from_project_data(data)
+    };
+    apply_template(&mut options, project_path, data, None)?;
+    Ok(options)
+}
+
+/// Báo cáo các bí mật đã bị che khi tạo ngữ cảnh với `options` (rỗng nếu tắt tính năng).
+pub fn take_redaction_report(options: &ExportOptions) -> models::RedactionReport {
+    options
+        .redactor
+        .as_ref()
+        .map(|redactor| redactor.take_report())
+        .unwrap_or_default()
+}
+
+/// Tạo ngữ cảnh toàn dự án theo các cài đặt xuất đã lưu (luôn dùng cây thư mục đầy đủ),
+/// kèm báo cáo các bí mật đã bị che.
+pub fn build_project_context(
+    project_path: &str,
+    data: &models::CachedProjectData,
+) -> Result<(String, models::RedactionReport), String> {
     let all_files: Vec<String> = data.file_metadata_cache.keys().cloned().collect();
+    if all_files.is_empty() {
+        return Err("project.export_no_files".to_string());
+    }
+    let options = project_export_options(project_path, data)?;
+    let context = context_generator::generate_context_from_files(
+        project_path,
+        &all_files,
+        &data.file_tree,
+        &options,
+        &data.file_metadata_cache,
+    )?;
+    Ok((context, take_redaction_report(&options)))
+}
 
-    if let Ok(proj_context) = context_generator::generate_context_from_files(
+/// Đo ngữ cảnh toàn dự án đúng như khi xuất (xem `context_generator::measure_context`).
+pub fn measure_project_context(
+    project_path: &str,
+    data: &models::CachedProjectData,
+) -> Result<models::ContextMeasurement, String> {
+    let all_files: Vec<String> = data.file_metadata_cache.keys().cloned().collect();
+    if all_files.is_empty() {
+        return Err("project.export_no_files".to_string());
+    }
+    let options = project_export_options(project_path, data)?;
+    context_generator::measure_context(
         project_path,
         &all_files,
-        use_full_tree,
         &data.file_tree,
-        with_line_numbers,
-        without_comments,
-        remove_debug_logs,
-        super_compressed,
-        always_apply_text,
-        exclude_extensions,
+        &options,
         &data.file_metadata_cache,
-    ) {
-        let file_name = sync_path_base.join("_PROJECT_CONTEXT.txt");
-        let _ =
-            save_context_to_path_internal(file_name.to_string_lossy().to_string(), proj_context);
+        None,
+    )
+}
+
+/// Giống `build_project_context` nhưng chia thành các phần tối đa `tokens_per_part` token.
+pub fn build_project_context_chunks(
+    project_path: &str,
+    data: &models::CachedProjectData,
+    tokens_per_part: usize,
+) -> Result<(Vec<String>, models::RedactionReport), String> {
+    let all_files: Vec<String> = data.file_metadata_cache.keys().cloned().collect();
+    if all_files.is_empty() {
+        return Err("project.export_no_files".to_string());
     }
+    let options = project_export_options(project_path, data)?;
+    let parts = context_generator::generate_context_chunks(
+        project_path,
+        &all_files,
+        &data.file_tree,
+        &options,
+        &data.file_metadata_cache,
+        tokens_per_part,
+    )?;
+    Ok((parts, take_redaction_report(&options)))
+}
 
-    for group in &data.groups {
-        let expanded_files = context_generator::expand_group_paths_to_files(
-            &group.paths,
-            &data.file_metadata_cache,
-            Path::new(project_path),
-        );
-        if !expanded_files.is_empty() {
-            if let Ok(group_context) = context_generator::generate_context_from_files(
+/// Tên file của phần thứ `index` (bắt đầu từ 1), ví dụ `_PROJECT_CONTEXT.part01.txt`.
+pub fn chunk_file_name(stem: &str, extension: &str, index: usize) -> String {
+    format!("{}.part{:02}.{}", stem, index, extension)
+}
+
+/// Tạo ngữ cảnh cho một nhóm theo các cài đặt xuất đã lưu của dự án.
+/// Nếu nhóm có giới hạn token, ngữ cảnh được thu gọn cho vừa và kèm báo cáo;
+/// luôn kèm báo cáo các bí mật đã bị che.
+pub fn build_group_context(
+    project_path: &str,
+    data: &models::CachedProjectData,
+    group: &models::Group,
+) -> Result<(String, Option<models::TokenBudgetReport>, models::RedactionReport), String> {
+    let options = group_export_options(project_path, data, group)?;
+    let (context, budget) = build_group_context_with_options(project_path, data, group, &options)?;
+    Ok((context, budget, take_redaction_report(&options)))
+}
+
+/// Đo ngữ cảnh của nhóm đúng như khi xuất, kể cả giới hạn token của nhóm.
+pub fn measure_group_context(
+    project_path: &str,
+    data: &models::CachedProjectData,
+    group: &models::Group,
+) -> Result<models::ContextMeasurement, String> {
+    let options = group_export_options(project_path, data, group)?;
+    let (files, budget) = group_files_and_budget(project_path, data, group)?;
+    context_generator::measure_context(
+        project_path,
+        &files,
+        &data.file_tree,
+        &options,
+        &data.file_metadata_cache,
+        budget,
+    )
+}
+
+fn group_export_options(
+    project_path: &str,
+    data: &models::CachedProjectData,
+    group: &models::Group,
+) -> Result<ExportOptions, String> {
+    let mut options = ExportOptions {
+        outline: group.outline.unwrap_or(data.export_outline.unwrap_or(false)),
+        repo_map: group_repo_map(project_path, data, group),
+        ..ExportOptions::from_project_data(data)
+    };
+    apply_template(&mut options, project_path, data, Some(group))?;
+    Ok(options)
+}
+
+/// Làm mới chỉ mục ký hiệu của dự án, trả về `true` nếu chỉ mục thay đổi (cần lưu lại).
+pub fn refresh_symbol_index(project_path: &str, data: &mut models::CachedProjectData) -> bool {
+    let index = symbol_index::refresh_index(
+        Path::new(project_path),
+        &data.file_metadata_cache,
+        data.symbol_index.as_ref(),
+    );
+    let changed = data.symbol_index.as_ref() != Some(&index);
+    data.symbol_index = Some(index);
+    changed
+}
+
+/// Nạp dữ liệu dự án, làm mới chỉ mục ký hiệu và lưu lại nếu có thay đổi.
+pub fn load_symbol_index(
+    storage_root: &Path,
+    project_path: &str,
+) -> Result<BTreeMap<String, models::FileSymbols>, String> {
+    let _lock = file_cache::lock_project_data(storage_root, project_path)?;
+    let mut data = file_cache::load_project_data(storage_root, project_path)?;
+    if refresh_symbol_index(project_path, &mut data) {
+        file_cache::save_project_data(storage_root, project_path, &data)?;
+    }
+    Ok(data.symbol_index.unwrap_or_default())
+}
+
+/// Bản đồ ký hiệu của các file nằm ngoài nhóm, nếu dự án bật tùy chọn chèn repo map.
+pub fn group_repo_map(
+    project_path: &str,
+    data: &models::CachedProjectData,
+    group: &models::Group,
+) -> Option<String> {
+    if !data.export_repo_map.unwrap_or(false) {
+        return None;
+    }
+    let index = symbol_index::refresh_index(
+        Path::new(project_path),
+        &data.file_metadata_cache,
+        data.symbol_index.as_ref(),
+    );
+    let group_files = context_generator::expand_group_paths_to_files(
+        &group.paths,
+        &data.file_metadata_cache,
+        Path::new(project_path),
+    );
+    let skip_paths: HashSet<&str> = group_files.iter().map(String::as_str).collect();
+    Some(symbol_index::render_repo_map(&index, &skip_paths))
+}
+
+pub fn build_group_context_with_options(
+    project_path: &str,
+    data: &models::CachedProjectData,
+    group: &models::Group,
+    options: &ExportOptions,
+) -> Result<(String, Option<models::TokenBudgetReport>), String> {
+    let (expanded_files, budget) = group_files_and_budget(project_path, data, group)?;
+    context_generator::generate_context_with_budget(
+        project_path,
+        &expanded_files,
+        &data.file_tree,
+        options,
+        &data.file_metadata_cache,
+        budget,
+    )
+}
+
+// Các file của nhóm và giới hạn token (nếu có) áp dụng khi xuất
+fn group_files_and_budget<'a>(
+    project_path: &str,
+    data: &models::CachedProjectData,
+    group: &'a models::Group,
+) -> Result<(Vec<String>, Option<TokenBudget<'a>>), String> {
+    let expanded_files = context_generator::expand_group_paths_to_files(
+        &group.paths,
+        &data.file_metadata_cache,
+        Path::new(project_path),
+    );
+    if expanded_files.is_empty() {
+        return Err("group.export_no_files".to_string());
+    }
+    let budget = group.token_limit.map(|token_limit| TokenBudget {
+        token_limit,
+        priority_paths: &group.paths,
+    });
+    Ok((expanded_files, budget))
+}
+
+/// Định dạng báo cáo che bí mật thành văn bản, mỗi bí mật một dòng `đường_dẫn:dòng  luật`.
+pub fn format_redaction_report(report: &models::RedactionReport) -> String {
+    report
+        .secrets
+        .iter()
+        .map(|secret| format!("{}:{}\t{}\n", secret.path, secret.line, secret.rule))
+        .collect()
+}
+
+fn remove_project_context_parts(sync_path_base: &Path) {
+    if let Ok(entries) = fs::read_dir(sync_path_base) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("_PROJECT_CONTEXT.part") && name.ends_with(".txt") {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+}
+
+pub fn perform_auto_export(project_path: &str, data: &models::CachedProjectData) {
+    let sync_path_base = PathBuf::from(data.sync_path.as_ref().unwrap());
+    let mut options = ExportOptions::from_project_data(data);
+    if let Err(e) = apply_template(&mut options, project_path, data, None) {
+        println!("[Error] Auto-export template: {}", e);
+    }
+    let all_files: Vec<String> = data.file_metadata_cache.keys().cloned().collect();
+
+    // Xóa các phần cũ để không còn sót lại khi số phần giảm hoặc tắt chế độ chia nhỏ
+    remove_project_context_parts(&sync_path_base);
+
+    match data.export_chunk_token_limit.filter(|limit| *limit > 0) {
+        Some(tokens_per_part) => {
+            if let Ok(parts) = context_generator::generate_context_chunks(
+                project_path,
+                &all_files,
+                &data.file_tree,
+                &options,
+                &data.file_metadata_cache,
+                tokens_per_part,
+            ) {
+                let _ = fs::remove_file(sync_path_base.join("_PROJECT_CONTEXT.txt"));
+                for (i, part) in parts.into_iter().enumerate() {
+                    let file_name = sync_path_base.join(chunk_file_name("_PROJECT_CONTEXT", "txt", i + 1));
+                    let _ = save_context_to_path_internal(file_name.to_string_lossy().to_string(), part);
+                }
+            }
+        }
+        None => {
+            if let Ok(proj_context) = context_generator::generate_context_from_files(
                 project_path,
-                &expanded_files,
-                use_full_tree,
+                &all_files,
                 &data.file_tree,
-                with_line_numbers,
-                without_comments,
-                remove_debug_logs,
-                super_compressed,
-                always_apply_text,
-                exclude_extensions,
+                &options,
                 &data.file_metadata_cache,
             ) {
-                let safe_name = sanitize_group_name(&group.name);
-                let file_name = sync_path_base.join(format!("{}_context.txt", safe_name));
-                let _ = save_context_to_path_internal(file_name.to_string_lossy().to_string(), group_context);
+                let file_name = sync_path_base.join("_PROJECT_CONTEXT.txt");
+                let _ = save_context_to_path_internal(
+                    file_name.to_string_lossy().to_string(),
+                    proj_context,
+                );
             }
         }
     }
-}
\ No newline at end of file
+
+    let mut redactions = take_redaction_report(&options);
+    for group in &data.groups {
+        if let Ok((group_context, _, group_redactions)) = build_group_context(project_path, data, group) {
+            let safe_name = sanitize_group_name(&group.name);
+            let file_name = sync_path_base.join(format!("{}_context.txt", safe_name));
+            let _ = save_context_to_path_internal(file_name.to_string_lossy().to_string(), group_context);
+            redactions.secrets.extend(group_redactions.secrets);
+        }
+    }
+
+    // Báo cáo các bí mật đã che nằm cạnh các file ngữ cảnh, chỉ khi có bí mật bị che
+    let report_path = sync_path_base.join(REDACTION_REPORT_FILENAME);
+    if redactions.secrets.is_empty() {
+        let _ = fs::remove_file(report_path);
+    } else {
+        redactions.secrets.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+        redactions.secrets.dedup();
+        let _ = save_context_to_path_internal(
+            report_path.to_string_lossy().to_string(),
+            format_redaction_report(&redactions),
+        );
+    }
+}
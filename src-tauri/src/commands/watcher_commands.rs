@@ -1,34 +1,83 @@
 // src-tauri/src/commands/watcher_commands.rs
+use crate::models::ActiveWatcher;
+use crate::{file_cache, project_scanner};
 use lazy_static::lazy_static;
 use notify::{RecursiveMode, RecommendedWatcher, Watcher};
-use std::sync::Mutex;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::{Duration, Instant};
-use std::path::Path;
-use tauri::{command, Emitter, Window};
+use tauri::{command, AppHandle, Emitter, Manager, Window};
+use super::utils::{perform_auto_export, storage_root};
+
+// Khoảng yên lặng mặc định trước khi xử lý một loạt thay đổi
+const DEFAULT_DEBOUNCE_MS: u64 = 1000;
+// Khi thay đổi liên tục không dứt, vẫn xử lý sau tối đa chừng này lần khoảng yên lặng
+const MAX_WAIT_FACTOR: u32 = 10;
+
+// Mỗi dự án chỉ có một watcher; thay đổi được xử lý một lần rồi gửi tới mọi cửa sổ đăng ký
+struct ProjectWatcher {
+    _watcher: RecommendedWatcher,
+    windows: Arc<Mutex<BTreeSet<String>>>,
+    // Cài đặt watcher đang dùng; khác với cài đặt đã lưu thì watcher được tạo lại
+    debounce: Duration,
+    custom_ignore_patterns: Option<Vec<String>>,
+}
 
 lazy_static! {
-    static ref FILE_WATCHER: Mutex<Option<RecommendedWatcher>> = Mutex::new(None);
-    static ref LAST_CHANGE_TIME: Mutex<Option<Instant>> = Mutex::new(None);
+    // Khóa: đường dẫn dự án
+    static ref FILE_WATCHERS: Mutex<BTreeMap<String, ProjectWatcher>> = Mutex::new(BTreeMap::new());
 }
 
 #[command]
 pub fn start_file_watching(window: Window, path: String) -> Result<(), String> {
-    stop_file_watching()?;
+    let window_label = window.label().to_string();
+    let mut watchers = FILE_WATCHERS.lock().unwrap();
+    // Mỗi cửa sổ chỉ hiển thị một dự án: hủy đăng ký khỏi các dự án khác của cửa sổ này
+    unsubscribe(&mut watchers, &window_label, |project_path| project_path != path);
+
+    let app = window.app_handle().clone();
+    let project_data = file_cache::load_project_data(&storage_root(&app)?, &path).unwrap_or_default();
+    let debounce = Duration::from_millis(project_data.watcher_debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS));
+    let custom_ignore_patterns = project_data.custom_ignore_patterns;
+    // Watcher đang chạy với đúng cài đặt thì chỉ cần đăng ký thêm cửa sổ; nếu cài đặt đã đổi
+    // thì tạo lại watcher và giữ nguyên các cửa sổ đã đăng ký
+    let windows = match watchers.remove(&path) {
+        Some(existing)
+            if existing.debounce == debounce && existing.custom_ignore_patterns == custom_ignore_patterns =>
+        {
+            existing.windows.lock().unwrap().insert(window_label.clone());
+            watchers.insert(path.clone(), existing);
+            println!("[Watcher] Window {} subscribed to: {}", window_label, path);
+            return Ok(());
+        }
+        Some(existing) => {
+            println!("[Watcher] Settings changed, restarting watcher: {}", path);
+            existing.windows
+        }
+        None => Arc::new(Mutex::new(BTreeSet::new())),
+    };
+    windows.lock().unwrap().insert(window_label.clone());
 
-    let window_clone = window.clone();
-    let debounce_duration = Duration::from_secs(2);
+    // Dùng cùng quy tắc .gitignore và mẫu loại trừ tùy chỉnh với scanner để bỏ qua
+    // node_modules, target, .git... Đổi mẫu tùy chỉnh sẽ quét lại dự án, lần quét đó gọi lại
+    // hàm này và watcher được tạo lại với bộ lọc mới.
+    let mut filter = project_scanner::PathFilter::new(Path::new(&path), custom_ignore_patterns.as_deref())?;
 
+    // Watcher chỉ chuyển đường dẫn thay đổi sang luồng xử lý; luồng này tự kết thúc
+    // khi watcher bị hủy (sender bị drop cùng closure)
+    let (tx, rx) = mpsc::channel::<Vec<PathBuf>>();
     let mut watcher = notify::recommended_watcher(move |res: Result<notify::Event, notify::Error>| {
         match res {
             Ok(event) => {
                 if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() {
-                    let mut last_change = LAST_CHANGE_TIME.lock().unwrap();
-                    let now = Instant::now();
-
-                    if last_change.is_none() || now.duration_since(last_change.unwrap()) > debounce_duration {
-                        println!("[Watcher] Detected change: {:?}, triggering rescan.", event.paths);
-                        let _ = window_clone.emit("file_change_detected", ());
-                        *last_change = Some(now);
+                    let paths: Vec<PathBuf> = event
+                        .paths
+                        .into_iter()
+                        .filter(|p| filter.is_relevant_change(p))
+                        .collect();
+                    if !paths.is_empty() {
+                        let _ = tx.send(paths);
                     }
                 }
             }
@@ -41,16 +90,267 @@ pub fn start_file_watching(window: Window, path: String) -> Result<(), String> {
         .watch(Path::new(&path), RecursiveMode::Recursive)
         .map_err(|e| format!("Không thể bắt đầu theo dõi thư mục: {}", e))?;
 
-    *FILE_WATCHER.lock().unwrap() = Some(watcher);
-    println!("[Watcher] Started watching path: {}", path);
+    let project_path = path.clone();
+    let subscribers = Arc::clone(&windows);
+    std::thread::spawn(move || {
+        while let Ok(first) = rx.recv() {
+            let changed_paths = collect_batch(&rx, first, debounce, debounce * MAX_WAIT_FACTOR);
+            let window_labels: Vec<String> = subscribers.lock().unwrap().iter().cloned().collect();
+            if let Err(e) = process_changes(&app, &window_labels, &project_path, &changed_paths) {
+                println!("[Watcher] Incremental update failed: {}", e);
+            }
+        }
+    });
+
+    println!("[Watcher] Started watching path: {} (window {})", path, window_label);
+    watchers.insert(path, ProjectWatcher { _watcher: watcher, windows, debounce, custom_ignore_patterns });
     Ok(())
 }
 
-#[command]
-pub fn stop_file_watching() -> Result<(), String> {
-    if let Some(watcher) = FILE_WATCHER.lock().unwrap().take() {
-        drop(watcher);
-        println!("[Watcher] Stopped watching.");
+// Hủy đăng ký cửa sổ khỏi các dự án thỏa `matches`; watcher không còn cửa sổ nào thì bị dừng
+fn unsubscribe(
+    watchers: &mut BTreeMap<String, ProjectWatcher>,
+    window_label: &str,
+    matches: impl Fn(&str) -> bool,
+) {
+    watchers.retain(|project_path, watcher| {
+        if !matches(project_path) {
+            return true;
+        }
+        let mut windows = watcher.windows.lock().unwrap();
+        windows.remove(window_label);
+        if windows.is_empty() {
+            println!("[Watcher] Stopped watching: {}", project_path);
+        }
+        !windows.is_empty()
+    });
+}
+
+// Debounce cạnh sau: gom mọi đường dẫn cho tới khi không có sự kiện mới trong `quiet`
+// (hoặc đã chờ quá `max_wait`), nhờ vậy thay đổi ở cuối một loạt lưu file / git checkout
+// không bị bỏ sót
+fn collect_batch(
+    rx: &mpsc::Receiver<Vec<PathBuf>>,
+    first: Vec<PathBuf>,
+    quiet: Duration,
+    max_wait: Duration,
+) -> Vec<PathBuf> {
+    let started = Instant::now();
+    collect_batch_with(first, quiet, max_wait, || started.elapsed(), |timeout| {
+        rx.recv_timeout(timeout).ok()
+    })
+}
+
+// Phần logic của `collect_batch`, tách đồng hồ (`elapsed`) và nguồn sự kiện (`recv`)
+// ra ngoài để test chạy trên thời gian giả lập thay vì sleep thật
+fn collect_batch_with(
+    first: Vec<PathBuf>,
+    quiet: Duration,
+    max_wait: Duration,
+    elapsed: impl Fn() -> Duration,
+    mut recv: impl FnMut(Duration) -> Option<Vec<PathBuf>>,
+) -> Vec<PathBuf> {
+    let mut changed_paths: BTreeSet<PathBuf> = first.into_iter().collect();
+    loop {
+        let remaining = max_wait.saturating_sub(elapsed());
+        if remaining.is_zero() {
+            break;
+        }
+        match recv(quiet.min(remaining)) {
+            Some(more) => changed_paths.extend(more),
+            // Yên lặng đủ lâu, hoặc watcher đã bị hủy: xử lý những gì đã gom được
+            None => break,
+        }
+    }
+    changed_paths.into_iter().collect()
+}
+
+// Cập nhật tăng dần dữ liệu dự án và gửi diff tới các cửa sổ đang mở dự án;
+// chỉ quét lại toàn bộ khi cần
+fn process_changes(
+    app: &AppHandle,
+    window_labels: &[String],
+    path: &str,
+    changed_paths: &[PathBuf],
+) -> Result<(), String> {
+    let storage_root = storage_root(app)?;
+    // Các lệnh từ giao diện (sửa nhóm, cài đặt) chờ tới khi cập nhật này được lưu xong
+    let lock = file_cache::lock_project_data(&storage_root, path)?;
+    let mut project_data = file_cache::load_project_data(&storage_root, path)?;
+    let app_settings = file_cache::load_app_settings(&storage_root).unwrap_or_default();
+    let diff = project_scanner::apply_incremental_update(
+        path,
+        &mut project_data,
+        changed_paths,
+        project_scanner::ScanOptions {
+            user_non_analyzable_extensions: app_settings.non_analyzable_extensions,
+            tokenizer: app_settings.tokenizer.unwrap_or_default(),
+        },
+    )?;
+    let Some(diff) = diff else {
+        println!("[Watcher] Detected change: {:?}, triggering rescan.", changed_paths);
+        for label in window_labels {
+            let _ = app.emit_to(label.as_str(), "file_change_detected", ());
+        }
+        return Ok(());
+    };
+    if diff.is_empty() && diff.touched.is_empty() {
+        return Ok(());
+    }
+    file_cache::save_project_data(&storage_root, path, &project_data)?;
+    drop(lock);
+    // Chỉ mtime trong cache thay đổi: đã lưu, không có gì cần báo cho giao diện
+    if diff.is_empty() {
+        return Ok(());
+    }
+    println!(
+        "[Watcher] Applied changes: +{} -{} ~{}",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.modified.len()
+    );
+
+    if project_data.sync_enabled.unwrap_or(false) && project_data.sync_path.is_some() {
+        perform_auto_export(path, &project_data);
+    }
+
+    let metadata: BTreeMap<_, _> = diff
+        .added
+        .iter()
+        .chain(&diff.modified)
+        .filter_map(|p| project_data.file_metadata_cache.get(p).map(|m| (p, m)))
+        .collect();
+    let groups: Vec<_> = project_data
+        .groups
+        .iter()
+        .map(|g| serde_json::json!({ "id": g.id, "paths": g.paths, "stats": g.stats }))
+        .collect();
+    let payload = serde_json::json!({
+        "diff": diff,
+        "metadata": metadata,
+        "stats": project_data.stats,
+        "groups": groups,
+    });
+    for label in window_labels {
+        let _ = app.emit_to(label.as_str(), "project_files_changed", &payload);
     }
     Ok(())
-}
\ No newline at end of file
+}
+
+// Hủy đăng ký cửa sổ gọi lệnh; chỉ với dự án `path` nếu được truyền vào
+#[command]
+pub fn stop_file_watching(window: Window, path: Option<String>) -> Result<(), String> {
+    let mut watchers = FILE_WATCHERS.lock().unwrap();
+    unsubscribe(&mut watchers, window.label(), |project_path| {
+        path.as_deref().is_none_or(|p| p == project_path)
+    });
+    Ok(())
+}
+
+/// Hủy đăng ký một cửa sổ khỏi mọi dự án (khi cửa sổ bị đóng).
+pub fn stop_window_watchers(window_label: &str) {
+    unsubscribe(&mut FILE_WATCHERS.lock().unwrap(), window_label, |_| true);
+}
+
+#[command]
+pub fn list_active_watchers() -> Vec<ActiveWatcher> {
+    FILE_WATCHERS
+        .lock()
+        .unwrap()
+        .iter()
+        .flat_map(|(project_path, watcher)| {
+            watcher
+                .windows
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|window_label| ActiveWatcher {
+                    project_path: project_path.clone(),
+                    window_label: window_label.clone(),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::collections::VecDeque;
+
+    // Nguồn sự kiện giả lập: mỗi sự kiện đến ở một thời điểm (ms) cố định trên đồng hồ ảo
+    struct FakeEvents {
+        now: Cell<Duration>,
+        queue: RefCell<VecDeque<(Duration, Vec<PathBuf>)>>,
+    }
+
+    impl FakeEvents {
+        fn new(events: Vec<(u64, &str)>) -> Self {
+            FakeEvents {
+                now: Cell::new(Duration::ZERO),
+                queue: RefCell::new(
+                    events
+                        .into_iter()
+                        .map(|(ms, name)| (Duration::from_millis(ms), vec![PathBuf::from(name)]))
+                        .collect(),
+                ),
+            }
+        }
+
+        fn recv(&self, timeout: Duration) -> Option<Vec<PathBuf>> {
+            let deadline = self.now.get() + timeout;
+            let mut queue = self.queue.borrow_mut();
+            match queue.front() {
+                Some((at, _)) if *at <= deadline => {
+                    let (at, paths) = queue.pop_front().unwrap();
+                    self.now.set(self.now.get().max(at));
+                    Some(paths)
+                }
+                _ => {
+                    self.now.set(deadline);
+                    None
+                }
+            }
+        }
+
+        fn collect(&self, first: Vec<PathBuf>, quiet: Duration, max_wait: Duration) -> Vec<PathBuf> {
+            let started = self.now.get();
+            collect_batch_with(first, quiet, max_wait, || self.now.get() - started, |t| self.recv(t))
+        }
+    }
+
+    #[test]
+    fn batch_waits_for_quiet_period_and_keeps_trailing_events() {
+        let events = FakeEvents::new(vec![(30, "b.rs"), (60, "a.rs"), (90, "c.rs"), (390, "late.rs")]);
+
+        let quiet = Duration::from_millis(150);
+        let batch = events.collect(vec![PathBuf::from("a.rs")], quiet, quiet * MAX_WAIT_FACTOR);
+        assert_eq!(batch, vec![PathBuf::from("a.rs"), PathBuf::from("b.rs"), PathBuf::from("c.rs")]);
+        // Batch được xử lý đúng sau khoảng yên lặng tính từ sự kiện cuối
+        assert_eq!(events.now.get(), Duration::from_millis(240));
+
+        let next = events.recv(Duration::from_secs(1)).unwrap();
+        assert_eq!(events.collect(next, quiet, quiet * MAX_WAIT_FACTOR), vec![PathBuf::from("late.rs")]);
+    }
+
+    #[test]
+    fn batch_is_flushed_after_max_wait_under_constant_changes() {
+        let names: Vec<String> = (1..=40).map(|i| format!("{:02}.rs", i)).collect();
+        let events = FakeEvents::new(names.iter().enumerate().map(|(i, n)| ((i as u64 + 1) * 10, n.as_str())).collect());
+
+        let batch = events.collect(Vec::new(), Duration::from_millis(100), Duration::from_millis(150));
+        assert_eq!(events.now.get(), Duration::from_millis(150));
+        assert_eq!(batch.len(), 15);
+        assert_eq!(batch.last(), Some(&PathBuf::from("15.rs")));
+    }
+
+    #[test]
+    fn batch_is_flushed_when_the_watcher_is_dropped() {
+        let (tx, rx) = mpsc::channel::<Vec<PathBuf>>();
+        tx.send(vec![PathBuf::from("a.rs")]).unwrap();
+        drop(tx);
+        let batch = collect_batch(&rx, Vec::new(), Duration::from_secs(60), Duration::from_secs(600));
+        assert_eq!(batch, vec![PathBuf::from("a.rs")]);
+    }
+}
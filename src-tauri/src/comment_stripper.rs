@@ -0,0 +1,1304 @@
+// src-tauri/src/comment_stripper.rs
+// Xóa comment bằng bộ phân tích từ vựng cho từng họ ngôn ngữ thay vì regex.
+// Bộ quét hiểu chuỗi (kể cả raw string, template literal, heredoc), ký tự và comment lồng nhau,
+// nên không làm hỏng URL trong chuỗi, `#[derive]`, màu hex CSS hay dòng shebang.
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Kết quả quét tại một vị trí (độ dài tính bằng byte).
+enum Token {
+    // Đoạn giữ nguyên: chuỗi, ký tự, heredoc...
+    Literal(usize),
+    Comment(usize),
+    Code,
+}
+
+type Scanner<'a> = &'a dyn Fn(&str, usize) -> Token;
+
+#[derive(Clone, Copy, PartialEq)]
+enum CLang {
+    C,
+    Cpp,
+    Rust,
+    Go,
+    Java,
+    CSharp,
+    Swift,
+    Kotlin,
+    Js,
+    Dart,
+    Css,
+    Scss,
+    Jsonc,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HashLang {
+    Python,
+    Ruby,
+    Shell,
+    Yaml,
+    Toml,
+    Dockerfile,
+    GitIgnore,
+    R,
+    Perl,
+    PowerShell,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Markup {
+    Html,
+    Xml,
+    Markdown,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum DashLang {
+    Sql,
+    Lua,
+    Haskell,
+    Ada,
+}
+
+#[derive(Clone, Copy)]
+enum Syntax {
+    CLike(CLang),
+    Hash(HashLang),
+    Markup(Markup),
+    Dash(DashLang),
+    Lisp { elisp: bool },
+    Erlang,
+    Basic,
+    Php,
+    Component { astro: bool },
+}
+
+fn syntax_for_path(file_rel_path: &str) -> Option<Syntax> {
+    let file_name = Path::new(file_rel_path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match file_name.as_str() {
+        "dockerfile" => return Some(Syntax::Hash(HashLang::Dockerfile)),
+        ".gitignore" => return Some(Syntax::Hash(HashLang::GitIgnore)),
+        _ => {}
+    }
+    let extension = Path::new(&file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    let syntax = match extension {
+        "c" | "glsl" => Syntax::CLike(CLang::C),
+        "cpp" | "h" => Syntax::CLike(CLang::Cpp),
+        "rs" => Syntax::CLike(CLang::Rust),
+        "go" => Syntax::CLike(CLang::Go),
+        "java" => Syntax::CLike(CLang::Java),
+        "cs" => Syntax::CLike(CLang::CSharp),
+        "swift" => Syntax::CLike(CLang::Swift),
+        "kt" => Syntax::CLike(CLang::Kotlin),
+        "js" | "jsx" | "ts" | "tsx" => Syntax::CLike(CLang::Js),
+        "dart" => Syntax::CLike(CLang::Dart),
+        "css" => Syntax::CLike(CLang::Css),
+        "scss" | "less" => Syntax::CLike(CLang::Scss),
+        "jsonc" => Syntax::CLike(CLang::Jsonc),
+        // GDScript dùng `#` và chuỗi giống Python
+        "py" | "gd" => Syntax::Hash(HashLang::Python),
+        "rb" => Syntax::Hash(HashLang::Ruby),
+        "sh" => Syntax::Hash(HashLang::Shell),
+        "yml" | "yaml" => Syntax::Hash(HashLang::Yaml),
+        "toml" => Syntax::Hash(HashLang::Toml),
+        "dockerfile" => Syntax::Hash(HashLang::Dockerfile),
+        "gitignore" => Syntax::Hash(HashLang::GitIgnore),
+        "r" => Syntax::Hash(HashLang::R),
+        "pl" | "pm" => Syntax::Hash(HashLang::Perl),
+        "ps1" => Syntax::Hash(HashLang::PowerShell),
+        "html" => Syntax::Markup(Markup::Html),
+        "xml" | "svg" => Syntax::Markup(Markup::Xml),
+        "md" => Syntax::Markup(Markup::Markdown),
+        "sql" => Syntax::Dash(DashLang::Sql),
+        "lua" => Syntax::Dash(DashLang::Lua),
+        "hs" => Syntax::Dash(DashLang::Haskell),
+        "ada" => Syntax::Dash(DashLang::Ada),
+        "lisp" | "cl" | "scm" => Syntax::Lisp { elisp: false },
+        // Emacs Lisp dùng `;` chứ không phải `#`
+        "el" => Syntax::Lisp { elisp: true },
+        "erl" | "hrl" => Syntax::Erlang,
+        "vb" | "vbs" => Syntax::Basic,
+        "php" => Syntax::Php,
+        "vue" => Syntax::Component { astro: false },
+        "astro" => Syntax::Component { astro: true },
+        _ => return None,
+    };
+    Some(syntax)
+}
+
+/// Xóa comment khỏi nội dung file theo ngôn ngữ suy ra từ đường dẫn,
+/// sau đó bỏ các dòng trống (trừ những dòng nằm bên trong chuỗi nhiều dòng).
+pub fn strip_comments(content: &str, file_rel_path: &str) -> String {
+    let mut output = Output::default();
+    match syntax_for_path(file_rel_path) {
+        Some(Syntax::Component { astro }) => strip_component(&mut output, content, astro),
+        Some(syntax) => lex(&mut output, content, 0, content.len(), &|src, i| scan(syntax, src, i)),
+        None => output.push(content, false),
+    }
+    output.finish()
+}
+
+/// Đánh dấu từng byte của `content`: `true` nếu là code, `false` nếu nằm trong chuỗi hay comment.
+/// Trả về None với ngôn ngữ không được hỗ trợ.
+pub(crate) fn code_mask(content: &str, file_rel_path: &str) -> Option<Vec<bool>> {
+    let syntax = syntax_for_path(file_rel_path)
+        .filter(|syntax| !matches!(syntax, Syntax::Component { .. }))?;
+    let mut mask = vec![true; content.len()];
+    let mut i = 0;
+    while i < content.len() {
+        match scan(syntax, content, i) {
+            Token::Literal(len) | Token::Comment(len) if len > 0 => {
+                let end = (i + len).min(content.len());
+                mask[i..end].fill(false);
+                i = end;
+            }
+            _ => i += char_len(content, i),
+        }
+    }
+    Some(mask)
+}
+
+fn scan(syntax: Syntax, src: &str, i: usize) -> Token {
+    match syntax {
+        Syntax::CLike(lang) => scan_c_like(lang, src, i),
+        Syntax::Hash(lang) => scan_hash(lang, src, i),
+        Syntax::Markup(kind) => scan_markup(kind, src, i),
+        Syntax::Dash(lang) => scan_dash(lang, src, i),
+        Syntax::Lisp { elisp } => scan_lisp(elisp, src, i),
+        Syntax::Erlang => scan_erlang(src, i),
+        Syntax::Basic => scan_basic(src, i),
+        Syntax::Php => scan_php(src, i),
+        Syntax::Component { .. } => Token::Code,
+    }
+}
+
+// --- KẾT QUẢ ---
+
+#[derive(Default)]
+struct Output {
+    text: String,
+    line: usize,
+    // Các dòng có comment bị xóa (cần cắt khoảng trắng cuối dòng)
+    touched: HashSet<usize>,
+    // Các dòng kết thúc bên trong một literal nhiều dòng (không được xóa dù trống)
+    in_literal: HashSet<usize>,
+}
+
+impl Output {
+    fn push(&mut self, text: &str, literal: bool) {
+        for _ in text.matches('\n') {
+            if literal {
+                self.in_literal.insert(self.line);
+            }
+            self.line += 1;
+        }
+        self.text.push_str(text);
+    }
+
+    fn remove_comment(&mut self, next: Option<char>) {
+        self.touched.insert(self.line);
+        // Comment khối nằm giữa hai token được thay bằng một khoảng trắng: `a/* x */b` -> `a b`
+        let glued_before = self.text.chars().last().is_some_and(|c| !c.is_whitespace());
+        if glued_before && next.is_some_and(|c| !c.is_whitespace()) {
+            self.text.push(' ');
+        }
+    }
+
+    fn current_line_is_blank(&self) -> bool {
+        let line = self.text.rsplit('\n').next().unwrap_or("");
+        line.trim().is_empty()
+    }
+
+    fn finish(self) -> String {
+        self.text
+            .split('\n')
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let line = line.strip_suffix('\r').unwrap_or(line);
+                let line = if self.touched.contains(&index) { line.trim_end() } else { line };
+                (self.in_literal.contains(&index) || !line.trim().is_empty()).then_some(line)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn lex(output: &mut Output, src: &str, start: usize, end: usize, scan: Scanner) {
+    let mut i = start;
+    while i < end {
+        match scan(src, i) {
+            Token::Literal(len) if len > 0 => {
+                let len = len.min(end - i);
+                output.push(&src[i..i + len], true);
+                i += len;
+            }
+            Token::Comment(len) if len > 0 => {
+                let len = len.min(end - i);
+                output.remove_comment(src[i + len..].chars().next());
+                i += len;
+                // Comment ở đầu dòng: bỏ luôn khoảng trắng theo sau để code không bị thụt lề thừa
+                if output.current_line_is_blank() {
+                    while i < end && matches!(src.as_bytes()[i], b' ' | b'\t') {
+                        i += 1;
+                    }
+                }
+            }
+            _ => {
+                let len = char_len(src, i);
+                output.push(&src[i..i + len], false);
+                i += len;
+            }
+        }
+    }
+}
+
+// --- HÀM TIỆN ÍCH CHO BỘ QUÉT ---
+
+fn at(src: &str, i: usize, pattern: &str) -> bool {
+    src.as_bytes()
+        .get(i..)
+        .is_some_and(|rest| rest.starts_with(pattern.as_bytes()))
+}
+
+fn at_ignore_case(src: &str, i: usize, pattern: &str) -> bool {
+    src.as_bytes()
+        .get(i..i + pattern.len())
+        .is_some_and(|s| s.eq_ignore_ascii_case(pattern.as_bytes()))
+}
+
+fn find_ignore_case(src: &str, from: usize, pattern: &str) -> Option<usize> {
+    src.as_bytes()
+        .get(from..)?
+        .windows(pattern.len())
+        .position(|w| w.eq_ignore_ascii_case(pattern.as_bytes()))
+        .map(|p| from + p)
+}
+
+fn byte_at(src: &str, i: usize) -> Option<u8> {
+    src.as_bytes().get(i).copied()
+}
+
+fn prev_byte(src: &str, i: usize) -> Option<u8> {
+    i.checked_sub(1).map(|j| src.as_bytes()[j])
+}
+
+fn is_ident(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+fn after_ident(src: &str, i: usize) -> bool {
+    prev_byte(src, i).is_some_and(is_ident)
+}
+
+fn char_len(src: &str, i: usize) -> usize {
+    src.get(i..)
+        .and_then(|s| s.chars().next())
+        .map_or(1, char::len_utf8)
+}
+
+// Vị trí cuối dòng, trước "\n" hoặc "\r\n"
+fn line_end(src: &str, i: usize) -> usize {
+    let bytes = src.as_bytes();
+    let end = bytes[i..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |p| i + p);
+    if end > i && bytes[end - 1] == b'\r' {
+        end - 1
+    } else {
+        end
+    }
+}
+
+// Vị trí đầu dòng kế tiếp (hoặc cuối file)
+fn next_line(src: &str, i: usize) -> usize {
+    let bytes = src.as_bytes();
+    bytes[i..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map_or(bytes.len(), |p| i + p + 1)
+}
+
+fn line_start(src: &str, i: usize) -> usize {
+    src.as_bytes()[..i]
+        .iter()
+        .rposition(|&b| b == b'\n')
+        .map_or(0, |p| p + 1)
+}
+
+// Chỉ có khoảng trắng từ đầu dòng đến `i`
+fn at_line_start(src: &str, i: usize) -> bool {
+    src.as_bytes()[line_start(src, i)..i]
+        .iter()
+        .all(|&b| b == b' ' || b == b'\t')
+}
+
+// Độ dài từ `i` đến hết dòng bắt đầu bằng `marker` (tìm từ dòng sau), hoặc đến cuối file
+fn until_line_starting(src: &str, i: usize, marker: &str) -> usize {
+    let mut line = next_line(src, i);
+    while line < src.len() {
+        if at(src, line, marker) {
+            return line_end(src, line) - i;
+        }
+        line = next_line(src, line);
+    }
+    src.len() - i
+}
+
+fn line_comment(src: &str, i: usize) -> Token {
+    Token::Comment(line_end(src, i) - i)
+}
+
+fn block_comment(src: &str, i: usize, open: &str, close: &str, nested: bool) -> Token {
+    let mut depth = 1;
+    let mut j = i + open.len();
+    while j < src.len() {
+        if at(src, j, close) {
+            depth -= 1;
+            j += close.len();
+            if depth == 0 {
+                return Token::Comment(j - i);
+            }
+        } else if nested && at(src, j, open) {
+            depth += 1;
+            j += open.len();
+        } else {
+            j += 1;
+        }
+    }
+    Token::Comment(src.len() - i)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Escape {
+    Backslash,
+    // PowerShell: `"
+    Backtick,
+    // SQL, VB: '' hoặc "" bên trong chuỗi
+    Doubled,
+    None,
+}
+
+// Phần nội suy chứa code bên trong chuỗi, ví dụ `${...}` hay `\(...)`
+struct Hole<'a> {
+    open: &'a str,
+    inc: u8,
+    dec: u8,
+    scan: Scanner<'a>,
+}
+
+// Độ dài của chuỗi bắt đầu tại `i` có phần mở dài `open` byte.
+// Chuỗi một dòng chưa được đóng sẽ dừng ở cuối dòng để không nuốt phần còn lại của file.
+fn string_len(
+    src: &str,
+    i: usize,
+    open: usize,
+    close: &str,
+    escape: Escape,
+    multiline: bool,
+    hole: Option<&Hole>,
+) -> usize {
+    let bytes = src.as_bytes();
+    let mut j = i + open;
+    while j < bytes.len() {
+        if let Some(hole) = hole.filter(|hole| at(src, j, hole.open)) {
+            j += hole.open.len();
+            j += hole_len(src, j, hole);
+            continue;
+        }
+        let b = bytes[j];
+        if (escape == Escape::Backslash && b == b'\\') || (escape == Escape::Backtick && b == b'`') {
+            j += 2;
+            continue;
+        }
+        if at(src, j, close) {
+            if escape == Escape::Doubled && at(src, j + close.len(), close) {
+                j += 2 * close.len();
+                continue;
+            }
+            return j + close.len() - i;
+        }
+        if b == b'\n' && !multiline {
+            return j - i;
+        }
+        j += 1;
+    }
+    bytes.len() - i
+}
+
+fn hole_len(src: &str, i: usize, hole: &Hole) -> usize {
+    let mut depth = 1;
+    let mut j = i;
+    while j < src.len() {
+        match (hole.scan)(src, j) {
+            Token::Literal(len) | Token::Comment(len) if len > 0 => j += len,
+            _ => {
+                let b = src.as_bytes()[j];
+                if b == hole.inc {
+                    depth += 1;
+                } else if b == hole.dec {
+                    depth -= 1;
+                    if depth == 0 {
+                        return j + 1 - i;
+                    }
+                }
+                j += char_len(src, j);
+            }
+        }
+    }
+    src.len() - i
+}
+
+// Chuỗi dùng dấu nháy tại `i`, hỗ trợ cả dạng ba dấu nháy nhiều dòng (Python, Dart, TOML)
+fn quoted(src: &str, i: usize, escape: Escape, hole: Option<&Hole>) -> usize {
+    let quote = &src[i..i + 1];
+    let triple = quote.repeat(3);
+    if at(src, i, &triple) {
+        string_len(src, i, 3, &triple, escape, true, hole)
+    } else {
+        string_len(src, i, 1, quote, escape, false, hole)
+    }
+}
+
+// 'a', '\n', '\u{1F600}', 'é'; trả về None với lifetime/nhãn Rust ('a) hay dấu nháy đơn lẻ
+fn char_literal(src: &str, i: usize) -> Option<usize> {
+    let bytes = src.as_bytes();
+    match *bytes.get(i + 1)? {
+        b'\\' => {
+            let close = bytes
+                .get(i + 3..)?
+                .iter()
+                .take(10)
+                .position(|&b| b == b'\'' || b == b'\n')?;
+            (bytes[i + 3 + close] == b'\'').then_some(close + 4)
+        }
+        b'\'' | b'\n' | b'\r' => None,
+        _ => {
+            let len = char_len(src, i + 1);
+            (bytes.get(i + 1 + len) == Some(&b'\'')).then_some(len + 2)
+        }
+    }
+}
+
+// Heredoc (`<<EOF`, `<<-EOF`, `<<~'EOF'`, `<<<EOT` của PHP): giữ nguyên đến hết dòng kết thúc.
+// Trả về None nếu không tìm thấy dòng kết thúc.
+fn heredoc_len(src: &str, i: usize, marker: &str) -> Option<usize> {
+    let bytes = src.as_bytes();
+    let mut j = i + marker.len();
+    if matches!(byte_at(src, j), Some(b'-' | b'~')) {
+        j += 1;
+    }
+    while matches!(byte_at(src, j), Some(b' ' | b'\t')) && marker == "<<<" {
+        j += 1;
+    }
+    if matches!(byte_at(src, j), Some(b'\'' | b'"')) {
+        j += 1;
+    }
+    let tag_start = j;
+    while j < bytes.len() && bytes[j].is_ascii() && is_ident(bytes[j]) {
+        j += 1;
+    }
+    if j == tag_start || bytes[tag_start].is_ascii_digit() {
+        return None;
+    }
+    let tag = &src[tag_start..j];
+    let mut line = next_line(src, j);
+    while line < bytes.len() {
+        let end = line_end(src, line);
+        if let Some(rest) = src[line..end].trim_start().strip_prefix(tag) {
+            if !rest.bytes().next().is_some_and(is_ident) {
+                return Some(end - i);
+            }
+        }
+        line = next_line(src, line);
+    }
+    None
+}
+
+// --- HỌ C (// và /* */) ---
+
+fn scan_c_like(lang: CLang, src: &str, i: usize) -> Token {
+    use CLang::*;
+    let scan: Scanner = &|s, j| scan_c_like(lang, s, j);
+    let nested = matches!(lang, Rust | Swift | Kotlin | Dart);
+    match src.as_bytes()[i] {
+        b'/' if at(src, i, "/*") => block_comment(src, i, "/*", "*/", nested),
+        // CSS thuần không có comment `//` (ví dụ `url(http://...)`)
+        b'/' if at(src, i, "//") && lang != Css => line_comment(src, i),
+        b'/' if lang == Js && regex_can_start(src, i) => {
+            regex_len(src, i).map_or(Token::Code, Token::Literal)
+        }
+        b'"' => Token::Literal(c_double_quoted(lang, src, i, scan)),
+        b'\'' => match lang {
+            Js | Css | Scss => Token::Literal(string_len(src, i, 1, "'", Escape::Backslash, false, None)),
+            Dart => Token::Literal(quoted(src, i, Escape::Backslash, Some(&dollar_hole(scan)))),
+            Swift | Jsonc => Token::Code,
+            // Dấu phân cách chữ số của C++14: 1'000'000
+            Cpp if prev_byte(src, i).is_some_and(|b| b.is_ascii_digit()) => Token::Code,
+            _ => char_literal(src, i).map_or(Token::Code, Token::Literal),
+        },
+        b'`' => match lang {
+            Js => {
+                let hole = dollar_hole(scan);
+                Token::Literal(string_len(src, i, 1, "`", Escape::Backslash, true, Some(&hole)))
+            }
+            Go => Token::Literal(string_len(src, i, 1, "`", Escape::None, true, None)),
+            // Tên định danh trong dấu `, ví dụ tên test của Kotlin
+            Kotlin | Swift => Token::Literal(string_len(src, i, 1, "`", Escape::None, false, None)),
+            _ => Token::Code,
+        },
+        b'r' | b'b' | b'c' if lang == Rust => rust_raw_string(src, i).map_or(Token::Code, Token::Literal),
+        b'r' if lang == Dart && !after_ident(src, i) && matches!(byte_at(src, i + 1), Some(b'\'' | b'"')) => {
+            Token::Literal(1 + quoted(src, i + 1, Escape::None, None))
+        }
+        b'#' if lang == Swift => swift_raw_string(src, i).map_or(Token::Code, Token::Literal),
+        b'u' | b'U' if matches!(lang, Css | Scss) => css_url(src, i),
+        _ => Token::Code,
+    }
+}
+
+fn dollar_hole(scan: Scanner) -> Hole {
+    Hole { open: "${", inc: b'{', dec: b'}', scan }
+}
+
+fn c_double_quoted(lang: CLang, src: &str, i: usize, scan: Scanner) -> usize {
+    use CLang::*;
+    let quotes = src.as_bytes()[i..].iter().take_while(|&&b| b == b'"').count();
+    let hole = match lang {
+        Kotlin | Dart => Some(dollar_hole(scan)),
+        Swift => Some(Hole { open: "\\(", inc: b'(', dec: b')', scan }),
+        _ => None,
+    };
+    match lang {
+        // Text block / chuỗi nhiều dòng """...""" (raw string của C# có thể dùng nhiều dấu nháy hơn)
+        Java | Kotlin | Swift | Dart | CSharp if quotes >= 3 => {
+            let count = if lang == CSharp { quotes } else { 3 };
+            let close = "\"".repeat(count);
+            let escape = if matches!(lang, Kotlin | CSharp) { Escape::None } else { Escape::Backslash };
+            string_len(src, i, count, &close, escape, true, hole.as_ref())
+        }
+        Cpp if prev_byte(src, i) == Some(b'R') => cpp_raw_string(src, i)
+            .unwrap_or_else(|| string_len(src, i, 1, "\"", Escape::Backslash, false, None)),
+        CSharp => {
+            // Tiền tố @ (verbatim) và $ (nội suy) đứng ngay trước dấu nháy
+            let prefix = &src.as_bytes()[i.saturating_sub(2)..i];
+            let verbatim = prefix.ends_with(b"@") || prefix == b"@$";
+            let interpolated = prefix.ends_with(b"$") || prefix == b"$@";
+            let hole = interpolated.then_some(Hole { open: "{", inc: b'{', dec: b'}', scan });
+            if verbatim {
+                string_len(src, i, 1, "\"", Escape::Doubled, true, hole.as_ref())
+            } else {
+                string_len(src, i, 1, "\"", Escape::Backslash, false, hole.as_ref())
+            }
+        }
+        // Chuỗi Rust có thể kéo dài nhiều dòng
+        _ => string_len(src, i, 1, "\"", Escape::Backslash, lang == Rust, hole.as_ref()),
+    }
+}
+
+// r"...", r#"..."#, br"...", cr#"..."#; `r#ident` không phải raw string
+fn rust_raw_string(src: &str, i: usize) -> Option<usize> {
+    if after_ident(src, i) {
+        return None;
+    }
+    let mut j = i;
+    if matches!(byte_at(src, j), Some(b'b' | b'c')) {
+        j += 1;
+    }
+    if byte_at(src, j) != Some(b'r') {
+        return None;
+    }
+    j += 1;
+    let hashes = src.as_bytes()[j..].iter().take_while(|&&b| b == b'#').count();
+    if byte_at(src, j + hashes) != Some(b'"') {
+        return None;
+    }
+    let close = format!("\"{}", "#".repeat(hashes));
+    Some(string_len(src, i, j + hashes + 1 - i, &close, Escape::None, true, None))
+}
+
+// R"delim(...)delim" của C++11, `i` là vị trí dấu nháy
+fn cpp_raw_string(src: &str, i: usize) -> Option<usize> {
+    let rest = &src.as_bytes()[i + 1..];
+    let paren = rest.iter().take(17).position(|&b| b == b'(')?;
+    let delimiter = &rest[..paren];
+    if !delimiter.iter().all(|b| b.is_ascii_graphic() && !b"()\\\"".contains(b)) {
+        return None;
+    }
+    let close = format!("){}\"", String::from_utf8_lossy(delimiter));
+    Some(string_len(src, i, paren + 2, &close, Escape::None, true, None))
+}
+
+// #"..."# và #"""..."""# của Swift
+fn swift_raw_string(src: &str, i: usize) -> Option<usize> {
+    let hashes = src.as_bytes()[i..].iter().take_while(|&&b| b == b'#').count();
+    let quote_at = i + hashes;
+    if byte_at(src, quote_at) != Some(b'"') {
+        return None;
+    }
+    let hash_str = "#".repeat(hashes);
+    if at(src, quote_at, "\"\"\"") {
+        let close = format!("\"\"\"{}", hash_str);
+        Some(string_len(src, i, hashes + 3, &close, Escape::None, true, None))
+    } else {
+        let close = format!("\"{}", hash_str);
+        Some(string_len(src, i, hashes + 1, &close, Escape::None, false, None))
+    }
+}
+
+// url(...) không có dấu nháy trong CSS được giữ nguyên, kể cả `//` bên trong
+fn css_url(src: &str, i: usize) -> Token {
+    if !at_ignore_case(src, i, "url(") || prev_byte(src, i).is_some_and(|b| is_ident(b) || b == b'-') {
+        return Token::Code;
+    }
+    let bytes = src.as_bytes();
+    let mut j = i + 4;
+    while j < bytes.len() && bytes[j].is_ascii_whitespace() {
+        j += 1;
+    }
+    if matches!(byte_at(src, j), Some(b'"' | b'\'')) {
+        return Token::Code;
+    }
+    bytes[j..]
+        .iter()
+        .position(|&b| b == b')' || b == b'\n')
+        .filter(|&p| bytes[j + p] == b')')
+        .map_or(Token::Code, |p| Token::Literal(j + p + 1 - i))
+}
+
+// Dấu `/` mở regex literal khi đứng sau toán tử, dấu mở ngoặc hoặc từ khóa như `return`
+fn regex_can_start(src: &str, i: usize) -> bool {
+    let before = src[..i].trim_end();
+    match before.bytes().last() {
+        None => true,
+        Some(b) if b"(,=:[!&|?{};+-*%<>~^".contains(&b) => true,
+        Some(b) if is_ident(b) || b == b'$' => {
+            let word = before
+                .rsplit(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                .next()
+                .unwrap_or("");
+            matches!(
+                word,
+                "return" | "typeof" | "case" | "do" | "else" | "in" | "of" | "new" | "delete"
+                    | "void" | "throw" | "yield" | "await"
+            )
+        }
+        _ => false,
+    }
+}
+
+fn regex_len(src: &str, i: usize) -> Option<usize> {
+    let bytes = src.as_bytes();
+    let mut j = i + 1;
+    let mut in_class = false;
+    while j < bytes.len() {
+        match bytes[j] {
+            b'\\' => j += 1,
+            b'[' => in_class = true,
+            b']' => in_class = false,
+            b'/' if !in_class => {
+                j += 1;
+                while j < bytes.len() && bytes[j].is_ascii_alphabetic() {
+                    j += 1;
+                }
+                return Some(j - i);
+            }
+            b'\n' => return None,
+            _ => {}
+        }
+        j += 1;
+    }
+    None
+}
+
+// --- HỌ DẤU THĂNG (#) ---
+
+fn scan_hash(lang: HashLang, src: &str, i: usize) -> Token {
+    use HashLang::*;
+    if i == 0 && at(src, 0, "#!") {
+        return Token::Literal(line_end(src, 0));
+    }
+    let b = src.as_bytes()[i];
+    match (lang, b) {
+        // Dockerfile và .gitignore chỉ có comment ở đầu dòng
+        (Dockerfile, b'#') if at_line_start(src, i) => dockerfile_comment(src, i),
+        (GitIgnore, b'#') if line_start(src, i) == i => line_comment(src, i),
+        (Dockerfile | GitIgnore, _) => Token::Code,
+
+        // `$#`, `${#var}` và `a#b` không phải comment trong shell
+        (Shell, b'#') if !prev_byte(src, i)
+            .is_none_or(|p| p.is_ascii_whitespace() || b";&|()".contains(&p)) => Token::Code,
+        (Yaml, b'#') if !prev_byte(src, i).is_none_or(|p| p.is_ascii_whitespace()) => Token::Code,
+        (Perl, b'#') if prev_byte(src, i) == Some(b'$') => Token::Code,
+        (PowerShell, b'<') if at(src, i, "<#") => block_comment(src, i, "<#", "#>", false),
+        (_, b'#') => line_comment(src, i),
+
+        (Python | Toml, b'"') => Token::Literal(quoted(src, i, Escape::Backslash, None)),
+        (Python, b'\'') => Token::Literal(quoted(src, i, Escape::Backslash, None)),
+        (Toml, b'\'') => Token::Literal(quoted(src, i, Escape::None, None)),
+
+        (Ruby, b'"' | b'`') => {
+            let scan: Scanner = &|s, j| scan_hash(Ruby, s, j);
+            let hole = Hole { open: "#{", inc: b'{', dec: b'}', scan };
+            Token::Literal(string_len(src, i, 1, &src[i..i + 1], Escape::Backslash, true, Some(&hole)))
+        }
+        (Ruby, b'\'') => Token::Literal(string_len(src, i, 1, "'", Escape::Backslash, true, None)),
+        (Ruby, b'=') if line_start(src, i) == i && at(src, i, "=begin") => {
+            Token::Comment(until_line_starting(src, i, "=end"))
+        }
+        (Ruby | Perl, b'_') if line_start(src, i) == i && (at(src, i, "__END__") || at(src, i, "__DATA__")) => {
+            Token::Literal(src.len() - i)
+        }
+
+        (Shell, b'<') if at(src, i, "<<<") => Token::Literal(3),
+        (Ruby | Shell | Perl, b'<') if at(src, i, "<<") => {
+            heredoc_len(src, i, "<<").map_or(Token::Code, Token::Literal)
+        }
+        (Shell, b'\'') => {
+            // $'...' của bash cho phép escape, '...' thường thì không
+            let escape = if prev_byte(src, i) == Some(b'$') { Escape::Backslash } else { Escape::None };
+            Token::Literal(string_len(src, i, 1, "'", escape, true, None))
+        }
+        (Shell, b'"') => {
+            let scan: Scanner = &|s, j| scan_hash(Shell, s, j);
+            let hole = Hole { open: "$(", inc: b'(', dec: b')', scan };
+            Token::Literal(string_len(src, i, 1, "\"", Escape::Backslash, true, Some(&hole)))
+        }
+        (Shell, b'`') => Token::Literal(string_len(src, i, 1, "`", Escape::Backslash, true, None)),
+
+        (Yaml, b'"' | b'\'') if yaml_quote_starts_scalar(src, i) => {
+            let escape = if b == b'"' { Escape::Backslash } else { Escape::Doubled };
+            Token::Literal(string_len(src, i, 1, &src[i..i + 1], escape, true, None))
+        }
+        (Yaml, b'\n') => yaml_block_scalar(src, i).map_or(Token::Code, Token::Literal),
+
+        (R, b'"' | b'\'') => Token::Literal(string_len(src, i, 1, &src[i..i + 1], Escape::Backslash, true, None)),
+        (R, b'r' | b'R') => r_raw_string(src, i).map_or(Token::Code, Token::Literal),
+
+        (Perl, b'"' | b'\'') => Token::Literal(string_len(src, i, 1, &src[i..i + 1], Escape::Backslash, true, None)),
+        // Tài liệu POD (=pod, =head1...) kéo dài đến dòng `=cut`
+        (Perl, b'=') if line_start(src, i) == i && byte_at(src, i + 1).is_some_and(|c| c.is_ascii_alphabetic()) => {
+            if at(src, i, "=cut") {
+                line_comment(src, i)
+            } else {
+                Token::Comment(until_line_starting(src, i, "=cut"))
+            }
+        }
+
+        (PowerShell, b'@') if matches!(byte_at(src, i + 1), Some(b'"' | b'\'')) => {
+            let quote = &src[i + 1..i + 2];
+            let close = format!("\n{}@", quote);
+            Token::Literal(string_len(src, i, 2, &close, Escape::None, true, None))
+        }
+        (PowerShell, b'\'') => Token::Literal(string_len(src, i, 1, "'", Escape::Doubled, true, None)),
+        (PowerShell, b'"') => {
+            let scan: Scanner = &|s, j| scan_hash(PowerShell, s, j);
+            let hole = Hole { open: "$(", inc: b'(', dec: b')', scan };
+            Token::Literal(string_len(src, i, 1, "\"", Escape::Backtick, true, Some(&hole)))
+        }
+        _ => Token::Code,
+    }
+}
+
+// Chỉ thị parser ở đầu Dockerfile (`# syntax=...`, `# escape=...`) không phải comment
+fn dockerfile_comment(src: &str, i: usize) -> Token {
+    let is_directive = |line: &str| {
+        line.trim_start()
+            .strip_prefix('#')
+            .and_then(|rest| rest.split_once('='))
+            .is_some_and(|(key, _)| {
+                matches!(key.trim().to_ascii_lowercase().as_str(), "syntax" | "escape" | "check")
+            })
+    };
+    let end = line_end(src, i);
+    if src[..i].lines().all(is_directive) && is_directive(&src[i..end]) {
+        Token::Literal(end - i)
+    } else {
+        line_comment(src, i)
+    }
+}
+
+// Trong YAML, dấu nháy chỉ mở chuỗi ở đầu giá trị: `key: 'x'`, `- "x"`, `[a, 'b']`.
+// Dấu nháy giữa chữ (`don't`) thuộc về chuỗi không nháy.
+fn yaml_quote_starts_scalar(src: &str, i: usize) -> bool {
+    let before = &src[line_start(src, i)..i];
+    let trimmed = before.trim_end();
+    trimmed.is_empty()
+        || trimmed.ends_with(['[', '{', ','])
+        || (trimmed.len() < before.len() && trimmed.ends_with([':', '-', '?']))
+}
+
+// Nội dung của block scalar (`key: |`, `- >-`) là văn bản, kể cả các dòng có dấu `#`.
+// `i` là ký tự xuống dòng kết thúc dòng tiêu đề.
+fn yaml_block_scalar(src: &str, i: usize) -> Option<usize> {
+    let line = &src[line_start(src, i)..i];
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let content = line.find(" #").map_or(line, |p| &line[..p]).trim_end();
+    let (head, indicator) = content.rsplit_once([' ', '\t'])?;
+    let head = head.trim_end();
+    if !indicator.starts_with(['|', '>'])
+        || !indicator[1..].bytes().all(|b| b.is_ascii_digit() || b == b'+' || b == b'-')
+        || !(head.ends_with(':') || head.ends_with('-'))
+    {
+        return None;
+    }
+    let indent = line.len() - line.trim_start().len();
+    let mut end = i;
+    let mut next = i + 1;
+    while next < src.len() {
+        let body_end = line_end(src, next);
+        let body = &src[next..body_end];
+        if !body.trim().is_empty() {
+            if body.len() - body.trim_start().len() <= indent {
+                break;
+            }
+            end = body_end;
+        }
+        next = next_line(src, next);
+    }
+    (end > i).then_some(end - i)
+}
+
+// r"(...)", R'[...]', r"-{...}-" của R 4.0
+fn r_raw_string(src: &str, i: usize) -> Option<usize> {
+    if after_ident(src, i) {
+        return None;
+    }
+    let quote = byte_at(src, i + 1).filter(|b| matches!(b, b'"' | b'\''))?;
+    let dashes = src.as_bytes()[i + 2..].iter().take_while(|&&b| b == b'-').count();
+    let close_bracket = match byte_at(src, i + 2 + dashes)? {
+        b'(' => ')',
+        b'[' => ']',
+        b'{' => '}',
+        _ => return None,
+    };
+    let close = format!("{}{}{}", close_bracket, "-".repeat(dashes), quote as char);
+    Some(string_len(src, i, dashes + 3, &close, Escape::None, true, None))
+}
+
+// --- HỌ HTML/XML/MARKDOWN (<!-- -->) ---
+
+fn scan_markup(kind: Markup, src: &str, i: usize) -> Token {
+    match src.as_bytes()[i] {
+        b'<' if at(src, i, "<!--") => block_comment(src, i, "<!--", "-->", false),
+        b'<' if kind != Markup::Markdown && at(src, i, "<![CDATA[") => {
+            Token::Literal(string_len(src, i, 9, "]]>", Escape::None, true, None))
+        }
+        // Nội dung <script> và <style> của HTML được giữ nguyên
+        b'<' if kind == Markup::Html => ["script", "style"]
+            .iter()
+            .find(|tag| {
+                at_ignore_case(src, i + 1, tag)
+                    && byte_at(src, i + 1 + tag.len()).is_some_and(|b| b == b'>' || b.is_ascii_whitespace())
+            })
+            .map_or(Token::Code, |tag| {
+                let close = find_ignore_case(src, i + 1, &format!("</{}", tag));
+                Token::Literal(close.unwrap_or(src.len()) - i)
+            }),
+        b'`' | b'~' if kind == Markup::Markdown => markdown_code(src, i),
+        _ => Token::Code,
+    }
+}
+
+// Khối code có rào (``` hoặc ~~~) và code span trong Markdown được giữ nguyên
+fn markdown_code(src: &str, i: usize) -> Token {
+    let bytes = src.as_bytes();
+    let fence_char = bytes[i];
+    let run = bytes[i..].iter().take_while(|&&b| b == fence_char).count();
+    if run >= 3 && at_line_start(src, i) {
+        let mut line = next_line(src, i);
+        while line < bytes.len() {
+            let end = line_end(src, line);
+            let trimmed = src[line..end].trim();
+            if trimmed.len() >= run && trimmed.bytes().all(|b| b == fence_char) {
+                return Token::Literal(end - i);
+            }
+            line = next_line(src, line);
+        }
+        return Token::Literal(bytes.len() - i);
+    }
+    if fence_char == b'`' {
+        // Code span được đóng bởi chuỗi dấu ` có cùng độ dài
+        let mut j = i + run;
+        while j < bytes.len() {
+            if bytes[j] == b'`' {
+                let close = bytes[j..].iter().take_while(|&&b| b == b'`').count();
+                if close == run {
+                    return Token::Literal(j + close - i);
+                }
+                j += close;
+            } else {
+                j += 1;
+            }
+        }
+    }
+    Token::Literal(run)
+}
+
+// --- HỌ DẤU GẠCH (--) ---
+
+fn scan_dash(lang: DashLang, src: &str, i: usize) -> Token {
+    use DashLang::*;
+    let b = src.as_bytes()[i];
+    match (lang, b) {
+        (Lua, b'-') if at(src, i, "--") => lua_long_bracket(src, i + 2)
+            .map_or_else(|| line_comment(src, i), |len| Token::Comment(len + 2)),
+        (Lua, b'[') => lua_long_bracket(src, i).map_or(Token::Code, Token::Literal),
+        (Lua, b'"' | b'\'') => Token::Literal(string_len(src, i, 1, &src[i..i + 1], Escape::Backslash, false, None)),
+
+        // Pragma {-# ... #-} không phải comment
+        (Haskell, b'{') if at(src, i, "{-#") => Token::Literal(string_len(src, i, 3, "#-}", Escape::None, true, None)),
+        (Haskell, b'{') if at(src, i, "{-") => block_comment(src, i, "{-", "-}", true),
+        (Haskell, b'-') => haskell_dashes(src, i),
+        (Haskell, b'"') => Token::Literal(string_len(src, i, 1, "\"", Escape::Backslash, false, None)),
+        // x' là tên biến có dấu phẩy trên, không phải ký tự
+        (Haskell, b'\'') if !after_ident(src, i) => char_literal(src, i).map_or(Token::Code, Token::Literal),
+
+        (Sql | Ada, b'-') if at(src, i, "--") => line_comment(src, i),
+        (Sql, b'/') if at(src, i, "/*") => block_comment(src, i, "/*", "*/", false),
+        (Sql, b'\'' | b'"' | b'`') => Token::Literal(string_len(src, i, 1, &src[i..i + 1], Escape::Doubled, true, None)),
+        (Sql, b'$') => dollar_quoted(src, i).map_or(Token::Code, Token::Literal),
+
+        (Ada, b'"') => Token::Literal(string_len(src, i, 1, "\"", Escape::Doubled, false, None)),
+        // Ký tự 'x' (kể cả ''') khác với thuộc tính X'First
+        (Ada, b'\'') if !prev_byte(src, i).is_some_and(|p| is_ident(p) || p == b')') => {
+            let len = char_len(src, i + 1);
+            if byte_at(src, i + 1 + len) == Some(b'\'') {
+                Token::Literal(len + 2)
+            } else {
+                Token::Code
+            }
+        }
+        _ => Token::Code,
+    }
+}
+
+// [[...]], [==[...]==] của Lua; `i` là vị trí dấu [ đầu tiên
+fn lua_long_bracket(src: &str, i: usize) -> Option<usize> {
+    if byte_at(src, i) != Some(b'[') {
+        return None;
+    }
+    let level = src.as_bytes()[i + 1..].iter().take_while(|&&b| b == b'=').count();
+    if byte_at(src, i + 1 + level) != Some(b'[') {
+        return None;
+    }
+    let close = format!("]{}]", "=".repeat(level));
+    Some(string_len(src, i, level + 2, &close, Escape::None, true, None))
+}
+
+// `--` chỉ mở comment khi chuỗi dấu gạch không thuộc một toán tử như `-->` hay `|--`
+fn haskell_dashes(src: &str, i: usize) -> Token {
+    let run = src.as_bytes()[i..].iter().take_while(|&&b| b == b'-').count();
+    let is_symbol = |b: u8| b"!#$%&*+./<=>?@\\^|~:".contains(&b);
+    if run < 2 || prev_byte(src, i).is_some_and(is_symbol) || byte_at(src, i + run).is_some_and(is_symbol) {
+        Token::Literal(run)
+    } else {
+        line_comment(src, i)
+    }
+}
+
+// Chuỗi $$...$$ / $tag$...$tag$ của PostgreSQL; `$1` là tham số
+fn dollar_quoted(src: &str, i: usize) -> Option<usize> {
+    if after_ident(src, i) || byte_at(src, i + 1).is_some_and(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let tag_len = src.as_bytes()[i + 1..]
+        .iter()
+        .take_while(|&&b| b.is_ascii() && is_ident(b))
+        .count();
+    if byte_at(src, i + 1 + tag_len) != Some(b'$') {
+        return None;
+    }
+    let delimiter = &src[i..i + tag_len + 2];
+    let close = src[i + delimiter.len()..].find(delimiter)?;
+    Some(delimiter.len() + close + delimiter.len())
+}
+
+// --- CÁC HỌ CÒN LẠI ---
+
+// Lisp (;) với comment khối #| |# lồng nhau và ký tự #\; (Emacs Lisp: ?;)
+fn scan_lisp(elisp: bool, src: &str, i: usize) -> Token {
+    match src.as_bytes()[i] {
+        b';' => line_comment(src, i),
+        b'#' if at(src, i, "#|") => block_comment(src, i, "#|", "|#", true),
+        b'#' if at(src, i, "#\\") => Token::Literal(2 + char_len(src, i + 2)),
+        b'"' => Token::Literal(string_len(src, i, 1, "\"", Escape::Backslash, true, None)),
+        b'?' if elisp && prev_byte(src, i).is_none_or(|p| p.is_ascii_whitespace() || b"('`,".contains(&p)) => {
+            match byte_at(src, i + 1) {
+                Some(b'\\') => Token::Literal(2 + char_len(src, i + 2)),
+                Some(c) if !c.is_ascii_whitespace() => Token::Literal(1 + char_len(src, i + 1)),
+                _ => Token::Code,
+            }
+        }
+        _ => Token::Code,
+    }
+}
+
+// Erlang (%) với ký tự $% và atom '...'
+fn scan_erlang(src: &str, i: usize) -> Token {
+    match src.as_bytes()[i] {
+        b'%' => line_comment(src, i),
+        b'"' => Token::Literal(string_len(src, i, 1, "\"", Escape::Backslash, true, None)),
+        b'\'' => Token::Literal(string_len(src, i, 1, "'", Escape::Backslash, false, None)),
+        b'$' if byte_at(src, i + 1) == Some(b'\\') => Token::Literal(2 + char_len(src, i + 2)),
+        b'$' => Token::Literal(1 + char_len(src, i + 1)),
+        _ => Token::Code,
+    }
+}
+
+// Visual Basic (' và REM đầu câu lệnh)
+fn scan_basic(src: &str, i: usize) -> Token {
+    match src.as_bytes()[i] {
+        b'\'' => line_comment(src, i),
+        b'"' => Token::Literal(string_len(src, i, 1, "\"", Escape::Doubled, false, None)),
+        b'r' | b'R'
+            if at_ignore_case(src, i, "rem")
+                && byte_at(src, i + 3).is_none_or(|b| b.is_ascii_whitespace())
+                && {
+                    let before = src[line_start(src, i)..i].trim_end();
+                    before.is_empty() || before.ends_with(':')
+                } =>
+        {
+            line_comment(src, i)
+        }
+        _ => Token::Code,
+    }
+}
+
+// PHP: phần HTML ngoài thẻ <?php ... ?> được giữ nguyên; `#[...]` là attribute của PHP 8
+fn scan_php(src: &str, i: usize) -> Token {
+    if i == 0 {
+        return Token::Literal(php_html_len(src, 0));
+    }
+    let scan: Scanner = &scan_php;
+    match src.as_bytes()[i] {
+        b'?' if at(src, i, "?>") => Token::Literal(2 + php_html_len(src, i + 2)),
+        b'#' if at(src, i, "#[") => Token::Code,
+        b'#' => php_line_comment(src, i),
+        b'/' if at(src, i, "//") => php_line_comment(src, i),
+        b'/' if at(src, i, "/*") => block_comment(src, i, "/*", "*/", false),
+        b'\'' | b'`' => Token::Literal(string_len(src, i, 1, &src[i..i + 1], Escape::Backslash, true, None)),
+        b'"' => {
+            let hole = Hole { open: "{$", inc: b'{', dec: b'}', scan };
+            Token::Literal(string_len(src, i, 1, "\"", Escape::Backslash, true, Some(&hole)))
+        }
+        b'<' if at(src, i, "<<<") => Token::Literal(heredoc_len(src, i, "<<<").unwrap_or(3)),
+        _ => Token::Code,
+    }
+}
+
+// Độ dài phần HTML từ `i` đến hết thẻ mở PHP tiếp theo
+fn php_html_len(src: &str, i: usize) -> usize {
+    match src[i..].find("<?") {
+        Some(p) if at_ignore_case(src, i + p, "<?php") => p + 5,
+        Some(p) if at(src, i + p, "<?=") => p + 3,
+        Some(p) => p + 2,
+        None => src.len() - i,
+    }
+}
+
+// Comment một dòng của PHP kết thúc ở cuối dòng hoặc ở `?>`
+fn php_line_comment(src: &str, i: usize) -> Token {
+    let end = line_end(src, i);
+    Token::Comment(src[i..end].find("?>").unwrap_or(end - i))
+}
+
+// Vue/Astro: template dùng comment HTML, <script> là JS/TS, <style> là CSS/SCSS,
+// frontmatter `---` của Astro là TypeScript
+fn strip_component(output: &mut Output, src: &str, astro: bool) {
+    // Template không có phần <script>/<style> nên dùng bộ quét XML (chỉ <!-- --> và CDATA)
+    let template: Scanner = &|s, j| scan_markup(Markup::Xml, s, j);
+    let script: Scanner = &|s, j| scan_c_like(CLang::Js, s, j);
+    let mut i = 0;
+    if astro && at(src, 0, "---") {
+        if let Some(p) = src[3..].find("\n---") {
+            let end = 3 + p + 1;
+            output.push(&src[..3], false);
+            lex(output, src, 3, end, script);
+            i = end;
+        }
+    }
+    while i < src.len() {
+        let Some((body_start, tag, lang)) = next_embedded_block(src, i) else {
+            lex(output, src, i, src.len(), template);
+            break;
+        };
+        lex(output, src, i, body_start, template);
+        let body_end = find_ignore_case(src, body_start, &format!("</{}", tag)).unwrap_or(src.len());
+        lex(output, src, body_start, body_end, &|s, j| scan_c_like(lang, s, j));
+        i = body_end;
+    }
+}
+
+// Tìm thẻ <script> hoặc <style> kế tiếp, trả về vị trí bắt đầu nội dung, tên thẻ và ngôn ngữ
+fn next_embedded_block(src: &str, from: usize) -> Option<(usize, &'static str, CLang)> {
+    let mut search = from;
+    loop {
+        let open = src.as_bytes().get(search..)?.iter().position(|&b| b == b'<')? + search;
+        let tag = ["script", "style"].into_iter().find(|tag| {
+            at_ignore_case(src, open + 1, tag)
+                && byte_at(src, open + 1 + tag.len()).is_some_and(|b| b == b'>' || b.is_ascii_whitespace())
+        });
+        let Some(tag) = tag else {
+            search = open + 1;
+            continue;
+        };
+        let tag_end = src[open..].find('>')? + open;
+        let attributes = src[open..tag_end].to_ascii_lowercase();
+        let lang = match tag {
+            "script" => CLang::Js,
+            _ if ["scss", "sass", "less"].iter().any(|l| attributes.contains(&format!("lang=\"{}\"", l))) => CLang::Scss,
+            _ => CLang::Css,
+        };
+        return Some((tag_end + 1, tag, lang));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // (file, nội dung gốc, kết quả mong đợi)
+    const CORPUS: &[(&str, &str, &str)] = &[
+        // Rust: URL trong chuỗi, attribute, lifetime, ký tự, raw string, comment lồng nhau
+        (
+            "lib.rs",
+            "#![allow(dead_code)]\n/// Doc comment\n#[derive(Debug)] // trailing\nstruct A<'a> { url: &'a str }\n\nfn f() -> char {\n    let s = \"http://example.com\"; // c\n    let r = r#\"raw // \"not\" /* comment */\"#;\n    /* outer /* inner */ still comment */\n    let c = '\"'; let d = '/';\n    'x'\n}\n",
+            "#![allow(dead_code)]\n#[derive(Debug)]\nstruct A<'a> { url: &'a str }\nfn f() -> char {\n    let s = \"http://example.com\";\n    let r = r#\"raw // \"not\" /* comment */\"#;\n    let c = '\"'; let d = '/';\n    'x'\n}",
+        ),
+        (
+            "lines.rs",
+            "let s = \"line one\n\n// still the string\n\";\nlet x = a/* gap */b;\n",
+            "let s = \"line one\n\n// still the string\n\";\nlet x = a b;",
+        ),
+        // JS/TS: template literal lồng nhau, regex chứa //, phép chia
+        (
+            "app.ts",
+            "const u = `http://${host}/${`//${x}`}`; // c\nconst re = /\\/\\/+/g; /* c */\nconst half = total / 2 // c\nconst q = 'it\\'s // fine';\n",
+            "const u = `http://${host}/${`//${x}`}`;\nconst re = /\\/\\/+/g;\nconst half = total / 2\nconst q = 'it\\'s // fine';",
+        ),
+        // C++: raw string, dấu phân cách chữ số
+        (
+            "main.cpp",
+            "auto s = R\"x(// not a comment)x\"; // c\nint n = 1'000'000; // c\nchar q = '\\'';\n",
+            "auto s = R\"x(// not a comment)x\";\nint n = 1'000'000;\nchar q = '\\'';",
+        ),
+        // Go: raw string trong dấu `
+        ("main.go", "s := `/* keep */ // keep`\n// drop\n", "s := `/* keep */ // keep`"),
+        // C#: chuỗi verbatim và chuỗi nội suy có chuỗi lồng bên trong
+        (
+            "Program.cs",
+            "var p = @\"C:\\dir\\\"\"// keep\"; // c\nvar m = $\"{(ok ? \"a//b\" : \"c\")}\"; // c\n",
+            "var p = @\"C:\\dir\\\"\"// keep\";\nvar m = $\"{(ok ? \"a//b\" : \"c\")}\";",
+        ),
+        // Swift: chuỗi nhiều dòng và raw string
+        (
+            "main.swift",
+            "let a = \"\"\"\n// keep\n\"\"\"\nlet b = #\"// \"keep\"\"#\n/* a /* b */ c */\n",
+            "let a = \"\"\"\n// keep\n\"\"\"\nlet b = #\"// \"keep\"\"#",
+        ),
+        // Kotlin: mẫu chuỗi ${} có chuỗi lồng
+        ("Main.kt", "val s = \"${map[\"//k\"]} // keep\" // c\n", "val s = \"${map[\"//k\"]} // keep\""),
+        // CSS: màu hex, url không có dấu nháy
+        (
+            "style.css",
+            "a { color: #fff; background: url(http://x.com/a.png); } /* c */\n",
+            "a { color: #fff; background: url(http://x.com/a.png); }",
+        ),
+        ("style.scss", "$c: #0af; // c\n.a { content: \"//\"; }\n", "$c: #0af;\n.a { content: \"//\"; }"),
+        // Python: shebang, chuỗi ba dấu nháy, # trong chuỗi
+        (
+            "script.py",
+            "#!/usr/bin/env python3\n# comment\nx = \"#not\"  # c\ndoc = \"\"\"\n# keep\n\n\"\"\"\ny = '#' # c\n",
+            "#!/usr/bin/env python3\nx = \"#not\"\ndoc = \"\"\"\n# keep\n\n\"\"\"\ny = '#'",
+        ),
+        // Ruby: nội suy #{} và =begin/=end
+        (
+            "app.rb",
+            "puts \"#{a} # keep\" # c\n=begin\nblock\n=end\nx = 1\n",
+            "puts \"#{a} # keep\"\nx = 1",
+        ),
+        // Shell: $#, ${#var}, heredoc
+        (
+            "run.sh",
+            "#!/bin/sh\necho $# ${#arr[@]} a#b # c\ncat <<EOF\n# keep\nEOF\n",
+            "#!/bin/sh\necho $# ${#arr[@]} a#b\ncat <<EOF\n# keep\nEOF",
+        ),
+        // YAML: dấu # trong chuỗi, dấu nháy giữa chữ, block scalar
+        (
+            "ci.yml",
+            "name: \"a # b\" # c\ntitle: don't # c\nrun: |\n  echo ok # keep\n\n  #keep\nnext: 1\n",
+            "name: \"a # b\"\ntitle: don't\nrun: |\n  echo ok # keep\n\n  #keep\nnext: 1",
+        ),
+        ("Cargo.toml", "name = \"a#b\" # c\nlit = 'c:\\#'\n", "name = \"a#b\"\nlit = 'c:\\#'"),
+        (
+            "Dockerfile",
+            "# syntax=docker/dockerfile:1\n# c\nRUN echo \"#1\" # keep\n",
+            "# syntax=docker/dockerfile:1\nRUN echo \"#1\" # keep",
+        ),
+        ("ci.ps1", "<# block #>\n$s = \"a # b\" # c\n", "$s = \"a # b\""),
+        ("lib.pl", "my $n = $#arr; # c\n=pod\ndocs\n=cut\nprint 1;\n", "my $n = $#arr;\nprint 1;"),
+        // HTML/Markdown
+        (
+            "index.html",
+            "<!-- c --><p>a</p>\n<script>const s = \"<!-- keep -->\";</script>\n",
+            "<p>a</p>\n<script>const s = \"<!-- keep -->\";</script>",
+        ),
+        ("README.md", "Text <!-- c -->\n```html\n<!-- keep -->\n```\n`<!-- keep -->`\n", "Text\n```html\n<!-- keep -->\n```\n`<!-- keep -->`"),
+        // SQL/Lua/Haskell/Ada
+        (
+            "query.sql",
+            "SELECT '--x', 'it''s' -- c\n/* c */ FROM t; $$ -- keep $$\n",
+            "SELECT '--x', 'it''s'\nFROM t; $$ -- keep $$",
+        ),
+        ("init.lua", "--[[ block\n]] local s = [[--keep]] -- c\n", "local s = [[--keep]]"),
+        (
+            "Main.hs",
+            "{-# LANGUAGE GADTs #-}\nx' = a --> b -- c\n{- a {- b -} c -}\ns = \"--\"\n",
+            "{-# LANGUAGE GADTs #-}\nx' = a --> b\ns = \"--\"",
+        ),
+        ("main.ada", "C : Character := '-'; -- c\nX'First\n", "C : Character := '-';\nX'First"),
+        // Lisp/Erlang/VB
+        ("core.lisp", "(list #\\; \";\") ; c\n#| a #| b |# |#\n", "(list #\\; \";\")"),
+        ("init.el", "(insert ?; \"x\") ; c\n", "(insert ?; \"x\")"),
+        ("mod.erl", "X = $%, Y = \"%\". % c\n", "X = $%, Y = \"%\"."),
+        ("mod.vb", "Dim s = \"It's\" ' c\nREM comment\nx = 1\n", "Dim s = \"It's\"\nx = 1"),
+        // PHP: HTML bên ngoài, attribute, chuỗi
+        (
+            "index.php",
+            "<p>don't</p>\n<?php\n#[Attr]\n$u = \"http://x\"; # c\n// c\n?>\n<a href=\"http://y\">y</a>\n",
+            "<p>don't</p>\n<?php\n#[Attr]\n$u = \"http://x\";\n?>\n<a href=\"http://y\">y</a>",
+        ),
+        // Vue/Astro
+        (
+            "App.vue",
+            "<template>\n  <!-- c -->\n  <a href=\"http://x\">x</a>\n</template>\n<script setup>\nconst u = \"http://y\" // c\n</script>\n<style lang=\"scss\">\n.a { color: #fff; } // c\n</style>\n",
+            "<template>\n  <a href=\"http://x\">x</a>\n</template>\n<script setup>\nconst u = \"http://y\"\n</script>\n<style lang=\"scss\">\n.a { color: #fff; }\n</style>",
+        ),
+        (
+            "Page.astro",
+            "---\nconst u = \"http://x\"; // c\n---\n<a href=\"http://y\">y</a>\n",
+            "---\nconst u = \"http://x\";\n---\n<a href=\"http://y\">y</a>",
+        ),
+    ];
+
+    #[test]
+    fn strips_comments_without_touching_literals() {
+        for (path, input, expected) in CORPUS {
+            assert_eq!(strip_comments(input, path), *expected, "file: {}", path);
+        }
+    }
+
+    #[test]
+    fn unknown_files_only_lose_blank_lines() {
+        assert_eq!(strip_comments("a // b\n\n# c\n", "notes.txt"), "a // b\n# c");
+    }
+}
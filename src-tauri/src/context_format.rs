@@ -0,0 +1,405 @@
+// src-tauri/src/context_format.rs
+// Các định dạng đầu ra của ngữ cảnh: văn bản thuần, XML, Markdown, JSON và JSONL.
+// context_generator chuẩn bị cây thư mục và nội dung file, formatter chỉ lo trình bày.
+use crate::context_generator::count_tokens;
+use crate::models::{BudgetAction, ExportFormat, Tokenizer};
+use serde_json::{json, Value};
+use std::fmt::Write as FmtWrite;
+use std::path::Path;
+
+const SEPARATOR: &str = "================================================";
+const TRUNCATED_MARKER: &str = "... [ĐÃ CẮT BỚT ĐỂ VỪA GIỚI HẠN TOKEN]";
+
+/// Một file trong ngữ cảnh, nội dung đã qua xử lý (xóa comment, cắt bớt...).
+pub struct ContextFileEntry<'a> {
+    pub path: &'a str,
+    pub content: &'a str,
+    pub budget_action: Option<BudgetAction>,
+    // Tokenizer dùng cho số token của file trong JSON và template
+    pub tokenizer: Tokenizer,
+}
+
+/// Toàn bộ dữ liệu cần để trình bày một ngữ cảnh (hoặc một phần của nó).
+pub struct ContextDocument<'a> {
+    pub directory_structure: &'a str,
+    // Cây thư mục đã chứa nội dung file (chế độ siêu nén của định dạng văn bản thuần)
+    pub inline_tree: bool,
+    // (phần hiện tại, tổng số phần) khi ngữ cảnh được chia nhỏ
+    pub part: Option<(usize, usize)>,
+    pub files: Vec<ContextFileEntry<'a>>,
+    pub with_line_numbers: bool,
+    pub always_apply_text: Option<&'a str>,
+    // Bản đồ ký hiệu của phần còn lại của dự án, đặt trước cây thư mục
+    pub repo_map: Option<&'a str>,
+}
+
+pub trait ContextFormatter: Sync {
+    /// Trình bày riêng một file, dùng để ước lượng kích thước khi chia phần.
+    fn render_file(&self, file: &ContextFileEntry, with_line_numbers: bool) -> String;
+    fn render(&self, doc: &ContextDocument) -> String;
+}
+
+pub fn formatter_for(format: ExportFormat) -> &'static dyn ContextFormatter {
+    match format {
+        ExportFormat::Plain => &PlainFormatter,
+        ExportFormat::Xml => &XmlFormatter,
+        ExportFormat::Markdown => &MarkdownFormatter,
+        ExportFormat::Json => &JsonFormatter { lines: false },
+        ExportFormat::Jsonl => &JsonFormatter { lines: true },
+    }
+}
+
+/// Tên ngôn ngữ theo phần mở rộng, dùng cho code block Markdown và trường `language` của JSON.
+pub fn language_for_path(path: &str) -> &'static str {
+    let file_name = Path::new(path)
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    if file_name == "dockerfile" {
+        return "dockerfile";
+    }
+    if file_name == "makefile" {
+        return "makefile";
+    }
+    let extension = Path::new(&file_name)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+    match extension {
+        "rs" => "rust",
+        "ts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "py" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" => "kotlin",
+        "swift" => "swift",
+        "c" | "h" => "c",
+        "cpp" | "cc" | "hpp" => "cpp",
+        "cs" => "csharp",
+        "rb" => "ruby",
+        "php" => "php",
+        "dart" => "dart",
+        "lua" => "lua",
+        "sql" => "sql",
+        "sh" | "bash" => "bash",
+        "ps1" => "powershell",
+        "html" => "html",
+        "xml" | "svg" => "xml",
+        "css" => "css",
+        "scss" => "scss",
+        "less" => "less",
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "astro" => "astro",
+        "json" | "jsonc" => "json",
+        "yml" | "yaml" => "yaml",
+        "toml" => "toml",
+        "md" => "markdown",
+        "gd" => "gdscript",
+        _ => "text",
+    }
+}
+
+fn push_numbered(output: &mut String, content: &str, with_line_numbers: bool) {
+    if with_line_numbers {
+        for (i, line) in content.lines().enumerate() {
+            let _ = writeln!(output, "{}: {}", i + 1, line);
+        }
+    } else {
+        output.push_str(content);
+    }
+}
+
+fn end_line(output: &mut String) {
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+}
+
+fn is_truncated(file: &ContextFileEntry) -> bool {
+    file.budget_action == Some(BudgetAction::Truncated)
+}
+
+// --- VĂN BẢN THUẦN (định dạng mặc định) ---
+struct PlainFormatter;
+
+impl ContextFormatter for PlainFormatter {
+    fn render_file(&self, file: &ContextFileEntry, with_line_numbers: bool) -> String {
+        let mut section = format!("{}\nFILE: {}\n{}\n", SEPARATOR, file.path, SEPARATOR);
+        push_numbered(&mut section, file.content, with_line_numbers);
+        if is_truncated(file) {
+            let _ = write!(section, "\n{}", TRUNCATED_MARKER);
+        }
+        section.push_str("\n\n");
+        section
+    }
+
+    fn render(&self, doc: &ContextDocument) -> String {
+        let mut output = String::new();
+        if let Some((index, total)) = doc.part {
+            let _ = writeln!(output, "{}\nPART {} OF {}\n{}", SEPARATOR, index, total, SEPARATOR);
+        }
+        if let Some(map) = doc.repo_map {
+            let _ = write!(output, "Repository map:\n{}\n", map);
+        }
+        if doc.inline_tree {
+            let _ = write!(output, "Directory structure:\n{}", doc.directory_structure);
+        } else {
+            let _ = write!(output, "Directory structure:\n{}\n\n", doc.directory_structure);
+            for file in &doc.files {
+                output.push_str(&self.render_file(file, doc.with_line_numbers));
+            }
+        }
+        if let Some(text) = doc.always_apply_text {
+            let _ = writeln!(output, "\n{}\n**ALWAYS APPLY**\n{}\n{}", SEPARATOR, SEPARATOR, text);
+        }
+        output
+    }
+}
+
+// --- XML: mỗi file nằm trong thẻ <file path="..."> ---
+struct XmlFormatter;
+
+fn escape_xml_attr(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// Bọc nội dung trong CDATA; "]]>" trong nội dung được tách thành hai khối CDATA
+fn push_cdata(output: &mut String, content: &str) {
+    output.push_str("<![CDATA[\n");
+    output.push_str(&content.replace("]]>", "]]]]><![CDATA[>"));
+    end_line(output);
+    output.push_str("]]>\n");
+}
+
+impl ContextFormatter for XmlFormatter {
+    fn render_file(&self, file: &ContextFileEntry, with_line_numbers: bool) -> String {
+        let mut section = format!("<file path=\"{}\"", escape_xml_attr(file.path));
+        if is_truncated(file) {
+            section.push_str(" truncated=\"true\"");
+        }
+        section.push_str(">\n");
+        let mut body = String::new();
+        push_numbered(&mut body, file.content, with_line_numbers);
+        push_cdata(&mut section, &body);
+        section.push_str("</file>\n");
+        section
+    }
+
+    fn render(&self, doc: &ContextDocument) -> String {
+        let mut output = String::new();
+        if let Some((index, total)) = doc.part {
+            let _ = writeln!(output, "<part index=\"{}\" total=\"{}\" />", index, total);
+        }
+        if let Some(map) = doc.repo_map {
+            output.push_str("<repo_map>\n");
+            push_cdata(&mut output, map);
+            output.push_str("</repo_map>\n");
+        }
+        output.push_str("<directory_structure>\n");
+        push_cdata(&mut output, doc.directory_structure);
+        output.push_str("</directory_structure>\n");
+        for file in &doc.files {
+            output.push_str(&self.render_file(file, doc.with_line_numbers));
+        }
+        if let Some(text) = doc.always_apply_text {
+            output.push_str("<always_apply>\n");
+            push_cdata(&mut output, text);
+            output.push_str("</always_apply>\n");
+        }
+        output
+    }
+}
+
+// --- MARKDOWN: code block có gắn ngôn ngữ ---
+struct MarkdownFormatter;
+
+// Rào code block phải dài hơn chuỗi dấu ` dài nhất trong nội dung
+fn code_fence(content: &str) -> String {
+    let longest_run = content
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest_run.max(2) + 1)
+}
+
+impl ContextFormatter for MarkdownFormatter {
+    fn render_file(&self, file: &ContextFileEntry, with_line_numbers: bool) -> String {
+        let fence = code_fence(file.content);
+        let mut section = format!(
+            "## {}\n\n{}{}\n",
+            file.path,
+            fence,
+            language_for_path(file.path)
+        );
+        push_numbered(&mut section, file.content, with_line_numbers);
+        end_line(&mut section);
+        let _ = writeln!(section, "{}", fence);
+        if is_truncated(file) {
+            let _ = writeln!(section, "\n_{}_", TRUNCATED_MARKER);
+        }
+        section.push('\n');
+        section
+    }
+
+    fn render(&self, doc: &ContextDocument) -> String {
+        let mut output = String::new();
+        if let Some((index, total)) = doc.part {
+            let _ = writeln!(output, "# Part {} of {}\n", index, total);
+        }
+        if let Some(map) = doc.repo_map {
+            let fence = code_fence(map);
+            let _ = writeln!(output, "## Repository map\n\n{}text\n{}{}\n", fence, map, fence);
+        }
+        let fence = code_fence(doc.directory_structure);
+        let _ = writeln!(
+            output,
+            "## Directory structure\n\n{}text\n{}{}\n",
+            fence, doc.directory_structure, fence
+        );
+        for file in &doc.files {
+            output.push_str(&self.render_file(file, doc.with_line_numbers));
+        }
+        if let Some(text) = doc.always_apply_text {
+            let _ = writeln!(output, "## Always apply\n\n{}", text);
+        }
+        output
+    }
+}
+
+// --- JSON / JSONL: nội dung file luôn được giữ nguyên (không đánh số dòng) ---
+struct JsonFormatter {
+    lines: bool,
+}
+
+fn file_json(file: &ContextFileEntry) -> Value {
+    let mut value = json!({
+        "path": file.path,
+        "language": language_for_path(file.path),
+        "tokens": count_tokens(file.tokenizer, file.content),
+        "content": file.content,
+    });
+    if let Some(action) = file.budget_action {
+        value["budgetAction"] = json!(action);
+    }
+    value
+}
+
+fn part_json(part: Option<(usize, usize)>) -> Value {
+    part.map_or(Value::Null, |(index, total)| json!({ "index": index, "total": total }))
+}
+
+impl ContextFormatter for JsonFormatter {
+    fn render_file(&self, file: &ContextFileEntry, _with_line_numbers: bool) -> String {
+        let mut value = file_json(file);
+        if self.lines {
+            value["type"] = json!("file");
+            format!("{}\n", value)
+        } else {
+            serde_json::to_string_pretty(&value).unwrap_or_default()
+        }
+    }
+
+    fn render(&self, doc: &ContextDocument) -> String {
+        if self.lines {
+            let mut output = String::new();
+            let _ = writeln!(
+                output,
+                "{}",
+                json!({
+                    "type": "directoryStructure",
+                    "part": part_json(doc.part),
+                    "content": doc.directory_structure,
+                })
+            );
+            if let Some(map) = doc.repo_map {
+                let _ = writeln!(output, "{}", json!({ "type": "repoMap", "content": map }));
+            }
+            for file in &doc.files {
+                output.push_str(&self.render_file(file, false));
+            }
+            if let Some(text) = doc.always_apply_text {
+                let _ = writeln!(output, "{}", json!({ "type": "alwaysApply", "content": text }));
+            }
+            output
+        } else {
+            let document = json!({
+                "part": part_json(doc.part),
+                "repoMap": doc.repo_map,
+                "directoryStructure": doc.directory_structure,
+                "files": doc.files.iter().map(file_json).collect::<Vec<_>>(),
+                "alwaysApply": doc.always_apply_text,
+            });
+            serde_json::to_string_pretty(&document).unwrap_or_default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document<'a>(files: Vec<ContextFileEntry<'a>>) -> ContextDocument<'a> {
+        ContextDocument {
+            directory_structure: "└── src/\n    └── main.rs\n",
+            inline_tree: false,
+            part: None,
+            files,
+            with_line_numbers: false,
+            always_apply_text: None,
+            repo_map: None,
+        }
+    }
+
+    #[test]
+    fn structured_formats_carry_path_and_language() {
+        let content = "fn main() {}\n// ```not a fence```\n";
+        let entry = || ContextFileEntry { path: "src/main.rs", content, budget_action: None, tokenizer: Tokenizer::Cl100k };
+
+        let xml = formatter_for(ExportFormat::Xml).render(&document(vec![entry()]));
+        assert!(xml.contains("<file path=\"src/main.rs\">\n<![CDATA[\nfn main() {}\n"));
+
+        let markdown = formatter_for(ExportFormat::Markdown).render(&document(vec![entry()]));
+        assert!(markdown.contains("## src/main.rs\n\n````rust\n"));
+
+        let json = formatter_for(ExportFormat::Json).render(&document(vec![entry()]));
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["files"][0]["language"], "rust");
+        assert_eq!(value["files"][0]["content"], content);
+        assert!(value["files"][0]["tokens"].as_u64().unwrap() > 0);
+
+        let jsonl = formatter_for(ExportFormat::Jsonl).render(&document(vec![entry()]));
+        let lines: Vec<Value> = jsonl.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines[0]["type"], "directoryStructure");
+        assert_eq!(lines[1]["path"], "src/main.rs");
+    }
+
+    #[test]
+    fn xml_content_cannot_break_out_of_its_element() {
+        let content = "if a < b && c { html(\"</file>\") }\nlet s = \"]]>\";\n";
+        let entry = ContextFileEntry { path: "a&b.rs", content, budget_action: None, tokenizer: Tokenizer::Cl100k };
+        let mut doc = document(vec![entry]);
+        doc.always_apply_text = Some("Luôn dùng <T> & ]]> khi cần");
+
+        let xml = formatter_for(ExportFormat::Xml).render(&doc);
+        assert!(xml.contains("<file path=\"a&amp;b.rs\">"));
+        assert_eq!(xml.matches("</file>").count(), 2);
+        assert!(xml.contains("let s = \"]]]]><![CDATA[>\";"));
+
+        // Nội dung ghép lại từ các khối CDATA phải trùng với nội dung gốc
+        let start = xml.find("<file").unwrap();
+        let body = &xml[start..];
+        let body = &body[body.find("<![CDATA[\n").unwrap() + 10..body.find("\n]]>\n</file>").unwrap() + 1];
+        assert_eq!(body.replace("]]]]><![CDATA[>", "]]>"), content);
+        assert!(xml.contains("<always_apply>\n<![CDATA[\nLuôn dùng <T> & ]]]]><![CDATA[> khi cần\n]]>\n</always_apply>"));
+    }
+}
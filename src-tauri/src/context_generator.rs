@@ -1,22 +1,22 @@
 // src-tauri/src/context_generator.rs
-use crate::models::{FileNode, FsEntry}; // <-- Thêm FileNode
+use crate::comment_stripper;
+use crate::context_format::{formatter_for, ContextDocument, ContextFileEntry};
+use crate::context_template::ContextTemplate;
+use crate::models::{
+    BudgetAction, BudgetCutFile, CachedProjectData, ContextMeasurement, ExportFormat,
+    FileContextMeasurement, FileMetadata, FileNode, FsEntry, TokenBudgetReport, Tokenizer,
+}; // <-- Thêm FileNode
+use crate::secret_redactor::SecretRedactor;
+use crate::{outline, tokenizer};
 use lazy_static::lazy_static;
 use regex::Regex;
-use std::collections::{BTreeMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Write as FmtWrite;
 use std::fs;
 use std::path::Path;
+use std::sync::Arc;
 
 lazy_static! {
-    static ref C_STYLE_SINGLE_LINE_COMMENT: Regex = Regex::new(r"//.*").unwrap();
-    static ref C_STYLE_MULTI_LINE_COMMENT: Regex = Regex::new(r"(?s)/\*.*?\*/").unwrap();
-    static ref HASH_COMMENT: Regex = Regex::new(r"#.*").unwrap();
-    static ref HTML_XML_COMMENT: Regex = Regex::new(r"(?s)<!--.*?-->").unwrap();
-    static ref SQL_LUA_COMMENT: Regex = Regex::new(r"--.*").unwrap();
-    static ref VBNET_COMMENT: Regex = Regex::new(r"'.*").unwrap();
-    static ref LISP_COMMENT: Regex = Regex::new(r";.*").unwrap();
-    static ref ERLANG_COMMENT: Regex = Regex::new(r"%.*").unwrap();
-
     // Regex toàn diện để tìm các câu lệnh ghi log gỡ lỗi phổ biến trên nhiều ngôn ngữ
     static ref DEBUG_LOG_REGEX: Regex = Regex::new(concat!(
         r"(?im)^\s*(?:",
@@ -33,64 +33,6 @@ lazy_static! {
     static ref WHITESPACE_REGEX: Regex = Regex::new(r"\s+").unwrap();
 }
 
-fn remove_comments_from_content(content: &str, file_rel_path: &str) -> String {
-    let extension = Path::new(file_rel_path)
-        .extension()
-        .and_then(std::ffi::OsStr::to_str)
-        .unwrap_or("");
-
-    let processed_content = match extension {
-        // Chú thích kiểu C (// và /* */)
-        "js" | "jsx" | "ts" | "tsx" | "rs" | "go" | "c" | "cpp" | "h" | "java" | "cs" | "swift" | "kt" | "css" | "scss" | "less" | "jsonc" | "glsl" | "dart" | "gd" => {
-            let temp = C_STYLE_SINGLE_LINE_COMMENT.replace_all(content, "");
-            C_STYLE_MULTI_LINE_COMMENT.replace_all(&temp, "").to_string()
-        },
-        // Chú thích bằng dấu thăng (#)
-        "py" | "rb" | "sh" | "yml" | "yaml" | "toml" | "dockerfile" | "gitignore" | "r" | "pl" | "pm" | "ps1" | "el" => {
-            HASH_COMMENT.replace_all(content, "").to_string()
-        },
-        // Chú thích kiểu HTML/XML (<!-- -->)
-        "html" | "xml" | "svg" | "md" => {
-            HTML_XML_COMMENT.replace_all(content, "").to_string()
-        },
-        // Chú thích kiểu SQL/Lua (--)
-        "sql" | "lua" | "hs" | "ada" => {
-             SQL_LUA_COMMENT.replace_all(content, "").to_string()
-        },
-        // Chú thích kiểu Lisp (;)
-        "lisp" | "cl" | "scm" => {
-            LISP_COMMENT.replace_all(content, "").to_string()
-        },
-        // Chú thích kiểu Erlang (%)
-        "erl" | "hrl" => {
-            ERLANG_COMMENT.replace_all(content, "").to_string()
-        },
-        // Chú thích kiểu VB (')
-        "vb" | "vbs" => {
-            VBNET_COMMENT.replace_all(content, "").to_string()
-        },
-        // Ngôn ngữ hỗn hợp
-        "php" => {
-            let temp1 = C_STYLE_MULTI_LINE_COMMENT.replace_all(content, "");
-            let temp2 = C_STYLE_SINGLE_LINE_COMMENT.replace_all(&temp1, "");
-            HASH_COMMENT.replace_all(&temp2, "").to_string()
-        },
-        "vue" | "astro" => {
-            let temp = HTML_XML_COMMENT.replace_all(content, "");
-            let temp2 = C_STYLE_MULTI_LINE_COMMENT.replace_all(&temp, "");
-            C_STYLE_SINGLE_LINE_COMMENT.replace_all(&temp2, "").to_string()
-        }
-        _ => content.to_string(),
-    };
-
-    // Loại bỏ các dòng trống được tạo ra sau khi xóa comment
-    processed_content
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .collect::<Vec<_>>()
-        .join("\n")
-}
-
 fn remove_debug_logs_from_content(content: &str) -> String {
     // Replace found debug logs with an empty string
     DEBUG_LOG_REGEX.replace_all(content, "").to_string()
@@ -124,10 +66,8 @@ fn format_tree_super_compressed(
     tree: &BTreeMap<String, FsEntry>,
     prefix: &str,
     current_path: &Path,
-    root_path: &Path,
     output: &mut String,
-    without_comments: bool,
-    remove_debug_logs: bool,
+    inline_contents: &HashMap<String, String>,
     exclude_extensions_set: &HashSet<&str>,
 ) {
     let mut entries = tree.iter().peekable();
@@ -140,19 +80,11 @@ fn format_tree_super_compressed(
             FsEntry::File => {
                 let extension = new_path.extension().and_then(std::ffi::OsStr::to_str).unwrap_or("");
                 if !exclude_extensions_set.contains(extension) {
-                    let full_path = root_path.join(&new_path);
-                    let content_str = if let Ok(mut content) = fs::read_to_string(&full_path) {
-                        let rel_path_str = new_path.to_string_lossy();
-                        if without_comments {
-                            content = remove_comments_from_content(&content, &rel_path_str);
-                        }
-                        if remove_debug_logs {
-                            content = remove_debug_logs_from_content(&content);
-                        }
-                        format!("[{}]", compress_content_for_tree(&content))
-                    } else {
-                        "[KHÔNG THỂ ĐỌC FILE]".to_string()
-                    };
+                    let rel_path_str = new_path.to_string_lossy().replace("\\", "/");
+                    let content_str = inline_contents
+                        .get(&rel_path_str)
+                        .map(String::as_str)
+                        .unwrap_or("[KHÔNG THỂ ĐỌC FILE]");
                     let _ = writeln!(output, "{}{}{} {}", prefix, connector, name, content_str);
                 } else {
                     let _ = writeln!(output, "{}{}{} [BỊ LOẠI TRỪ]", prefix, connector, name);
@@ -161,12 +93,22 @@ fn format_tree_super_compressed(
             FsEntry::Directory(children) => {
                 let _ = writeln!(output, "{}{}{}/", prefix, connector, name);
                 let new_prefix = format!("{}{}", prefix, if is_last { "    " } else { "│   " });
-                format_tree_super_compressed(children, &new_prefix, &new_path, root_path, output, without_comments, remove_debug_logs, exclude_extensions_set);
+                format_tree_super_compressed(children, &new_prefix, &new_path, output, inline_contents, exclude_extensions_set);
             }
         }
     }
 }
 
+fn collect_tree_files(tree: &BTreeMap<String, FsEntry>, current_path: &Path, output: &mut Vec<String>) {
+    for (name, entry) in tree {
+        let new_path = current_path.join(name);
+        match entry {
+            FsEntry::File => output.push(new_path.to_string_lossy().replace("\\", "/")),
+            FsEntry::Directory(children) => collect_tree_files(children, &new_path, output),
+        }
+    }
+}
+
 // --- HÀM HELPER MỚI: Chuyển đổi từ FileNode (của cache) sang FsEntry (của builder) ---
 fn convert_file_node_to_fs_entry(node: &FileNode) -> FsEntry {
     if let Some(children) = &node.children {
@@ -183,7 +125,7 @@ fn convert_file_node_to_fs_entry(node: &FileNode) -> FsEntry {
 // === BẮT ĐẦU PHẦN SỬA LỖI DỨT ĐIỂM ===
 pub fn expand_group_paths_to_files(
     group_paths: &[String],
-    metadata_cache: &BTreeMap<String, crate::models::FileMetadata>,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
     _root_path: &Path, // Không cần truy cập đĩa nữa
 ) -> Vec<String> {
     let mut all_files_in_group: HashSet<String> = HashSet::new();
@@ -217,25 +159,585 @@ pub fn expand_group_paths_to_files(
 }
 // === KẾT THÚC PHẦN SỬA LỖI DỨT ĐIỂM ===
 
-// --- CẬP NHẬT CHỮ KÝ VÀ LOGIC CỦA HÀM NÀY ---
+/// Các cài đặt quyết định cách tạo ngữ cảnh.
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    pub use_full_tree: bool,
+    pub with_line_numbers: bool,
+    pub without_comments: bool,
+    pub remove_debug_logs: bool,
+    pub super_compressed: bool,
+    // Chỉ giữ khai báo của các file mã nguồn (xem `outline`)
+    pub outline: bool,
+    pub always_apply_text: Option<String>,
+    pub exclude_extensions: Option<Vec<String>>,
+    pub format: ExportFormat,
+    // Template của người dùng, nếu có sẽ thay cho `format`
+    pub template: Option<Arc<ContextTemplate>>,
+    // Biến bổ sung cho template: group.name, git.branch, project.name...
+    pub template_vars: BTreeMap<String, String>,
+    // Bản đồ dự án (xem `symbol_index`) chèn vào đầu ngữ cảnh
+    pub repo_map: Option<String>,
+    // Che bí mật trong nội dung file; báo cáo lấy qua `SecretRedactor::take_report`
+    pub redactor: Option<Arc<SecretRedactor>>,
+    // Tokenizer dùng để đếm token khi chia phần, áp giới hạn và đo ngữ cảnh
+    pub tokenizer: Tokenizer,
+}
+
+impl ExportOptions {
+    /// Lấy các cài đặt xuất đã lưu của dự án (mặc định giống UI).
+    pub fn from_project_data(data: &CachedProjectData) -> Self {
+        Self {
+            use_full_tree: data.export_use_full_tree.unwrap_or(false),
+            with_line_numbers: data.export_with_line_numbers.unwrap_or(true),
+            without_comments: data.export_without_comments.unwrap_or(false),
+            remove_debug_logs: data.export_remove_debug_logs.unwrap_or(false),
+            super_compressed: data.export_super_compressed.unwrap_or(false),
+            outline: data.export_outline.unwrap_or(false),
+            always_apply_text: data.always_apply_text.clone(),
+            exclude_extensions: data.export_exclude_extensions.clone(),
+            format: data.export_format.unwrap_or_default(),
+            template: None,
+            template_vars: BTreeMap::new(),
+            repo_map: None,
+            redactor: SecretRedactor::from_project_data(data).map(Arc::new),
+            // Tokenizer trong AppSettings ở lần quét gần nhất, cùng tokenizer với số token trong cache
+            tokenizer: data.stats.tokenizer,
+        }
+    }
+}
+
+/// Giới hạn token áp dụng khi tạo ngữ cảnh cho một nhóm.
+pub struct TokenBudget<'a> {
+    pub token_limit: usize,
+    // Các đường dẫn được chọn trực tiếp trong nhóm, được ưu tiên giữ lại
+    pub priority_paths: &'a [String],
+}
+
+/// Đếm token theo tokenizer của lần xuất (xem `ExportOptions::tokenizer`).
+pub fn count_tokens(tokenizer: Tokenizer, text: &str) -> usize {
+    tokenizer::count(tokenizer, text)
+}
+
+// Nội dung của một file trong ngữ cảnh, có thể bị thu gọn/cắt bớt để vừa giới hạn token
+struct ContextFile {
+    path: String,
+    body: String,
+    tokens: usize,
+    original_tokens: usize,
+    action: Option<BudgetAction>,
+}
+
+// Đọc file và áp dụng vùng loại trừ, xóa comment, xóa debug log, dàn ý
+fn read_processed_content(
+    root_path: &Path,
+    file_rel_path: &str,
+    options: &ExportOptions,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+) -> Option<String> {
+    let mut content = fs::read_to_string(root_path.join(file_rel_path)).ok()?;
+
+    // --- NEW LOGIC: APPLY EXCLUSIONS FIRST ---
+    if let Some(metadata) = metadata_cache.get(file_rel_path) {
+        if let Some(ranges) = &metadata.excluded_ranges {
+            if !ranges.is_empty() {
+                let mut final_content = String::with_capacity(content.len());
+                let mut last_index = 0;
+                for (start, end) in ranges {
+                    if *start >= last_index {
+                        final_content.push_str(&content[last_index..*start]);
+                    }
+                    last_index = *end;
+                }
+                if last_index < content.len() {
+                    final_content.push_str(&content[last_index..]);
+                }
+                content = final_content;
+            }
+        }
+    }
+
+    // Che bí mật trước các bước biến đổi khác để số dòng trong báo cáo khớp với file
+    // (lệch đi nếu file có vùng loại trừ)
+    if let Some(redactor) = &options.redactor {
+        content = redactor.redact(&content, file_rel_path);
+    }
+    if options.without_comments {
+        content = comment_stripper::strip_comments(&content, file_rel_path);
+    }
+    if options.remove_debug_logs {
+        content = remove_debug_logs_from_content(&content);
+    }
+    if options.outline {
+        if let Some(outlined) = outline::outline_content(&content, file_rel_path) {
+            content = outlined;
+        }
+    }
+    if options.super_compressed {
+        content = compress_content_for_tree(&content);
+    }
+    Some(content)
+}
+
+// Chế độ siêu nén của định dạng văn bản thuần hiển thị nội dung ngay trong cây thư mục
+fn uses_inline_tree(options: &ExportOptions) -> bool {
+    options.super_compressed && options.format == ExportFormat::Plain
+}
+
+fn render_inline(file: &ContextFile) -> String {
+    match file.action {
+        Some(BudgetAction::Omitted) => "[ĐÃ LƯỢC BỎ ĐỂ VỪA GIỚI HẠN TOKEN]".to_string(),
+        Some(BudgetAction::Truncated) => format!("[{} ...ĐÃ CẮT BỚT]", file.body),
+        _ => format!("[{}]", file.body),
+    }
+}
+
+fn file_entry(file: &ContextFile, tokenizer: Tokenizer) -> ContextFileEntry<'_> {
+    ContextFileEntry {
+        path: &file.path,
+        content: &file.body,
+        budget_action: file.action,
+        tokenizer,
+    }
+}
+
+// Số token và số byte của riêng một file khi được trình bày, dùng để chia phần và đo ngữ cảnh
+fn rendered_file_size(file: &ContextFile, options: &ExportOptions) -> (usize, usize) {
+    if uses_inline_tree(options) {
+        let text = render_inline(file);
+        (count_tokens(options.tokenizer, &text), text.len())
+    } else if file.action == Some(BudgetAction::Omitted) {
+        (0, 0) // File bị lược bỏ không xuất hiện trong ngữ cảnh
+    } else if let Some(template) = &options.template {
+        // Phần tăng thêm khi template có thêm file này
+        let document = |files| ContextDocument {
+            directory_structure: "",
+            inline_tree: false,
+            part: None,
+            files,
+            with_line_numbers: options.with_line_numbers,
+            always_apply_text: None,
+            repo_map: None,
+        };
+        let with_file = template.render(&document(vec![file_entry(file, options.tokenizer)]), &options.template_vars);
+        let without_file = template.render(&document(Vec::new()), &options.template_vars);
+        (
+            count_tokens(options.tokenizer, &with_file).saturating_sub(count_tokens(options.tokenizer, &without_file)),
+            with_file.len().saturating_sub(without_file.len()),
+        )
+    } else {
+        let text = formatter_for(options.format).render_file(&file_entry(file, options.tokenizer), options.with_line_numbers);
+        (count_tokens(options.tokenizer, &text), text.len())
+    }
+}
+
+fn assemble_context(
+    tree: &BTreeMap<String, FsEntry>,
+    files: &[ContextFile],
+    options: &ExportOptions,
+    exclude_set: &HashSet<&str>,
+) -> String {
+    assemble_context_part(tree, files, options, exclude_set, None)
+}
+
+// Vị trí của một phần khi ngữ cảnh được chia nhỏ
+struct PartInfo<'a> {
+    index: usize,
+    total: usize,
+    // Đường dẫn file -> số thứ tự phần chứa nội dung của file đó
+    part_of_file: &'a HashMap<String, usize>,
+}
+
+fn assemble_context_part(
+    tree: &BTreeMap<String, FsEntry>,
+    files: &[ContextFile],
+    options: &ExportOptions,
+    exclude_set: &HashSet<&str>,
+    part: Option<&PartInfo>,
+) -> String {
+    let mut directory_structure = String::new();
+    let inline_tree = uses_inline_tree(options);
+    if inline_tree {
+        let mut inline_contents: HashMap<String, String> = HashMap::new();
+        if let Some(part) = part {
+            // Nội dung của file nằm ở phần khác được thay bằng chỉ dẫn tới phần đó
+            for (path, index) in part.part_of_file {
+                inline_contents.insert(path.clone(), format!("[PHẦN {}/{}]", index, part.total));
+            }
+        }
+        for file in files {
+            inline_contents.insert(file.path.clone(), render_inline(file));
+        }
+        format_tree_super_compressed(
+            tree,
+            "",
+            Path::new(""),
+            &mut directory_structure,
+            &inline_contents,
+            exclude_set,
+        );
+    } else {
+        format_tree(tree, "", &mut directory_structure);
+    }
+
+    let document = ContextDocument {
+        directory_structure: &directory_structure,
+        inline_tree,
+        part: part.map(|part| (part.index, part.total)),
+        files: if inline_tree {
+            Vec::new()
+        } else {
+            files
+                .iter()
+                .filter(|file| file.action != Some(BudgetAction::Omitted))
+                .map(|file| file_entry(file, options.tokenizer))
+                .collect()
+        },
+        with_line_numbers: options.with_line_numbers,
+        always_apply_text: options
+            .always_apply_text
+            .as_deref()
+            .filter(|text| !text.trim().is_empty()),
+        repo_map: options.repo_map.as_deref().filter(|map| !map.is_empty()),
+    };
+    match &options.template {
+        Some(template) => template.render(&document, &options.template_vars),
+        None => formatter_for(options.format).render(&document),
+    }
+}
+
+// Cắt văn bản (ưu tiên tại ranh giới dòng) sao cho không vượt quá `max_tokens`
+fn truncate_to_tokens(tokenizer: Tokenizer, text: &str, max_tokens: usize) -> String {
+    let total_tokens = count_tokens(tokenizer, text).max(1);
+    let mut end = text.len() * max_tokens / total_tokens;
+    loop {
+        while end > 0 && !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        let candidate = match text[..end].rfind('\n') {
+            Some(line_end) if line_end > end / 2 => &text[..line_end],
+            _ => &text[..end],
+        };
+        if end == 0 || count_tokens(tokenizer, candidate) <= max_tokens {
+            return candidate.to_string();
+        }
+        end = end * 9 / 10;
+    }
+}
+
+// Số token tối thiểu để việc giữ lại phần đầu của file còn có ý nghĩa
+const MIN_TRUNCATED_TOKENS: usize = 64;
+
+/// Rút gọn thành dàn ý, thu gọn, cắt bớt rồi lược bỏ các file có độ ưu tiên thấp nhất cho đến khi
+/// ngữ cảnh vừa với giới hạn token. File được chọn trực tiếp trong nhóm được
+/// ưu tiên giữ lại; trong cùng mức ưu tiên, file lớn hơn bị xử lý trước.
+fn fit_files_to_budget(
+    tree: &BTreeMap<String, FsEntry>,
+    files: &mut [ContextFile],
+    options: &ExportOptions,
+    exclude_set: &HashSet<&str>,
+    budget: &TokenBudget,
+) -> TokenBudgetReport {
+    let limit = budget.token_limit;
+    let original_token_count = count_tokens(options.tokenizer, &assemble_context(tree, files, options, exclude_set));
+
+    let mut order: Vec<usize> = (0..files.len()).collect();
+    order.sort_by_key(|&i| {
+        let is_priority = budget.priority_paths.iter().any(|p| p == &files[i].path);
+        (is_priority, std::cmp::Reverse(files[i].tokens))
+    });
+
+    let mut total = original_token_count;
+    // Bậc 1: chỉ giữ khai báo của các file mã nguồn lớn (bỏ qua nếu đã ở chế độ dàn ý/siêu nén)
+    if !options.outline && !options.super_compressed {
+        for &i in &order {
+            if total <= limit {
+                break;
+            }
+            let Some(outlined) = outline::outline_content(&files[i].body, &files[i].path) else {
+                continue;
+            };
+            let outlined_tokens = count_tokens(options.tokenizer, &outlined);
+            if outlined_tokens < files[i].tokens {
+                total -= files[i].tokens - outlined_tokens;
+                files[i].body = outlined;
+                files[i].tokens = outlined_tokens;
+                files[i].action = Some(BudgetAction::Outlined);
+            }
+        }
+    }
+
+    // Bậc 2: thu gọn các file lớn thành một dòng (chế độ siêu nén đã ở dạng này)
+    if !options.super_compressed {
+        for &i in &order {
+            if total <= limit {
+                break;
+            }
+            let compressed = compress_content_for_tree(&files[i].body);
+            let compressed_tokens = count_tokens(options.tokenizer, &compressed);
+            if compressed_tokens < files[i].tokens {
+                total -= files[i].tokens - compressed_tokens;
+                files[i].body = compressed;
+                files[i].tokens = compressed_tokens;
+                files[i].action = Some(BudgetAction::Compressed);
+            }
+        }
+    }
+
+    // Bậc 3: cắt bớt hoặc lược bỏ. Số token thực tế của ngữ cảnh có thể lệch
+    // đôi chút so với tổng từng phần nên lặp lại với số đếm chính xác.
+    total = count_tokens(options.tokenizer, &assemble_context(tree, files, options, exclude_set));
+    while total > limit {
+        let mut changed = false;
+        for &i in &order {
+            if total <= limit {
+                break;
+            }
+            if files[i].action == Some(BudgetAction::Omitted) {
+                continue;
+            }
+            let overshoot = total - limit;
+            let keep_tokens = files[i].tokens.saturating_sub(overshoot);
+            if keep_tokens >= MIN_TRUNCATED_TOKENS {
+                files[i].body = truncate_to_tokens(options.tokenizer, &files[i].body, keep_tokens);
+                files[i].action = Some(BudgetAction::Truncated);
+                let new_tokens = count_tokens(options.tokenizer, &files[i].body);
+                total -= files[i].tokens - new_tokens.min(files[i].tokens);
+                files[i].tokens = new_tokens;
+            } else {
+                total -= files[i].tokens.min(total);
+                files[i].body.clear();
+                files[i].tokens = 0;
+                files[i].action = Some(BudgetAction::Omitted);
+            }
+            changed = true;
+        }
+        total = count_tokens(options.tokenizer, &assemble_context(tree, files, options, exclude_set));
+        if !changed {
+            // Chỉ riêng cây thư mục đã vượt giới hạn, không thể cắt thêm
+            break;
+        }
+    }
+
+    let cut_files = files
+        .iter()
+        .filter_map(|file| {
+            file.action.map(|action| BudgetCutFile {
+                path: file.path.clone(),
+                action,
+                original_tokens: file.original_tokens,
+                final_tokens: file.tokens,
+            })
+        })
+        .collect();
+
+    TokenBudgetReport {
+        token_limit: limit,
+        original_token_count,
+        final_token_count: total,
+        cut_files,
+    }
+}
+
 pub fn generate_context_from_files(
     root_path_str: &str,
     file_paths: &[String],
-    use_full_tree: bool,
     full_project_tree: &Option<FileNode>,
-    with_line_numbers: bool,
-    without_comments: bool,
-    remove_debug_logs: bool,
-    super_compressed: bool,
-    always_apply_text: &Option<String>,
-    exclude_extensions: &Option<Vec<String>>,
-    metadata_cache: &BTreeMap<String, crate::models::FileMetadata>,
+    options: &ExportOptions,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
 ) -> Result<String, String> {
+    generate_context_with_budget(root_path_str, file_paths, full_project_tree, options, metadata_cache, None)
+        .map(|(context, _)| context)
+}
+
+/// Giống `generate_context_from_files`, nhưng nếu có `budget` thì ngữ cảnh được
+/// thu gọn cho vừa giới hạn token và trả về báo cáo các file đã bị cắt.
+pub fn generate_context_with_budget(
+    root_path_str: &str,
+    file_paths: &[String],
+    full_project_tree: &Option<FileNode>,
+    options: &ExportOptions,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    budget: Option<TokenBudget>,
+) -> Result<(String, Option<TokenBudgetReport>), String> {
+    render_with_budget(root_path_str, file_paths, full_project_tree, options, metadata_cache, budget)
+        .map(|(context, _, report)| (context, report))
+}
+
+/// Đo ngữ cảnh đúng như khi xuất (cùng cài đặt, vùng loại trừ và giới hạn token):
+/// tổng số token/byte và phần đóng góp của từng file. Phần còn lại (cây thư mục,
+/// tiêu đề, văn bản ALWAYS APPLY...) được tính vào `overhead_*`.
+pub fn measure_context(
+    root_path_str: &str,
+    file_paths: &[String],
+    full_project_tree: &Option<FileNode>,
+    options: &ExportOptions,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    budget: Option<TokenBudget>,
+) -> Result<ContextMeasurement, String> {
+    let (context, files, _) =
+        render_with_budget(root_path_str, file_paths, full_project_tree, options, metadata_cache, budget)?;
+    let files: Vec<FileContextMeasurement> = files
+        .iter()
+        .map(|file| {
+            let (tokens, bytes) = rendered_file_size(file, options);
+            FileContextMeasurement {
+                path: file.path.clone(),
+                tokens,
+                bytes,
+                budget_action: file.action,
+            }
+        })
+        .collect();
+    let total_tokens = count_tokens(options.tokenizer, &context);
+    let total_bytes = context.len();
+    Ok(ContextMeasurement {
+        tokenizer: options.tokenizer,
+        total_tokens,
+        total_bytes,
+        overhead_tokens: total_tokens.saturating_sub(files.iter().map(|f| f.tokens).sum()),
+        overhead_bytes: total_bytes.saturating_sub(files.iter().map(|f| f.bytes).sum()),
+        files,
+    })
+}
+
+fn render_with_budget(
+    root_path_str: &str,
+    file_paths: &[String],
+    full_project_tree: &Option<FileNode>,
+    options: &ExportOptions,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    budget: Option<TokenBudget>,
+) -> Result<(String, Vec<ContextFile>, Option<TokenBudgetReport>), String> {
+    let (tree, mut files) = prepare_context(
+        root_path_str,
+        file_paths,
+        full_project_tree,
+        options,
+        metadata_cache,
+        budget.is_some(),
+    )?;
+    let exclude_set = exclude_extension_set(options);
+
+    let report = budget
+        .filter(|budget| budget.token_limit > 0)
+        .map(|budget| fit_files_to_budget(&tree, &mut files, options, &exclude_set, &budget));
+
+    Ok((assemble_context(&tree, &files, options, &exclude_set), files, report))
+}
+
+/// Chia ngữ cảnh thành nhiều phần tại ranh giới giữa các file, mỗi phần không
+/// vượt quá `tokens_per_part` token. Mỗi phần lặp lại cây thư mục và có dòng
+/// "PART i OF N" để có thể dùng độc lập. Một file lớn hơn giới hạn vẫn được
+/// giữ nguyên trong một phần riêng.
+pub fn generate_context_chunks(
+    root_path_str: &str,
+    file_paths: &[String],
+    full_project_tree: &Option<FileNode>,
+    options: &ExportOptions,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    tokens_per_part: usize,
+) -> Result<Vec<String>, String> {
+    let (tree, mut files) = prepare_context(
+        root_path_str,
+        file_paths,
+        full_project_tree,
+        options,
+        metadata_cache,
+        false,
+    )?;
+    let exclude_set = exclude_extension_set(options);
+    for file in files.iter_mut() {
+        file.tokens = rendered_file_size(file, options).0;
+    }
+
+    // Phần chung của mọi phần: cây thư mục, dòng đánh số phần, văn bản ALWAYS APPLY.
+    // Đo với số phần lớn nhất có thể; ở chế độ siêu nén mọi file trong cây được thay bằng
+    // chỉ dẫn "[PHẦN i/N]" giống như khi trình bày một phần không chứa file đó
+    let max_parts = files.len().max(1);
+    let placeholders: HashMap<String, usize> = if uses_inline_tree(options) {
+        files.iter().map(|file| (file.path.clone(), max_parts)).collect()
+    } else {
+        HashMap::new()
+    };
+    let marker_info = PartInfo { index: max_parts, total: max_parts, part_of_file: &placeholders };
+    let overhead = count_tokens(options.tokenizer, &assemble_context_part(&tree, &[], options, &exclude_set, Some(&marker_info)));
+
+    let mut parts: Vec<Vec<ContextFile>> = vec![Vec::new()];
+    let mut current_tokens = overhead;
+    for file in files {
+        let last = parts.last_mut().expect("parts is never empty");
+        if !last.is_empty() && current_tokens + file.tokens > tokens_per_part {
+            current_tokens = overhead;
+            parts.push(Vec::new());
+        }
+        current_tokens += file.tokens;
+        parts.last_mut().expect("parts is never empty").push(file);
+    }
+
+    // Số token khi ghép lại có thể lệch đôi chút so với tổng ước lượng theo từng file:
+    // phần nào vẫn vượt giới hạn (và có hơn một file) thì tách đôi rồi trình bày lại
+    loop {
+        let rendered = render_parts(&tree, &parts, options, &exclude_set);
+        let oversized = rendered.iter().zip(&parts).position(|(text, part)| {
+            part.len() > 1 && count_tokens(options.tokenizer, text) > tokens_per_part
+        });
+        match oversized {
+            Some(i) => {
+                let half = parts[i].len() / 2;
+                let tail = parts[i].split_off(half);
+                parts.insert(i + 1, tail);
+            }
+            None => return Ok(rendered),
+        }
+    }
+}
+
+fn render_parts(
+    tree: &BTreeMap<String, FsEntry>,
+    parts: &[Vec<ContextFile>],
+    options: &ExportOptions,
+    exclude_set: &HashSet<&str>,
+) -> Vec<String> {
+    let total = parts.len();
+    let part_of_file: HashMap<String, usize> = parts
+        .iter()
+        .enumerate()
+        .flat_map(|(i, part)| part.iter().map(move |file| (file.path.clone(), i + 1)))
+        .collect();
+
+    parts
+        .iter()
+        .enumerate()
+        .map(|(i, part)| {
+            let info = PartInfo { index: i + 1, total, part_of_file: &part_of_file };
+            assemble_context_part(tree, part, options, exclude_set, Some(&info))
+        })
+        .collect()
+}
+
+fn exclude_extension_set(options: &ExportOptions) -> HashSet<&str> {
+    options
+        .exclude_extensions
+        .as_ref()
+        .map(|v| v.iter().map(|s| s.as_str()).collect())
+        .unwrap_or_default()
+}
+
+// Dựng cây thư mục và đọc nội dung đã xử lý của các file sẽ xuất
+fn prepare_context(
+    root_path_str: &str,
+    file_paths: &[String],
+    full_project_tree: &Option<FileNode>,
+    options: &ExportOptions,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    count_file_tokens: bool,
+) -> Result<(BTreeMap<String, FsEntry>, Vec<ContextFile>), String> {
     let root_path = Path::new(root_path_str);
     let mut tree_builder_root = BTreeMap::new();
 
     // --- LOGIC IF/ELSE MỚI ĐỂ XÂY DỰNG CÂY THƯ MỤC ---
-    if use_full_tree {
+    if options.use_full_tree {
         if let Some(tree_node) = full_project_tree {
             if let FsEntry::Directory(root_children) = convert_file_node_to_fs_entry(tree_node) {
                 tree_builder_root = root_children;
@@ -267,104 +769,168 @@ pub fn generate_context_from_files(
         }
     }
 
-    let exclude_set: HashSet<_> = exclude_extensions
-        .as_ref()
-        .map(|v| v.iter().map(|s| s.as_str()).collect())
-        .unwrap_or_default();
+    let exclude_set = exclude_extension_set(options);
 
-    let mut directory_structure = String::new();
-    let final_context = if super_compressed {
-        format_tree_super_compressed(
-            &tree_builder_root,
-            "",
-            Path::new(""),
-            root_path,
-            &mut directory_structure,
-            without_comments,
-            remove_debug_logs,
-            &exclude_set,
-        );
-        format!("Directory structure:\n{}", directory_structure)
+    // Chế độ siêu nén hiển thị nội dung của mọi file trong cây thư mục
+    let mut content_paths = if options.super_compressed {
+        let mut paths = Vec::new();
+        collect_tree_files(&tree_builder_root, Path::new(""), &mut paths);
+        paths
     } else {
-        format_tree(&tree_builder_root, "", &mut directory_structure);
-
-        let mut file_contents_string = String::new();
-        let mut sorted_files = file_paths.to_vec();
-        sorted_files.sort();
-
-        let final_files: Vec<_> = sorted_files
-                .into_iter()
-                .filter(|file_rel_path| {
-                    let extension = Path::new(file_rel_path)
-                        .extension()
-                        .and_then(std::ffi::OsStr::to_str)
-                        .unwrap_or("");
-                    !exclude_set.contains(extension)
-                })
-                .collect();
-
-        for file_rel_path in final_files {
-            let file_path = root_path.join(&file_rel_path);
-            if let Ok(mut content) = fs::read_to_string(&file_path) {
-                
-                // --- NEW LOGIC: APPLY EXCLUSIONS FIRST ---
-                if let Some(metadata) = metadata_cache.get(&file_rel_path) {
-                    if let Some(ranges) = &metadata.excluded_ranges {
-                        if !ranges.is_empty() {
-                            let mut final_content = String::with_capacity(content.len());
-                            let mut last_index = 0;
-                            for (start, end) in ranges {
-                                if *start >= last_index {
-                                    final_content.push_str(&content[last_index..*start]);
-                                }
-                                last_index = *end;
-                            }
-                            if last_index < content.len() {
-                                final_content.push_str(&content[last_index..]);
-                            }
-                            content = final_content;
-                        }
-                    }
-                }
-                
-                let mut processed_content = content;
-                
-                if without_comments {
-                    processed_content = remove_comments_from_content(&processed_content, &file_rel_path);
-                }
+        file_paths.to_vec()
+    };
+    content_paths.sort();
 
-                if remove_debug_logs {
-                    processed_content = remove_debug_logs_from_content(&processed_content);
-                }
+    let files: Vec<ContextFile> = content_paths
+        .into_iter()
+        .filter(|file_rel_path| {
+            let extension = Path::new(file_rel_path)
+                .extension()
+                .and_then(std::ffi::OsStr::to_str)
+                .unwrap_or("");
+            !exclude_set.contains(extension)
+        })
+        .filter_map(|path| {
+            let body = read_processed_content(root_path, &path, options, metadata_cache)?;
+            let tokens = if count_file_tokens { count_tokens(options.tokenizer, &body) } else { 0 };
+            Some(ContextFile { path, body, tokens, original_tokens: tokens, action: None })
+        })
+        .collect();
 
-                let header = format!("================================================\nFILE: {}\n================================================\n", file_rel_path.replace("\\", "/"));
-                file_contents_string.push_str(&header);
-                if with_line_numbers {
-                    for (i, line) in processed_content.lines().enumerate() {
-                        let _ = writeln!(file_contents_string, "{}: {}", i + 1, line);
-                    }
-                } else {
-                    file_contents_string.push_str(&processed_content);
-                }
-                file_contents_string.push_str("\n\n");
-            }
-        }
-        format!(
-            "Directory structure:\n{}\n\n{}",
-            directory_structure, file_contents_string
+    Ok((tree_builder_root, files))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn measurement_matches_exported_context() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let commented = "// Một chú thích rất dài sẽ bị loại bỏ khi xuất ngữ cảnh\nfn main() {}\n";
+        fs::write(root.join("main.rs"), commented).unwrap();
+        fs::write(root.join("lib.rs"), "pub fn add(a: i32, b: i32) -> i32 { a + b }\n").unwrap();
+        fs::write(root.join("notes.md"), "# Ghi chú\n").unwrap();
+
+        let files = vec!["lib.rs".to_string(), "main.rs".to_string(), "notes.md".to_string()];
+        let options = ExportOptions {
+            with_line_numbers: true,
+            without_comments: true,
+            exclude_extensions: Some(vec!["md".to_string()]),
+            ..Default::default()
+        };
+        let context =
+            generate_context_from_files(root.to_str().unwrap(), &files, &None, &options, &BTreeMap::new()).unwrap();
+        let measurement =
+            measure_context(root.to_str().unwrap(), &files, &None, &options, &BTreeMap::new(), None).unwrap();
+
+        assert_eq!(measurement.total_tokens, count_tokens(options.tokenizer, &context));
+        assert_eq!(measurement.total_bytes, context.len());
+        let paths: Vec<&str> = measurement.files.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["lib.rs", "main.rs"]);
+        // Chú thích đã bị loại bỏ nên file đóng góp ít token hơn nội dung gốc
+        let with_comments = ExportOptions { without_comments: false, ..options.clone() };
+        let raw = measure_context(root.to_str().unwrap(), &files, &None, &with_comments, &BTreeMap::new(), None)
+            .unwrap();
+        assert!(measurement.files[1].tokens < raw.files[1].tokens);
+        assert_eq!(measurement.files[0], raw.files[0]);
+        assert!(!context.contains("chú thích"));
+        let file_tokens: usize = measurement.files.iter().map(|f| f.tokens).sum();
+        assert_eq!(file_tokens + measurement.overhead_tokens, measurement.total_tokens);
+    }
+
+    #[test]
+    fn budget_cuts_non_priority_files_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let big_line = "let value = compute_something(alpha, beta, gamma);\n";
+        fs::write(root.join("keep.rs"), big_line.repeat(40)).unwrap();
+        fs::create_dir(root.join("extra")).unwrap();
+        fs::write(root.join("extra/big.rs"), big_line.repeat(400)).unwrap();
+
+        let files = vec!["extra/big.rs".to_string(), "keep.rs".to_string()];
+        let options = ExportOptions { with_line_numbers: true, ..Default::default() };
+        let priority = vec!["keep.rs".to_string()];
+        let (context, report) = generate_context_with_budget(
+            root.to_str().unwrap(),
+            &files,
+            &None,
+            &options,
+            &BTreeMap::new(),
+            Some(TokenBudget { token_limit: 1500, priority_paths: &priority }),
         )
-    };
+        .unwrap();
 
-    let mut final_context_with_suffix = final_context;
+        let report = report.expect("budget report");
+        assert!(report.original_token_count > 1500);
+        assert!(report.final_token_count <= 1500);
+        assert_eq!(report.final_token_count, count_tokens(options.tokenizer, &context));
+        assert!(report.cut_files.iter().all(|cut| cut.path == "extra/big.rs"));
+        assert!(context.contains("40: let value"));
+    }
+
+    #[test]
+    fn chunks_split_at_file_boundaries_and_repeat_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let line = "fn handler() { do_work(first, second, third); }\n";
+        let files: Vec<String> = (0..6).map(|i| format!("src/mod{}.rs", i)).collect();
+        fs::create_dir(root.join("src")).unwrap();
+        for file in &files {
+            fs::write(root.join(file), line.repeat(30)).unwrap();
+        }
 
-    if let Some(text) = always_apply_text {
-        if !text.trim().is_empty() {
-            let _ = writeln!(final_context_with_suffix, "\n================================================");
-            let _ = writeln!(final_context_with_suffix, "**ALWAYS APPLY**");
-            let _ = writeln!(final_context_with_suffix, "================================================");
-            let _ = writeln!(final_context_with_suffix, "{}", text);
+        let options = ExportOptions { with_line_numbers: true, ..Default::default() };
+        let parts = generate_context_chunks(
+            root.to_str().unwrap(),
+            &files,
+            &None,
+            &options,
+            &BTreeMap::new(),
+            1200,
+        )
+        .unwrap();
+
+        assert!(parts.len() > 1);
+        for (i, part) in parts.iter().enumerate() {
+            assert!(part.contains(&format!("PART {} OF {}", i + 1, parts.len())));
+            assert!(part.contains("Directory structure:\n└── src/\n"));
+            assert!(count_tokens(options.tokenizer, part) <= 1200);
+        }
+        for file in &files {
+            let header = format!("FILE: {}\n", file);
+            assert_eq!(parts.iter().filter(|part| part.contains(&header)).count(), 1);
         }
     }
 
-    Ok(final_context_with_suffix)
+    #[test]
+    fn super_compressed_chunks_stay_within_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let line = "fn handler() { do_work(first, second, third); }\n";
+        let files: Vec<String> = (0..40).map(|i| format!("src/module_with_long_name_{}.rs", i)).collect();
+        fs::create_dir(root.join("src")).unwrap();
+        for file in &files {
+            fs::write(root.join(file), line.repeat(8)).unwrap();
+        }
+
+        let options = ExportOptions { super_compressed: true, ..Default::default() };
+        let limit = 1500;
+        let parts = generate_context_chunks(root.to_str().unwrap(), &files, &None, &options, &BTreeMap::new(), limit)
+            .unwrap();
+
+        assert!(parts.len() > 1);
+        for (i, part) in parts.iter().enumerate() {
+            assert!(part.contains(&format!("PART {} OF {}", i + 1, parts.len())));
+            assert!(count_tokens(options.tokenizer, part) <= limit);
+        }
+        // Nội dung của mỗi file xuất hiện đúng một lần, các phần khác chỉ trỏ tới phần chứa nó
+        for file in &files {
+            let name = file.trim_start_matches("src/");
+            let inline = format!("{} [fn handler()", name);
+            assert_eq!(parts.iter().filter(|part| part.contains(&inline)).count(), 1);
+        }
+    }
 }
@@ -0,0 +1,296 @@
+// src-tauri/src/context_template.rs
+// Template do người dùng định nghĩa để trình bày toàn bộ ngữ cảnh, cú pháp giống Handlebars:
+//   {{tree}}, {{repo_map}}, {{stats.tokens}}, {{stats.files}}, {{group.name}}, {{git.branch}}, ...
+//   {{#each files}}{{path}} {{language}} {{tokens}} {{content}}{{/each}}
+//   {{#if git.branch}}...{{else}}...{{/if}}
+// Biến không tồn tại được thay bằng chuỗi rỗng.
+use crate::context_format::{language_for_path, ContextDocument, ContextFileEntry};
+use crate::context_generator::count_tokens;
+use crate::models::BudgetAction;
+use std::collections::BTreeMap;
+use std::fmt::Write as FmtWrite;
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Var(String),
+    Each(String, Vec<Node>),
+    If(String, Vec<Node>, Vec<Node>),
+}
+
+#[derive(Debug)]
+pub struct ContextTemplate {
+    nodes: Vec<Node>,
+}
+
+enum Token {
+    Text(String),
+    Tag(String),
+}
+
+impl Token {
+    // Các thẻ khối đứng một mình trên một dòng sẽ không để lại dòng trống
+    fn is_block_tag(&self) -> bool {
+        matches!(self, Token::Tag(tag) if tag.starts_with('#') || tag.starts_with('/') || tag == "else")
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let after_open = &rest[start + 2..];
+        let end = after_open
+            .find("}}")
+            .ok_or_else(|| "thẻ '{{' chưa được đóng".to_string())?;
+        tokens.push(Token::Tag(after_open[..end].trim().to_string()));
+        rest = &after_open[end + 2..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+
+    // Xác định trước các thẻ đứng một mình, rồi mới xóa phần thụt lề và ký tự xuống dòng
+    let standalone: Vec<bool> = (0..tokens.len())
+        .map(|i| {
+            if !tokens[i].is_block_tag() {
+                return false;
+            }
+            let before = match i.checked_sub(1).map(|j| &tokens[j]) {
+                None => true,
+                Some(Token::Text(text)) => {
+                    let line = text.rsplit('\n').next().unwrap_or("");
+                    line.trim().is_empty() && (text.contains('\n') || i == 1)
+                }
+                Some(Token::Tag(_)) => false,
+            };
+            let after = match tokens.get(i + 1) {
+                None => true,
+                Some(Token::Text(text)) => {
+                    let line = text.split('\n').next().unwrap_or("");
+                    line.trim().is_empty() && (text.contains('\n') || i + 2 == tokens.len())
+                }
+                Some(Token::Tag(_)) => false,
+            };
+            before && after
+        })
+        .collect();
+
+    for (i, is_standalone) in standalone.into_iter().enumerate() {
+        if !is_standalone {
+            continue;
+        }
+        if let Some(Token::Text(text)) = i.checked_sub(1).map(|j| &mut tokens[j]) {
+            let keep = text.rfind('\n').map_or(0, |pos| pos + 1);
+            text.truncate(keep);
+        }
+        if let Some(Token::Text(text)) = tokens.get_mut(i + 1) {
+            let skip = text.find('\n').map_or(text.len(), |pos| pos + 1);
+            text.drain(..skip);
+        }
+    }
+    Ok(tokens)
+}
+
+// Trả về các node đến khi gặp thẻ đóng khối (hoặc `else`) và thẻ đã dừng lại
+fn parse_nodes(
+    tokens: &mut std::vec::IntoIter<Token>,
+) -> Result<(Vec<Node>, Option<String>), String> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Tag(tag) => {
+                if let Some(block) = tag.strip_prefix('#') {
+                    let mut parts = block.split_whitespace();
+                    let kind = parts.next().unwrap_or("");
+                    let name = parts.next().unwrap_or("").to_string();
+                    if name.is_empty() {
+                        return Err(format!("thiếu tên biến trong '{{{{{}}}}}'", tag));
+                    }
+                    let (body, end) = parse_nodes(tokens)?;
+                    match kind {
+                        "each" => {
+                            expect_close(end, "each")?;
+                            nodes.push(Node::Each(name, body));
+                        }
+                        "if" => {
+                            let else_body = if end.as_deref() == Some("else") {
+                                let (else_body, end) = parse_nodes(tokens)?;
+                                expect_close(end, "if")?;
+                                else_body
+                            } else {
+                                expect_close(end, "if")?;
+                                Vec::new()
+                            };
+                            nodes.push(Node::If(name, body, else_body));
+                        }
+                        _ => return Err(format!("khối không được hỗ trợ: '{{{{{}}}}}'", tag)),
+                    }
+                } else if tag.starts_with('/') || tag == "else" {
+                    return Ok((nodes, Some(tag)));
+                } else {
+                    nodes.push(Node::Var(tag));
+                }
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn expect_close(end: Option<String>, kind: &str) -> Result<(), String> {
+    match end {
+        Some(tag) if tag == format!("/{}", kind) => Ok(()),
+        Some(tag) => Err(format!("thẻ '{{{{{}}}}}' không khớp với khối '{}'", tag, kind)),
+        None => Err(format!("khối '{}' chưa được đóng bằng '{{{{/{}}}}}'", kind, kind)),
+    }
+}
+
+// Biến của một file bên trong {{#each files}}
+fn file_var(file: &ContextFileEntry, name: &str, with_line_numbers: bool) -> Option<String> {
+    match name {
+        "path" => Some(file.path.to_string()),
+        "language" => Some(language_for_path(file.path).to_string()),
+        "tokens" => Some(count_tokens(file.tokenizer, file.content).to_string()),
+        "content" if with_line_numbers => {
+            let mut numbered = String::new();
+            for (i, line) in file.content.lines().enumerate() {
+                let _ = writeln!(numbered, "{}: {}", i + 1, line);
+            }
+            Some(numbered)
+        }
+        "content" => Some(file.content.to_string()),
+        "truncated" => Some(
+            if file.budget_action == Some(BudgetAction::Truncated) { "true" } else { "" }.to_string(),
+        ),
+        _ => None,
+    }
+}
+
+struct RenderScope<'a> {
+    doc: &'a ContextDocument<'a>,
+    vars: &'a BTreeMap<String, String>,
+    file: Option<&'a ContextFileEntry<'a>>,
+}
+
+impl RenderScope<'_> {
+    fn lookup(&self, name: &str) -> String {
+        let name = name.strip_prefix("this.").unwrap_or(name);
+        if let Some(value) = self
+            .file
+            .and_then(|file| file_var(file, name, self.doc.with_line_numbers))
+        {
+            return value;
+        }
+        match name {
+            "tree" => self.doc.directory_structure.to_string(),
+            "repo_map" => self.doc.repo_map.unwrap_or("").to_string(),
+            "stats.files" => self.doc.files.len().to_string(),
+            "stats.tokens" => self
+                .doc
+                .files
+                .iter()
+                .map(|file| count_tokens(file.tokenizer, file.content))
+                .sum::<usize>()
+                .to_string(),
+            "always_apply" => self.doc.always_apply_text.unwrap_or("").to_string(),
+            "part.index" => self.doc.part.map(|(index, _)| index.to_string()).unwrap_or_default(),
+            "part.total" => self.doc.part.map(|(_, total)| total.to_string()).unwrap_or_default(),
+            _ => self.vars.get(name).cloned().unwrap_or_default(),
+        }
+    }
+
+    fn is_truthy(&self, name: &str) -> bool {
+        match name {
+            "files" => !self.doc.files.is_empty(),
+            "part" => self.doc.part.is_some(),
+            _ => !self.lookup(name).is_empty(),
+        }
+    }
+}
+
+fn render_nodes(nodes: &[Node], scope: &RenderScope, output: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Var(name) => output.push_str(&scope.lookup(name)),
+            Node::Each(list, body) => {
+                // Hiện chỉ có danh sách `files`
+                if list == "files" {
+                    for file in &scope.doc.files {
+                        let file_scope = RenderScope { doc: scope.doc, vars: scope.vars, file: Some(file) };
+                        render_nodes(body, &file_scope, output);
+                    }
+                }
+            }
+            Node::If(name, body, else_body) => {
+                let branch = if scope.is_truthy(name) { body } else { else_body };
+                render_nodes(branch, scope, output);
+            }
+        }
+    }
+}
+
+impl ContextTemplate {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let mut tokens = tokenize(source)?.into_iter();
+        let (nodes, end) = parse_nodes(&mut tokens)?;
+        if let Some(tag) = end {
+            return Err(format!("thẻ '{{{{{}}}}}' không có khối mở tương ứng", tag));
+        }
+        Ok(Self { nodes })
+    }
+
+    /// `vars` chứa các biến bổ sung như `group.name`, `git.branch`, `project.name`.
+    pub fn render(&self, doc: &ContextDocument, vars: &BTreeMap<String, String>) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, &RenderScope { doc, vars, file: None }, &mut output);
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_tree_files_and_variables() {
+        let template = ContextTemplate::parse(
+            "# {{group.name}} ({{git.branch}})\n{{tree}}\n{{#each files}}\n--- {{path}} [{{language}}]\n{{content}}\n{{/each}}\n{{#if always_apply}}\nNOTE: {{always_apply}}\n{{else}}\nno note\n{{/if}}\n",
+        )
+        .unwrap();
+        let doc = ContextDocument {
+            directory_structure: "└── main.rs",
+            inline_tree: false,
+            part: None,
+            files: vec![ContextFileEntry {
+                path: "main.rs",
+                content: "fn main() {}",
+                budget_action: None,
+                tokenizer: Default::default(),
+            }],
+            with_line_numbers: false,
+            always_apply_text: None,
+            repo_map: None,
+        };
+        let vars = BTreeMap::from([
+            ("group.name".to_string(), "Core".to_string()),
+            ("git.branch".to_string(), "main".to_string()),
+        ]);
+        assert_eq!(
+            template.render(&doc, &vars),
+            "# Core (main)\n└── main.rs\n--- main.rs [rust]\nfn main() {}\nno note\n"
+        );
+    }
+
+    #[test]
+    fn rejects_unbalanced_blocks() {
+        assert!(ContextTemplate::parse("{{#each files}}{{path}}").is_err());
+        assert!(ContextTemplate::parse("{{#if tree}}{{/each}}").is_err());
+        assert!(ContextTemplate::parse("{{/if}}").is_err());
+        assert!(ContextTemplate::parse("{{tree").is_err());
+    }
+}
@@ -0,0 +1,450 @@
+// src-tauri/src/dependency_analyzer.rs
+// Phân tích import của từng file (Rust `mod`/`use`, JS/TS `import`/`require`, Python `import`)
+// và phân giải thành đường dẫn tương đối của các file trong dự án. Import tới thư viện bên ngoài
+// hoặc không tìm thấy file tương ứng sẽ bị bỏ qua.
+use crate::comment_stripper::strip_comments;
+use crate::models::FileMetadata;
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+use std::path::Path;
+
+lazy_static! {
+    static ref RUST_MOD: Regex =
+        Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?mod\s+([A-Za-z_]\w*)\s*;").unwrap();
+    static ref RUST_USE: Regex =
+        Regex::new(r"(?m)^\s*(?:pub(?:\([^)]*\))?\s+)?use\s+([^;]+);").unwrap();
+    static ref RUST_USE_ALIAS: Regex = Regex::new(r"\s+as\s+\w+").unwrap();
+    static ref JS_IMPORT: Regex = Regex::new(
+        r#"(?:^|[^\w$.])(?:import|export)\s+(?:type\s+)?(?:[\w*${}\s,]+?\s+from\s+)?["']([^"']+)["']"#
+    )
+    .unwrap();
+    static ref JS_REQUIRE: Regex =
+        Regex::new(r#"(?:^|[^\w$.])(?:require|import)\s*\(\s*["']([^"']+)["']\s*\)"#).unwrap();
+    static ref PY_FROM_IMPORT: Regex =
+        Regex::new(r"(?m)^\s*from\s+(\.*[\w.]*)\s+import\s+(\([^)]*\)|[^\n]+)").unwrap();
+    static ref PY_IMPORT: Regex = Regex::new(r"(?m)^\s*import\s+([^\n]+)").unwrap();
+}
+
+// Phần mở rộng được thử khi import JS/TS không ghi rõ
+const JS_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "d.ts", "json"];
+// Thư mục gốc được thử cho import tuyệt đối của Python
+const PYTHON_ROOTS: &[&str] = &["", "src/"];
+
+/// Trả về danh sách (đã sắp xếp, không trùng) các file trong `all_files` mà file này import.
+pub fn analyze_dependencies(content: &str, file_rel_path: &str, all_files: &HashSet<String>) -> Vec<String> {
+    resolve_imports(&extract_imports(content, file_rel_path), file_rel_path, all_files)
+}
+
+fn language(file_rel_path: &str) -> &'static str {
+    let extension = Path::new(file_rel_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    match extension.as_str() {
+        "rs" => "rust",
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "vue" | "svelte" | "astro" => "javascript",
+        "py" => "python",
+        _ => "",
+    }
+}
+
+/// Các câu lệnh import thô (đã bỏ comment) của file, chưa phân giải thành file.
+/// Được lưu trong cache để phân giải lại khi tập file của dự án thay đổi mà không cần đọc lại file.
+pub fn extract_imports(content: &str, file_rel_path: &str) -> Vec<String> {
+    let regexes: &[&Regex] = match language(file_rel_path) {
+        "rust" => &[&RUST_MOD, &RUST_USE],
+        "javascript" => &[&JS_IMPORT, &JS_REQUIRE],
+        "python" => &[&PY_FROM_IMPORT, &PY_IMPORT],
+        _ => return Vec::new(),
+    };
+    let content = strip_comments(content, file_rel_path);
+    let mut imports = BTreeSet::new();
+    for regex in regexes {
+        for found in regex.find_iter(&content) {
+            // Bỏ ký tự đứng trước từ khóa mà regex JS/TS bắt kèm
+            imports.insert(found.as_str().trim_start_matches(|c: char| !c.is_alphabetic()).trim_end().to_string());
+        }
+    }
+    imports.into_iter().collect()
+}
+
+/// Phân giải các câu lệnh import của `extract_imports` thành các file trong `all_files`.
+pub fn resolve_imports(imports: &[String], file_rel_path: &str, all_files: &HashSet<String>) -> Vec<String> {
+    let resolver = Resolver { file: file_rel_path, all_files };
+    let statements = imports.join("\n");
+    let mut dependencies = BTreeSet::new();
+    match language(file_rel_path) {
+        "rust" => resolver.rust(&statements, &mut dependencies),
+        "javascript" => resolver.javascript(&statements, &mut dependencies),
+        "python" => resolver.python(&statements, &mut dependencies),
+        _ => {}
+    }
+    dependencies.remove(file_rel_path);
+    dependencies.into_iter().collect()
+}
+
+struct Resolver<'a> {
+    file: &'a str,
+    all_files: &'a HashSet<String>,
+}
+
+// Chuẩn hóa `a/./b/../c` thành `a/c`; None nếu đi ra ngoài thư mục dự án
+fn normalize(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+fn parent_dir(path: &str) -> &str {
+    path.rfind('/').map_or("", |i| &path[..i])
+}
+
+fn join(dir: &str, rest: &str) -> String {
+    if dir.is_empty() {
+        rest.to_string()
+    } else {
+        format!("{}/{}", dir, rest)
+    }
+}
+
+impl Resolver<'_> {
+    fn find(&self, candidate: &str) -> Option<String> {
+        self.all_files.contains(candidate).then(|| candidate.to_string())
+    }
+
+    // --- RUST ---
+
+    // Thư mục gốc của crate: thư mục tổ tiên gần nhất có lib.rs hoặc main.rs
+    fn rust_crate_root(&self) -> Option<&str> {
+        let mut dir = parent_dir(self.file);
+        loop {
+            if self.all_files.contains(&join(dir, "lib.rs")) || self.all_files.contains(&join(dir, "main.rs")) {
+                return Some(dir);
+            }
+            if dir.is_empty() {
+                return None;
+            }
+            dir = parent_dir(dir);
+        }
+    }
+
+    // Đường dẫn module của file tính từ gốc crate: `src/commands/utils.rs` -> [commands, utils]
+    fn rust_module_path(&self, crate_root: &str) -> Vec<String> {
+        let relative = self.file[crate_root.len()..].trim_start_matches('/');
+        let without_ext = relative.strip_suffix(".rs").unwrap_or(relative);
+        let mut segments: Vec<String> = without_ext.split('/').map(str::to_string).collect();
+        if matches!(segments.last().map(String::as_str), Some("mod" | "lib" | "main")) {
+            segments.pop();
+        }
+        segments
+    }
+
+    // File định nghĩa module dài nhất khớp với đường dẫn (phần còn lại là tên item bên trong)
+    fn resolve_rust_module(&self, crate_root: &str, segments: &[String]) -> Option<String> {
+        (1..=segments.len()).rev().find_map(|len| {
+            let base = join(crate_root, &segments[..len].join("/"));
+            self.find(&format!("{}.rs", base))
+                .or_else(|| self.find(&format!("{}/mod.rs", base)))
+        })
+    }
+
+    fn rust(&self, content: &str, dependencies: &mut BTreeSet<String>) {
+        let Some(crate_root) = self.rust_crate_root() else {
+            return;
+        };
+        let module_path = self.rust_module_path(crate_root);
+        for captures in RUST_MOD.captures_iter(content) {
+            let mut segments = module_path.clone();
+            segments.push(captures[1].to_string());
+            let base = join(crate_root, &segments.join("/"));
+            if let Some(file) = self.find(&format!("{}.rs", base)).or_else(|| self.find(&format!("{}/mod.rs", base))) {
+                dependencies.insert(file);
+            }
+        }
+        for captures in RUST_USE.captures_iter(content) {
+            let tree: String = RUST_USE_ALIAS
+                .replace_all(&captures[1], "")
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect();
+            for path in expand_use_tree(&tree) {
+                let mut parts = path.split("::").filter(|p| !p.is_empty());
+                let mut segments = match parts.next() {
+                    Some("crate") => Vec::new(),
+                    Some("self") => module_path.clone(),
+                    Some("super") => {
+                        let mut base = module_path.clone();
+                        base.pop();
+                        base
+                    }
+                    _ => continue, // Crate bên ngoài hoặc std
+                };
+                for part in parts {
+                    match part {
+                        "super" => {
+                            segments.pop();
+                        }
+                        "self" | "*" => {}
+                        _ => segments.push(part.to_string()),
+                    }
+                }
+                if let Some(file) = self.resolve_rust_module(crate_root, &segments) {
+                    dependencies.insert(file);
+                }
+            }
+        }
+    }
+
+    // --- JAVASCRIPT / TYPESCRIPT ---
+
+    fn resolve_js(&self, specifier: &str) -> Option<String> {
+        let base = if specifier.starts_with("./") || specifier.starts_with("../") {
+            normalize(&join(parent_dir(self.file), specifier))?
+        } else if let Some(rest) = specifier.strip_prefix("@/").or_else(|| specifier.strip_prefix("~/")) {
+            // Bí danh phổ biến trỏ tới thư mục src
+            normalize(&join("src", rest))?
+        } else if specifier.starts_with('/') {
+            normalize(specifier)?
+        } else {
+            return None; // Gói trong node_modules
+        };
+        let base = base.split(['?', '#']).next().unwrap_or(&base).to_string();
+        self.find(&base)
+            .or_else(|| JS_EXTENSIONS.iter().find_map(|ext| self.find(&format!("{}.{}", base, ext))))
+            .or_else(|| JS_EXTENSIONS.iter().find_map(|ext| self.find(&format!("{}/index.{}", base, ext))))
+    }
+
+    fn javascript(&self, content: &str, dependencies: &mut BTreeSet<String>) {
+        for regex in [&*JS_IMPORT, &*JS_REQUIRE] {
+            for captures in regex.captures_iter(content) {
+                if let Some(file) = self.resolve_js(&captures[1]) {
+                    dependencies.insert(file);
+                }
+            }
+        }
+    }
+
+    // --- PYTHON ---
+
+    fn resolve_python_module(&self, dir: &str, dotted: &str) -> Option<String> {
+        let base = join(dir, &dotted.replace('.', "/"));
+        self.find(&format!("{}.py", base))
+            .or_else(|| self.find(&format!("{}/__init__.py", base)))
+    }
+
+    // Module tuyệt đối được thử từ gốc dự án, thư mục src và thư mục chứa file
+    fn resolve_python_absolute(&self, dotted: &str) -> Option<String> {
+        PYTHON_ROOTS
+            .iter()
+            .map(|root| root.trim_end_matches('/'))
+            .chain(std::iter::once(parent_dir(self.file)))
+            .find_map(|root| self.resolve_python_module(root, dotted))
+    }
+
+    fn python(&self, content: &str, dependencies: &mut BTreeSet<String>) {
+        for captures in PY_FROM_IMPORT.captures_iter(content) {
+            let module = &captures[1];
+            let names: Vec<&str> = captures[2]
+                .trim_matches(|c: char| c == '(' || c == ')' || c.is_whitespace())
+                .split(',')
+                .filter_map(|name| name.split_whitespace().next())
+                .filter(|name| *name != "*")
+                .collect();
+            let dots = module.len() - module.trim_start_matches('.').len();
+            let dotted = &module[dots..];
+            if dots > 0 {
+                // Import tương đối: mỗi dấu chấm sau dấu đầu tiên đi lên một cấp
+                let mut dir = parent_dir(self.file);
+                for _ in 1..dots {
+                    dir = parent_dir(dir);
+                }
+                let package = if dotted.is_empty() { dir.to_string() } else { join(dir, &dotted.replace('.', "/")) };
+                // `from . import x` có thể import module con x
+                let submodules: Vec<String> =
+                    names.iter().filter_map(|name| self.resolve_python_module(&package, name)).collect();
+                if submodules.is_empty() && !dotted.is_empty() {
+                    dependencies.extend(self.resolve_python_module(dir, dotted));
+                }
+                dependencies.extend(submodules);
+            } else {
+                let submodules: Vec<String> = names
+                    .iter()
+                    .filter_map(|name| self.resolve_python_absolute(&format!("{}.{}", dotted, name)))
+                    .collect();
+                if submodules.is_empty() {
+                    dependencies.extend(self.resolve_python_absolute(dotted));
+                }
+                dependencies.extend(submodules);
+            }
+        }
+        for captures in PY_IMPORT.captures_iter(content) {
+            for module in captures[1].split(',').filter_map(|m| m.split_whitespace().next()) {
+                dependencies.extend(self.resolve_python_absolute(module));
+            }
+        }
+    }
+}
+
+// `crate::{a, b::{c, d}}` -> ["crate::a", "crate::b::c", "crate::b::d"]
+fn expand_use_tree(tree: &str) -> Vec<String> {
+    let Some(open) = tree.find('{') else {
+        return vec![tree.to_string()];
+    };
+    let prefix = &tree[..open];
+    let Some(inner) = tree[open + 1..].strip_suffix('}') else {
+        return vec![prefix.to_string()];
+    };
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&inner[start..]);
+    items
+        .into_iter()
+        .filter(|item| !item.is_empty())
+        .flat_map(|item| expand_use_tree(&format!("{}{}", prefix, item)))
+        .collect()
+}
+
+/// Các file mà `start` phụ thuộc trực tiếp hoặc gián tiếp, tối đa `max_depth` bước
+/// (None = không giới hạn). Không bao gồm chính các file trong `start`.
+pub fn dependency_closure(
+    start: &[String],
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    max_depth: Option<usize>,
+) -> Vec<String> {
+    let graph: BTreeMap<&str, Vec<&str>> = metadata_cache
+        .iter()
+        .filter_map(|(path, meta)| {
+            let dependencies = meta.dependencies.as_ref()?;
+            Some((path.as_str(), dependencies.iter().map(String::as_str).collect()))
+        })
+        .collect();
+    walk_graph(start, &graph, metadata_cache, max_depth)
+}
+
+/// Các file import `start` trực tiếp hoặc gián tiếp (đồ thị ngược), tối đa `max_depth` bước
+/// (None = không giới hạn). Không bao gồm chính các file trong `start`.
+pub fn dependents_closure(
+    start: &[String],
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    max_depth: Option<usize>,
+) -> Vec<String> {
+    let mut graph: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for (path, meta) in metadata_cache {
+        for dependency in meta.dependencies.iter().flatten() {
+            graph.entry(dependency.as_str()).or_default().push(path.as_str());
+        }
+    }
+    walk_graph(start, &graph, metadata_cache, max_depth)
+}
+
+// BFS trên đồ thị `graph`, chỉ đi qua các file còn tồn tại trong cache
+fn walk_graph(
+    start: &[String],
+    graph: &BTreeMap<&str, Vec<&str>>,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    max_depth: Option<usize>,
+) -> Vec<String> {
+    let mut visited: HashSet<&str> = start.iter().map(String::as_str).collect();
+    let mut queue: VecDeque<(&str, usize)> = start.iter().map(|path| (path.as_str(), 0)).collect();
+    let mut reached = BTreeSet::new();
+    while let Some((path, depth)) = queue.pop_front() {
+        if max_depth.is_some_and(|max| depth >= max) {
+            continue;
+        }
+        for &next in graph.get(path).into_iter().flatten() {
+            if metadata_cache.contains_key(next) && visited.insert(next) {
+                reached.insert(next.to_string());
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+    reached.into_iter().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(paths: &[&str]) -> HashSet<String> {
+        paths.iter().map(|p| p.to_string()).collect()
+    }
+
+    #[test]
+    fn resolves_imports_to_project_files() {
+        let rust_files = files(&[
+            "src-tauri/src/lib.rs",
+            "src-tauri/src/models.rs",
+            "src-tauri/src/commands/mod.rs",
+            "src-tauri/src/commands/utils.rs",
+            "src-tauri/src/commands/group_commands.rs",
+        ]);
+        let lib = "pub mod commands;\nmod models;\n// mod ghost;\nuse std::fs;\n";
+        assert_eq!(
+            analyze_dependencies(lib, "src-tauri/src/lib.rs", &rust_files),
+            vec!["src-tauri/src/commands/mod.rs", "src-tauri/src/models.rs"]
+        );
+        let group_commands = "use crate::{models::Group as G, commands::utils};\nuse super::utils::{\n    apply_template,\n};\nuse serde::Serialize;\n";
+        assert_eq!(
+            analyze_dependencies(group_commands, "src-tauri/src/commands/group_commands.rs", &rust_files),
+            vec!["src-tauri/src/commands/utils.rs", "src-tauri/src/models.rs"]
+        );
+
+        let js_files = files(&["src/App.tsx", "src/store/appStore.ts", "src/lib/utils.ts", "src/components/ui/index.tsx", "src/styles.css"]);
+        let app = "import React from \"react\";\nimport { useAppStore } from \"@/store/appStore\";\nimport {\n  cn,\n} from './lib/utils';\nimport \"./styles.css\";\nconst ui = require(\"./components/ui\");\n// import x from \"./ghost\";\n";
+        assert_eq!(
+            analyze_dependencies(app, "src/App.tsx", &js_files),
+            vec!["src/components/ui/index.tsx", "src/lib/utils.ts", "src/store/appStore.ts", "src/styles.css"]
+        );
+
+        let py_files = files(&["app/__init__.py", "app/main.py", "app/models.py", "app/utils/__init__.py", "app/utils/text.py", "config.py"]);
+        let main = "import os, config\nfrom . import models\nfrom .utils import text as t\nfrom app.utils import (\n    helper,\n)\n";
+        assert_eq!(
+            analyze_dependencies(main, "app/main.py", &py_files),
+            vec!["app/models.py", "app/utils/__init__.py", "app/utils/text.py", "config.py"]
+        );
+    }
+
+    #[test]
+    fn closure_respects_depth() {
+        let meta = |dependencies: &[&str]| FileMetadata {
+            dependencies: Some(dependencies.iter().map(|d| d.to_string()).collect()),
+            ..Default::default()
+        };
+        let cache = BTreeMap::from([
+            ("a".to_string(), meta(&["b"])),
+            ("b".to_string(), meta(&["c", "a"])),
+            ("c".to_string(), meta(&["d"])),
+            ("d".to_string(), meta(&[])),
+        ]);
+        let start = vec!["a".to_string()];
+        assert_eq!(dependency_closure(&start, &cache, Some(1)), vec!["b"]);
+        assert_eq!(dependency_closure(&start, &cache, Some(2)), vec!["b", "c"]);
+        assert_eq!(dependency_closure(&start, &cache, None), vec!["b", "c", "d"]);
+
+        let shared = vec!["d".to_string()];
+        assert_eq!(dependents_closure(&shared, &cache, Some(1)), vec!["c"]);
+        assert_eq!(dependents_closure(&shared, &cache, None), vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,467 @@
+// src-tauri/src/edit_applier.rs
+// Áp dụng chỉnh sửa dạng search/replace và unified diff (do AI sinh ra) lên nội dung file.
+// Vị trí cần sửa được tìm theo thứ tự: khớp chính xác, khớp khi bỏ qua khác biệt khoảng trắng,
+// rồi khớp gần đúng theo độ giống của từng dòng. Khi thất bại, lỗi cho biết hunk nào và vị trí
+// gần giống nhất để mô hình có thể thử lại.
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::fmt;
+
+// Độ giống trung bình tối thiểu của các dòng để chấp nhận một vị trí khớp gần đúng
+const FUZZY_THRESHOLD: f64 = 0.85;
+
+lazy_static! {
+    static ref HUNK_HEADER: Regex = Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").unwrap();
+}
+
+/// Vị trí gần giống nhất với đoạn cần tìm khi không có vị trí nào đủ giống.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearestMatch {
+    pub line: usize, // Bắt đầu từ 1
+    pub similarity: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EditFailure {
+    pub hunk: Option<usize>, // Bắt đầu từ 1, None với search/replace
+    pub reason: String,
+    pub nearest: Option<NearestMatch>,
+}
+
+impl fmt::Display for EditFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(hunk) = self.hunk {
+            write!(f, "Hunk #{}: ", hunk)?;
+        }
+        write!(f, "{}", self.reason)?;
+        if let Some(nearest) = &self.nearest {
+            write!(
+                f,
+                "\nVị trí gần giống nhất: dòng {} (giống {:.0}%):\n{}",
+                nearest.line,
+                nearest.similarity * 100.0,
+                nearest.text
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    pub header: String,
+    pub old_start: usize, // Bắt đầu từ 1, 0 khi file gốc rỗng
+    pub lines: Vec<HunkLine>,
+}
+
+/// Phần diff của một file. `old_path`/`new_path` là None với `/dev/null` (tạo mới/xóa file).
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePatch {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+/// Thay đoạn `search` bằng `replace`. Đoạn cần tìm phải xác định duy nhất một vị trí.
+pub fn apply_search_replace(content: &str, search: &str, replace: &str) -> Result<String, EditFailure> {
+    let failure = |reason: String, nearest| EditFailure { hunk: None, reason, nearest };
+    if search.trim().is_empty() {
+        return Err(failure("Đoạn cần tìm đang trống.".to_string(), None));
+    }
+    let (content, trailing_newline) = split_trailing_newline(content);
+    let search = search.replace("\r\n", "\n");
+    let replace = replace.replace("\r\n", "\n");
+
+    match content.matches(search.as_str()).count() {
+        1 => return Ok(content.replacen(search.as_str(), &replace, 1) + trailing_newline),
+        0 => {}
+        count => {
+            return Err(failure(
+                format!("Đoạn cần tìm xuất hiện {} lần; hãy thêm các dòng xung quanh để xác định duy nhất.", count),
+                None,
+            ))
+        }
+    }
+
+    // Không khớp chính xác: so khớp theo dòng, bỏ qua khác biệt khoảng trắng hoặc gần đúng
+    let lines: Vec<&str> = content.split('\n').collect();
+    let needle: Vec<&str> = search.trim_matches('\n').split('\n').collect();
+    let found = locate(&lines, &needle, 0, 0, true).map_err(|located| match located {
+        Located::Ambiguous(count) => failure(
+            format!("Đoạn cần tìm khớp gần đúng {} vị trí; hãy thêm các dòng xung quanh để xác định duy nhất.", count),
+            None,
+        ),
+        Located::NotFound(nearest) => failure("Không tìm thấy đoạn cần tìm trong file.".to_string(), nearest),
+    })?;
+
+    let replacement: Vec<String> = replace
+        .trim_matches('\n')
+        .split('\n')
+        .map(|line| reindent(line, &needle, &lines[found..found + needle.len()]))
+        .collect();
+    let mut output: Vec<String> = lines[..found].iter().map(|l| l.to_string()).collect();
+    if !replace.trim_matches('\n').is_empty() {
+        output.extend(replacement);
+    }
+    output.extend(lines[found + needle.len()..].iter().map(|l| l.to_string()));
+    Ok(output.join("\n") + trailing_newline)
+}
+
+/// Đọc unified diff (có thể gồm nhiều file). Số dòng trong tiêu đề hunk không được tin tuyệt đối
+/// vì mô hình thường đếm sai; hunk kết thúc ở tiêu đề hunk hoặc tiêu đề file tiếp theo.
+pub fn parse_unified_diff(diff: &str) -> Result<Vec<FilePatch>, String> {
+    let lines: Vec<&str> = diff.lines().collect();
+    let mut patches: Vec<FilePatch> = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        if line.starts_with("--- ") && lines.get(index + 1).is_some_and(|next| next.starts_with("+++ ")) {
+            patches.push(FilePatch {
+                old_path: parse_diff_path(&line[4..]),
+                new_path: parse_diff_path(&lines[index + 1][4..]),
+                hunks: Vec::new(),
+            });
+            index += 2;
+            continue;
+        }
+        if let Some(captures) = HUNK_HEADER.captures(line) {
+            let patch = patches
+                .last_mut()
+                .ok_or_else(|| "Diff thiếu tiêu đề file (`--- a/...` và `+++ b/...`) trước hunk đầu tiên.".to_string())?;
+            let mut hunk = Hunk {
+                header: line.to_string(),
+                old_start: captures[1].parse().unwrap_or(0),
+                lines: Vec::new(),
+            };
+            index += 1;
+            while index < lines.len() {
+                let line = lines[index];
+                let is_file_header = line.starts_with("--- ")
+                    && lines.get(index + 1).is_some_and(|next| next.starts_with("+++ "));
+                // Các dòng thông tin của git giữa hai file cũng kết thúc hunk
+                let is_git_header = ["diff --git ", "index ", "new file mode ", "deleted file mode "]
+                    .iter()
+                    .any(|prefix| line.starts_with(prefix));
+                if HUNK_HEADER.is_match(line) || is_file_header || is_git_header {
+                    break;
+                }
+                match line.chars().next() {
+                    Some('+') => hunk.lines.push(HunkLine::Add(line[1..].to_string())),
+                    Some('-') => hunk.lines.push(HunkLine::Remove(line[1..].to_string())),
+                    Some(' ') => hunk.lines.push(HunkLine::Context(line[1..].to_string())),
+                    Some('\\') => {} // "\ No newline at end of file"
+                    // Dòng trống trong ngữ cảnh thường bị mất dấu cách đầu dòng
+                    None => hunk.lines.push(HunkLine::Context(String::new())),
+                    Some(_) => return Err(format!("Dòng không hợp lệ trong hunk '{}': {}", hunk.header, line)),
+                }
+                index += 1;
+            }
+            // Bỏ các dòng trống ở cuối hunk (thường là phần cách giữa các file)
+            while matches!(hunk.lines.last(), Some(HunkLine::Context(l)) if l.is_empty()) {
+                hunk.lines.pop();
+            }
+            patch.hunks.push(hunk);
+            continue;
+        }
+        index += 1;
+    }
+    if patches.is_empty() {
+        return Err("Không tìm thấy tiêu đề file nào trong diff.".to_string());
+    }
+    Ok(patches)
+}
+
+/// Áp dụng lần lượt các hunk lên nội dung. Dòng ngữ cảnh giữ nguyên nội dung thực trong file.
+pub fn apply_hunks(content: &str, hunks: &[Hunk]) -> Result<String, EditFailure> {
+    let (content, trailing_newline) = split_trailing_newline(content);
+    let mut lines: Vec<String> = if content.is_empty() {
+        Vec::new()
+    } else {
+        content.split('\n').map(str::to_string).collect()
+    };
+    // Độ lệch số dòng do các hunk trước gây ra, và vị trí sớm nhất hunk sau được phép bắt đầu
+    let mut offset: isize = 0;
+    let mut from = 0;
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        let old_block: Vec<&str> = hunk
+            .lines
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect();
+        let hint = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+
+        let position = if old_block.is_empty() {
+            // Hunk chỉ thêm dòng: chèn đúng vị trí trong tiêu đề
+            let insert_at = if hunk.old_start == 0 { 0 } else { hunk.old_start as isize + offset };
+            (insert_at.max(0) as usize).clamp(from, lines.len())
+        } else {
+            let line_refs: Vec<&str> = lines.iter().map(String::as_str).collect();
+            locate(&line_refs, &old_block, hint, from, false).map_err(|located| EditFailure {
+                hunk: Some(index + 1),
+                reason: format!("Không tìm thấy vị trí áp dụng '{}'.", hunk.header),
+                nearest: match located {
+                    Located::NotFound(nearest) => nearest,
+                    Located::Ambiguous(_) => None,
+                },
+            })?
+        };
+
+        let matched: Vec<String> = lines[position..position + old_block.len()].to_vec();
+        let matched_refs: Vec<&str> = matched.iter().map(String::as_str).collect();
+        let mut replacement = Vec::new();
+        let mut file_lines = matched.iter();
+        for line in &hunk.lines {
+            match line {
+                HunkLine::Context(_) => replacement.push(file_lines.next().cloned().unwrap_or_default()),
+                HunkLine::Remove(_) => {
+                    file_lines.next();
+                }
+                HunkLine::Add(text) => replacement.push(reindent(text, &old_block, &matched_refs)),
+            }
+        }
+        let replacement_len = replacement.len();
+        lines.splice(position..position + old_block.len(), replacement);
+        offset += replacement_len as isize - old_block.len() as isize;
+        from = position + replacement_len;
+    }
+
+    if lines.is_empty() {
+        return Ok(String::new());
+    }
+    Ok(lines.join("\n") + trailing_newline)
+}
+
+/// Nội dung file mới từ một diff tạo file (`--- /dev/null`).
+pub fn new_file_content(hunks: &[Hunk]) -> String {
+    let lines: Vec<&str> = hunks
+        .iter()
+        .flat_map(|h| &h.lines)
+        .filter_map(|l| match l {
+            HunkLine::Add(text) | HunkLine::Context(text) => Some(text.as_str()),
+            HunkLine::Remove(_) => None,
+        })
+        .collect();
+    if lines.is_empty() {
+        String::new()
+    } else {
+        lines.join("\n") + "\n"
+    }
+}
+
+enum Located {
+    NotFound(Option<NearestMatch>),
+    Ambiguous(usize),
+}
+
+// Tìm vị trí (chỉ số dòng) của `needle` trong `lines[from..]`. Khi có nhiều vị trí cùng mức,
+// chọn vị trí gần `hint` nhất, hoặc báo mơ hồ nếu `require_unique`.
+fn locate(
+    lines: &[&str],
+    needle: &[&str],
+    hint: usize,
+    from: usize,
+    require_unique: bool,
+) -> Result<usize, Located> {
+    if needle.len() > lines.len().saturating_sub(from) {
+        return Err(Located::NotFound(None));
+    }
+    let starts: Vec<usize> = (from..=lines.len() - needle.len()).collect();
+    let pick = |candidates: Vec<usize>| -> Option<Result<usize, Located>> {
+        match candidates.len() {
+            0 => None,
+            count if count > 1 && require_unique => Some(Err(Located::Ambiguous(count))),
+            _ => candidates.into_iter().min_by_key(|&start| start.abs_diff(hint)).map(Ok),
+        }
+    };
+
+    let exact = starts.iter().copied().filter(|&s| lines[s..s + needle.len()] == *needle).collect();
+    if let Some(result) = pick(exact) {
+        return result;
+    }
+    let normalized_needle: Vec<String> = needle.iter().map(|l| normalize_whitespace(l)).collect();
+    let whitespace = starts
+        .iter()
+        .copied()
+        .filter(|&s| {
+            lines[s..s + needle.len()]
+                .iter()
+                .zip(&normalized_needle)
+                .all(|(line, expected)| normalize_whitespace(line) == *expected)
+        })
+        .collect();
+    if let Some(result) = pick(whitespace) {
+        return result;
+    }
+
+    // Khớp gần đúng: độ giống trung bình của các dòng trong cửa sổ
+    let normalized_lines: Vec<String> = lines.iter().map(|l| normalize_whitespace(l)).collect();
+    let scored: Vec<(usize, f64)> = starts
+        .iter()
+        .map(|&s| {
+            let total: f64 = normalized_lines[s..s + needle.len()]
+                .iter()
+                .zip(&normalized_needle)
+                .map(|(line, expected)| line_similarity(line, expected))
+                .sum();
+            (s, total / needle.len() as f64)
+        })
+        .collect();
+    let best = scored.iter().map(|&(_, score)| score).fold(0.0, f64::max);
+    if best >= FUZZY_THRESHOLD {
+        let candidates = scored
+            .iter()
+            .filter(|&&(_, score)| (best - score).abs() < f64::EPSILON)
+            .map(|&(start, _)| start)
+            .collect();
+        if let Some(result) = pick(candidates) {
+            return result;
+        }
+    }
+    let nearest = scored
+        .iter()
+        .filter(|&&(_, score)| (best - score).abs() < f64::EPSILON && best > 0.0)
+        .min_by_key(|&&(start, _)| start.abs_diff(hint))
+        .map(|&(start, similarity)| NearestMatch {
+            line: start + 1,
+            similarity,
+            text: lines[start..start + needle.len()].join("\n"),
+        });
+    Err(Located::NotFound(nearest))
+}
+
+fn normalize_whitespace(line: &str) -> String {
+    line.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// Hệ số Dice trên các cặp ký tự liên tiếp: rẻ và đủ tốt để so hai dòng mã gần giống nhau
+fn line_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let bigrams = |s: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = s.chars().collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let (a_bigrams, b_bigrams) = (bigrams(a), bigrams(b));
+    if a_bigrams.is_empty() || b_bigrams.is_empty() {
+        return 0.0;
+    }
+    let mut remaining: Vec<(char, char)> = b_bigrams.clone();
+    let mut common = 0;
+    for bigram in &a_bigrams {
+        if let Some(position) = remaining.iter().position(|b| b == bigram) {
+            remaining.swap_remove(position);
+            common += 1;
+        }
+    }
+    2.0 * common as f64 / (a_bigrams.len() + b_bigrams.len()) as f64
+}
+
+// Khi đoạn khớp trong file có thụt lề khác với đoạn mô hình gửi, thụt lề dòng mới theo file
+fn reindent(line: &str, needle: &[&str], matched: &[&str]) -> String {
+    let indent = |s: &str| s[..s.len() - s.trim_start().len()].to_string();
+    let Some((expected, actual)) = needle
+        .iter()
+        .zip(matched)
+        .find(|(expected, _)| !expected.trim().is_empty())
+        .map(|(expected, actual)| (indent(expected), indent(actual)))
+    else {
+        return line.to_string();
+    };
+    if expected == actual {
+        return line.to_string();
+    }
+    match line.strip_prefix(expected.as_str()) {
+        Some(rest) if !line.trim().is_empty() => format!("{}{}", actual, rest),
+        _ => line.to_string(),
+    }
+}
+
+fn split_trailing_newline(content: &str) -> (String, &'static str) {
+    let content = content.replace("\r\n", "\n");
+    match content.strip_suffix('\n') {
+        Some(stripped) => (stripped.to_string(), "\n"),
+        None => (content, ""),
+    }
+}
+
+// `a/src/main.rs` -> `src/main.rs`, bỏ phần thời gian sau tab; `/dev/null` -> None
+fn parse_diff_path(raw: &str) -> Option<String> {
+    let path = raw.split('\t').next().unwrap_or("").trim();
+    if path == "/dev/null" {
+        return None;
+    }
+    let path = path.strip_prefix("a/").or_else(|| path.strip_prefix("b/")).unwrap_or(path);
+    Some(path.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "fn main() {\n    let total = sum(&[1, 2, 3]);\n    println!(\"{}\", total);\n}\n\nfn sum(values: &[i32]) -> i32 {\n    values.iter().sum()\n}\n";
+
+    #[test]
+    fn search_replace_tolerates_whitespace_and_reports_nearest_match() {
+        let exact = apply_search_replace(SOURCE, "values.iter().sum()", "values.iter().copied().sum()").unwrap();
+        assert!(exact.contains("    values.iter().copied().sum()\n"));
+
+        // Mô hình gửi sai thụt lề: dòng thay thế được thụt lề theo file
+        let reindented = apply_search_replace(
+            SOURCE,
+            "let total = sum(&[1, 2, 3]);\nprintln!(\"{}\", total);",
+            "let total = sum(&[1, 2, 3, 4]);\nprintln!(\"total = {}\", total);",
+        )
+        .unwrap();
+        assert!(reindented.starts_with("fn main() {\n    let total = sum(&[1, 2, 3, 4]);\n    println!(\"total = {}\", total);\n}"));
+
+        let fuzzy = apply_search_replace(SOURCE, "fn sum(values: &[i32]) -> i32 {\n    values.iter().sum();", "fn sum(values: &[i64]) -> i64 {\n    values.iter().sum()").unwrap();
+        assert!(fuzzy.contains("fn sum(values: &[i64]) -> i64 {\n    values.iter().sum()\n}\n"));
+
+        let failure = apply_search_replace(SOURCE, "fn product_of_all(values: &[i32]) -> i32 {", "").unwrap_err();
+        assert!(failure.to_string().contains("dòng 6"));
+        let nearest = failure.nearest.expect("nearest match");
+        assert_eq!(nearest.line, 6);
+        assert!(nearest.similarity < FUZZY_THRESHOLD);
+
+        let ambiguous = apply_search_replace("a\nb\na\n", "a", "c").unwrap_err();
+        assert!(ambiguous.reason.contains("2 lần"));
+    }
+
+    #[test]
+    fn unified_diff_applies_with_offsets_and_reports_failed_hunk() {
+        // Số dòng trong tiêu đề lệch so với file thật, ngữ cảnh có khác biệt khoảng trắng
+        let diff = "diff --git a/src/main.rs b/src/main.rs\n--- a/src/main.rs\n+++ b/src/main.rs\n@@ -3,3 +3,3 @@ fn main() {\n     let total = sum(&[1, 2, 3]);\n-    println!(\"{}\", total);\n+    println!(\"total = {}\", total);\n }\n@@ -7,2 +7,3 @@\n fn sum(values:  &[i32]) -> i32 {\n+    // Tổng các phần tử\n     values.iter().sum()\n--- /dev/null\n+++ b/src/lib.rs\n@@ -0,0 +1,2 @@\n+pub mod util;\n+pub mod config;\n";
+        let patches = parse_unified_diff(diff).unwrap();
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0].old_path.as_deref(), Some("src/main.rs"));
+        assert_eq!(patches[1].old_path, None);
+        assert_eq!(new_file_content(&patches[1].hunks), "pub mod util;\npub mod config;\n");
+
+        let patched = apply_hunks(SOURCE, &patches[0].hunks).unwrap();
+        assert_eq!(
+            patched,
+            "fn main() {\n    let total = sum(&[1, 2, 3]);\n    println!(\"total = {}\", total);\n}\n\nfn sum(values: &[i32]) -> i32 {\n    // Tổng các phần tử\n    values.iter().sum()\n}\n"
+        );
+
+        let broken = "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,1 +1,1 @@\n-fn main() {\n+fn start() {\n@@ -6,1 +6,1 @@\n-fn average(values: &[f64]) -> f64 {\n+fn mean(values: &[f64]) -> f64 {\n";
+        let patches = parse_unified_diff(broken).unwrap();
+        let failure = apply_hunks(SOURCE, &patches[0].hunks).unwrap_err();
+        assert_eq!(failure.hunk, Some(2));
+        assert_eq!(failure.nearest.map(|n| n.line), Some(6));
+
+        assert!(parse_unified_diff("@@ -1 +1 @@\n-a\n+b\n").is_err());
+    }
+}
@@ -1,48 +1,54 @@
 // src-tauri/src/file_cache.rs
-use crate::models::CachedProjectData;
+use crate::models::{AppSettings, CachedProjectData};
+use lazy_static::lazy_static;
+use std::collections::HashMap;
 use std::fs::{self, File};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
 use sha2::{Digest, Sha256}; // <-- THÊM IMPORT
-use tauri::{AppHandle, Manager}; // <-- THÊM IMPORT
 
 const PROJECT_DATA_FILENAME: &str = "project_data.json";
 
-// --- HÀM MỚI: TÁCH RIÊNG LOGIC LẤY THƯ MỤC CẤU HÌNH ---
-pub fn get_project_config_dir(app: &AppHandle, project_path_str: &str) -> Result<PathBuf, String> {
-    // 1. Lấy thư mục cấu hình chung của ứng dụng
-    let app_config_dir = app.path()
-        .app_config_dir()
-        .map_err(|e| format!("Không thể xác định thư mục cấu hình ứng dụng: {}", e))?;
+// Phải khớp với `identifier` trong tauri.conf.json để CLI dùng chung dữ liệu với GUI
+pub const APP_IDENTIFIER: &str = "com.tomisakae.master-context";
+const APP_SETTINGS_FILENAME: &str = "app_settings.json";
+
+lazy_static! {
+    // Khóa theo thư mục dữ liệu của từng dự án cho các thao tác đọc-sửa-ghi project_data.json.
+    // Mỗi dự án chỉ có một khóa, tồn tại suốt vòng đời tiến trình.
+    static ref PROJECT_DATA_LOCKS: Mutex<HashMap<PathBuf, &'static Mutex<()>>> = Mutex::new(HashMap::new());
+}
 
-    // 2. Băm (hash) đường dẫn dự án để tạo ID duy nhất
+/// Thư mục lưu trữ mặc định khi không có `AppHandle` (ví dụ: chạy từ CLI).
+/// Tính giống hệt `app.path().app_config_dir()` của Tauri.
+pub fn default_app_config_dir() -> Result<PathBuf, String> {
+    dirs::config_dir()
+        .map(|dir| dir.join(APP_IDENTIFIER))
+        .ok_or_else(|| "Không thể xác định thư mục cấu hình ứng dụng.".to_string())
+}
+
+// --- HÀM MỚI: TÁCH RIÊNG LOGIC LẤY THƯ MỤC CẤU HÌNH ---
+// `storage_root` là thư mục gốc lưu dữ liệu (bản GUI dùng app_config_dir của Tauri)
+pub fn get_project_config_dir(storage_root: &Path, project_path_str: &str) -> Result<PathBuf, String> {
+    // 1. Băm (hash) đường dẫn dự án để tạo ID duy nhất
     let mut hasher = Sha256::new();
     hasher.update(project_path_str.as_bytes());
     let project_hash = hasher.finalize();
     let project_id = format!("{:x}", project_hash);
 
-    // 3. Tạo đường dẫn cuối cùng: <app_config_dir>/projects/<project_id>
-    let config_dir = app_config_dir.join("projects").join(project_id);
+    // 2. Tạo đường dẫn cuối cùng: <storage_root>/projects/<project_id>
+    let config_dir = storage_root.join("projects").join(project_id);
 
-    // 4. Đảm bảo thư mục tồn tại
+    // 3. Đảm bảo thư mục tồn tại
     fs::create_dir_all(&config_dir)
         .map_err(|e| format!("Không thể tạo thư mục cấu hình cho dự án: {}", e))?;
     
     Ok(config_dir)
 }
 
-// --- CẬP NHẬT: Nhận thêm `app` và `profile_name` ---
-pub fn get_project_config_path(
-    app: &AppHandle,
-    project_path_str: &str,
-) -> Result<PathBuf, String> {
-    let config_dir = get_project_config_dir(app, project_path_str)?;
-    Ok(config_dir.join(PROJECT_DATA_FILENAME))
-}
-
-// --- CẬP NHẬT: Nhận thêm `app` và `profile_name` ---
-pub fn load_project_data(app: &AppHandle, path: &str) -> Result<CachedProjectData, String> {
-    let config_path = get_project_config_path(app, path)?;
+pub fn load_project_data(storage_root: &Path, path: &str) -> Result<CachedProjectData, String> {
+    let config_path = get_project_config_dir(storage_root, path)?.join(PROJECT_DATA_FILENAME);
     if !config_path.exists() {
         // If file doesn't exist, return a default empty structure
         return Ok(CachedProjectData::default());
@@ -58,13 +64,12 @@ pub fn load_project_data(app: &AppHandle, path: &str) -> Result<CachedProjectDat
     serde_json::from_str(&contents).map_err(|e| format!("Lỗi phân tích cú pháp JSON: {}", e))
 }
 
-// --- CẬP NHẬT: Nhận thêm `app` và `profile_name` ---
 pub fn save_project_data(
-    app: &AppHandle,
+    storage_root: &Path,
     path: &str,
     data: &CachedProjectData,
 ) -> Result<(), String> {
-    let config_path = get_project_config_path(app, path)?;
+    let config_path = get_project_config_dir(storage_root, path)?.join(PROJECT_DATA_FILENAME);
     let json_string = serde_json::to_string_pretty(data)
         .map_err(|e| format!("Không thể serialize dữ liệu dự án: {}", e))?;
     let mut file = File::create(config_path)
@@ -73,3 +78,83 @@ pub fn save_project_data(
         .map_err(|e| format!("Lỗi khi ghi file dữ liệu dự án: {}", e))?;
     Ok(())
 }
+
+/// Khóa dữ liệu của dự án: giữ khóa trong suốt quá trình nạp, sửa và lưu dữ liệu
+/// để watcher và các lệnh từ giao diện không ghi đè thay đổi của nhau.
+pub fn lock_project_data(storage_root: &Path, path: &str) -> Result<MutexGuard<'static, ()>, String> {
+    let config_dir = get_project_config_dir(storage_root, path)?;
+    let lock: &'static Mutex<()> = PROJECT_DATA_LOCKS
+        .lock()
+        .unwrap()
+        .entry(config_dir)
+        .or_insert_with(|| Box::leak(Box::default()));
+    // Khóa chỉ bảo vệ thứ tự đọc-ghi, không có dữ liệu nào bị hỏng khi một luồng panic
+    Ok(lock.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+/// Nạp dữ liệu dự án, cho `update` sửa rồi lưu lại, tất cả dưới khóa của dự án.
+/// Dữ liệu không được lưu nếu `update` trả về lỗi.
+pub fn update_project_data<T>(
+    storage_root: &Path,
+    path: &str,
+    update: impl FnOnce(&mut CachedProjectData) -> Result<T, String>,
+) -> Result<T, String> {
+    let _lock = lock_project_data(storage_root, path)?;
+    let mut data = load_project_data(storage_root, path)?;
+    let result = update(&mut data)?;
+    save_project_data(storage_root, path, &data)?;
+    Ok(result)
+}
+
+fn get_app_settings_path(storage_root: &Path) -> Result<PathBuf, String> {
+    if !storage_root.exists() {
+        fs::create_dir_all(storage_root)
+            .map_err(|e| format!("Không thể tạo thư mục cấu hình: {}", e))?;
+    }
+    Ok(storage_root.join(APP_SETTINGS_FILENAME))
+}
+
+pub fn load_app_settings(storage_root: &Path) -> Result<AppSettings, String> {
+    let path = get_app_settings_path(storage_root)?;
+    if !path.exists() {
+        return Ok(AppSettings::default());
+    }
+    let contents = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    if contents.is_empty() {
+        return Ok(AppSettings::default());
+    }
+    serde_json::from_str(&contents).map_err(|e| e.to_string())
+}
+
+pub fn save_app_settings(storage_root: &Path, settings: &AppSettings) -> Result<(), String> {
+    let settings_path = get_app_settings_path(storage_root)?;
+    let json_string = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    let mut file = File::create(settings_path).map_err(|e| e.to_string())?;
+    file.write_all(json_string.as_bytes())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Group;
+
+    #[test]
+    fn concurrent_updates_do_not_overwrite_each_other() {
+        let storage = tempfile::tempdir().unwrap();
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let storage_root = storage.path();
+                scope.spawn(move || {
+                    update_project_data(storage_root, "/project", |data| {
+                        data.groups.push(Group { id: i.to_string(), ..Default::default() });
+                        Ok(())
+                    })
+                    .unwrap();
+                });
+            }
+        });
+        let data = load_project_data(storage.path(), "/project").unwrap();
+        assert_eq!(data.groups.len(), 8);
+    }
+}
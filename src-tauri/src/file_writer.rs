@@ -0,0 +1,256 @@
+// src-tauri/src/file_writer.rs
+// Đọc/ghi file văn bản của dự án mà không làm thay đổi định dạng gốc: mã hóa (UTF-8 có/không BOM,
+// UTF-16 có BOM), kiểu xuống dòng, dòng trống cuối file và quyền truy cập. Việc ghi đi qua một file
+// tạm rồi đổi tên, nên file không bao giờ bị ghi dở nếu ứng dụng dừng giữa chừng.
+use crate::models::FileMtime;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Mã lỗi khi file trên đĩa đã bị thay đổi kể từ lần đọc.
+pub const MODIFIED_ON_DISK: &str = "file.modified_on_disk";
+
+const UTF8_BOM: &[u8] = &[0xEF, 0xBB, 0xBF];
+const UTF16LE_BOM: &[u8] = &[0xFF, 0xFE];
+const UTF16BE_BOM: &[u8] = &[0xFE, 0xFF];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    Utf16Le,
+    Utf16Be,
+}
+
+/// Định dạng của một file văn bản, được giữ nguyên khi ghi lại.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextFormat {
+    pub encoding: TextEncoding,
+    pub crlf: bool,
+    // None khi file không có dòng nào để suy ra (file rỗng hoặc chỉ một dòng không xuống dòng)
+    pub trailing_newline: Option<bool>,
+}
+
+/// Đọc file văn bản, trả về nội dung (không kèm BOM) và định dạng gốc.
+pub fn read_text(path: &Path) -> Result<(String, TextFormat), String> {
+    let bytes = fs::read(path).map_err(|e| format!("Không thể đọc file: {}", e))?;
+    decode(&bytes)
+}
+
+/// mtime hiện tại của file, dùng cho kiểm tra ghi đồng thời.
+pub fn file_mtime(path: &Path) -> Result<FileMtime, String> {
+    let modified = fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| format!("Không thể đọc thông tin file: {}", e))?
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    Ok(FileMtime { secs: modified.as_secs(), nanos: modified.subsec_nanos() })
+}
+
+/// Ghi `content` vào `path` một cách nguyên tử. Nếu file đã tồn tại, mã hóa, kiểu xuống dòng,
+/// dòng trống cuối file và quyền truy cập của nó được giữ nguyên. Với `expected_mtime`, từ chối ghi
+/// (`file.modified_on_disk`) khi file đã bị sửa hoặc xóa kể từ lần đọc.
+pub fn write_text_preserving(
+    path: &Path,
+    content: &str,
+    expected_mtime: Option<FileMtime>,
+) -> Result<(), String> {
+    let existing = match fs::metadata(path) {
+        Ok(metadata) => Some(metadata),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Không thể đọc thông tin file: {}", e)),
+    };
+    if let Some(expected) = expected_mtime {
+        if existing.is_none() || file_mtime(path)? != expected {
+            return Err(MODIFIED_ON_DISK.to_string());
+        }
+    }
+
+    let bytes = match &existing {
+        Some(_) => {
+            // File không đọc được dạng văn bản thì ghi nguyên nội dung mới
+            let format = read_text(path).map(|(_, format)| format).ok();
+            match format {
+                Some(format) => encode(&apply_format(content, format), format.encoding),
+                None => content.as_bytes().to_vec(),
+            }
+        }
+        None => content.as_bytes().to_vec(),
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Không thể tạo thư mục cha: {}", e))?;
+    }
+    let temp_path = temp_path_for(path);
+    let result = write_and_rename(&temp_path, path, &bytes, existing.map(|m| m.permissions()));
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn write_and_rename(
+    temp_path: &Path,
+    path: &Path,
+    bytes: &[u8],
+    permissions: Option<fs::Permissions>,
+) -> Result<(), String> {
+    let mut file = fs::File::create(temp_path).map_err(|e| format!("Không thể tạo file tạm: {}", e))?;
+    file.write_all(bytes).map_err(|e| format!("Không thể ghi file: {}", e))?;
+    if let Some(permissions) = permissions {
+        file.set_permissions(permissions)
+            .map_err(|e| format!("Không thể giữ quyền truy cập của file: {}", e))?;
+    }
+    file.sync_all().map_err(|e| format!("Không thể ghi file: {}", e))?;
+    drop(file);
+    fs::rename(temp_path, path).map_err(|e| format!("Không thể thay thế file: {}", e))
+}
+
+// File tạm là file ẩn trong cùng thư mục (cùng ổ đĩa để đổi tên nguyên tử, watcher bỏ qua file ẩn)
+fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    path.with_file_name(format!(".{}.{}.tmp", name, std::process::id()))
+}
+
+fn decode(bytes: &[u8]) -> Result<(String, TextFormat), String> {
+    let invalid = |e: String| format!("File không phải văn bản hợp lệ: {}", e);
+    let (content, encoding) = if let Some(rest) = bytes.strip_prefix(UTF8_BOM) {
+        (String::from_utf8(rest.to_vec()).map_err(|e| invalid(e.to_string()))?, TextEncoding::Utf8Bom)
+    } else if let Some(rest) = bytes.strip_prefix(UTF16LE_BOM) {
+        (decode_utf16(rest, u16::from_le_bytes).map_err(invalid)?, TextEncoding::Utf16Le)
+    } else if let Some(rest) = bytes.strip_prefix(UTF16BE_BOM) {
+        (decode_utf16(rest, u16::from_be_bytes).map_err(invalid)?, TextEncoding::Utf16Be)
+    } else {
+        (String::from_utf8(bytes.to_vec()).map_err(|e| invalid(e.to_string()))?, TextEncoding::Utf8)
+    };
+
+    let newlines = content.matches('\n').count();
+    let crlf_count = content.matches("\r\n").count();
+    let format = TextFormat {
+        encoding,
+        // Kiểu xuống dòng chiếm đa số
+        crlf: crlf_count * 2 > newlines,
+        trailing_newline: (newlines > 0).then(|| content.ends_with('\n')),
+    };
+    Ok((content, format))
+}
+
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Result<String, String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err("độ dài UTF-16 lẻ".to_string());
+    }
+    let units = bytes.chunks_exact(2).map(|pair| from_bytes([pair[0], pair[1]]));
+    char::decode_utf16(units)
+        .collect::<Result<String, _>>()
+        .map_err(|e| e.to_string())
+}
+
+// Chuẩn hóa xuống dòng và dòng trống cuối file của nội dung mới theo định dạng gốc
+fn apply_format(content: &str, format: TextFormat) -> String {
+    let content = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let mut normalized = content.replace("\r\n", "\n");
+    match format.trailing_newline {
+        Some(true) if !normalized.is_empty() && !normalized.ends_with('\n') => normalized.push('\n'),
+        Some(false) if normalized.ends_with('\n') => {
+            normalized.pop();
+        }
+        _ => {}
+    }
+    if format.crlf {
+        normalized = normalized.replace('\n', "\r\n");
+    }
+    normalized
+}
+
+fn encode(content: &str, encoding: TextEncoding) -> Vec<u8> {
+    match encoding {
+        TextEncoding::Utf8 => content.as_bytes().to_vec(),
+        TextEncoding::Utf8Bom => [UTF8_BOM, content.as_bytes()].concat(),
+        TextEncoding::Utf16Le => UTF16LE_BOM
+            .iter()
+            .copied()
+            .chain(content.encode_utf16().flat_map(u16::to_le_bytes))
+            .collect(),
+        TextEncoding::Utf16Be => UTF16BE_BOM
+            .iter()
+            .copied()
+            .chain(content.encode_utf16().flat_map(u16::to_be_bytes))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_keep_encoding_line_endings_and_trailing_newline() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let crlf_bom = dir.path().join("windows.txt");
+        fs::write(&crlf_bom, [UTF8_BOM, b"one\r\ntwo\r\n"].concat()).unwrap();
+        let (content, format) = read_text(&crlf_bom).unwrap();
+        assert_eq!(content, "one\r\ntwo\r\n");
+        assert_eq!(format.encoding, TextEncoding::Utf8Bom);
+        // Trình soạn thảo thường gửi về nội dung LF và bỏ dòng trống cuối
+        write_text_preserving(&crlf_bom, "one\ntwo\nthree", None).unwrap();
+        assert_eq!(fs::read(&crlf_bom).unwrap(), [UTF8_BOM, b"one\r\ntwo\r\nthree\r\n"].concat());
+
+        let no_trailing = dir.path().join("no_trailing.rs");
+        fs::write(&no_trailing, "fn a() {}\nfn b() {}").unwrap();
+        write_text_preserving(&no_trailing, "fn a() {}\nfn c() {}\n", None).unwrap();
+        assert_eq!(fs::read_to_string(&no_trailing).unwrap(), "fn a() {}\nfn c() {}");
+
+        let utf16 = dir.path().join("utf16.txt");
+        let utf16_bytes = |text: &str| -> Vec<u8> {
+            UTF16LE_BOM.iter().copied().chain(text.encode_utf16().flat_map(u16::to_le_bytes)).collect()
+        };
+        fs::write(&utf16, utf16_bytes("xin chào\n")).unwrap();
+        assert_eq!(read_text(&utf16).unwrap().0, "xin chào\n");
+        write_text_preserving(&utf16, "tạm biệt\n", None).unwrap();
+        assert_eq!(fs::read(&utf16).unwrap(), utf16_bytes("tạm biệt\n"));
+
+        // File mới được ghi đúng như nội dung nhận được, không để lại file tạm
+        let created = dir.path().join("nested/new.txt");
+        write_text_preserving(&created, "a\r\nb", None).unwrap();
+        assert_eq!(fs::read_to_string(&created).unwrap(), "a\r\nb");
+        let leftovers: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .filter_map(Result::ok)
+            .filter(|e| e.file_name().to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn refuses_to_overwrite_files_changed_since_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("shared.txt");
+        fs::write(&path, "v1\n").unwrap();
+        let read_at = file_mtime(&path).unwrap();
+
+        write_text_preserving(&path, "v2\n", Some(read_at)).unwrap();
+        // Lần ghi trước đã đổi mtime: phiên bản đọc lúc đầu không còn mới nhất
+        let stale = FileMtime { secs: read_at.secs.saturating_sub(10), ..read_at };
+        assert_eq!(write_text_preserving(&path, "v3\n", Some(stale)), Err(MODIFIED_ON_DISK.to_string()));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "v2\n");
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(write_text_preserving(&path, "v3\n", Some(read_at)), Err(MODIFIED_ON_DISK.to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_unix_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.sh");
+        fs::write(&path, "#!/bin/sh\necho hi\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o750)).unwrap();
+        write_text_preserving(&path, "#!/bin/sh\necho bye\n", None).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o750);
+    }
+}
@@ -1 +1,19 @@
-// src-tauri/src/git_utils.rs
\ No newline at end of file
+// src-tauri/src/git_utils.rs
+use git2::Repository;
+
+/// Tên nhánh hiện tại của repository chứa dự án (None nếu không phải repo Git hoặc HEAD tách rời).
+pub fn current_branch(project_path: &str) -> Option<String> {
+    let repo = Repository::discover(project_path).ok()?;
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    head.shorthand().map(String::from)
+}
+
+/// SHA của commit HEAD.
+pub fn head_commit_sha(project_path: &str) -> Option<String> {
+    let repo = Repository::discover(project_path).ok()?;
+    let head = repo.head().ok()?;
+    head.target().map(|oid| oid.to_string())
+}
@@ -9,15 +9,25 @@ pub fn update_groups_after_scan(
     new_metadata_cache: &BTreeMap<String, FileMetadata>,
     path_map: &BTreeMap<PathBuf, bool>,
     root_path: &Path,
+) {
+    prune_groups_and_recalculate(groups, new_metadata_cache, root_path, |p| {
+        path_map
+            .get(&root_path.join(p))
+            .map_or(false, |is_dir| *is_dir)
+    });
+}
+
+/// Xóa khỏi các nhóm những đường dẫn không còn tồn tại rồi tính lại stats.
+/// `dir_exists` cho biết một đường dẫn tương đối có phải thư mục còn tồn tại hay không.
+pub fn prune_groups_and_recalculate(
+    groups: &mut Vec<Group>,
+    new_metadata_cache: &BTreeMap<String, FileMetadata>,
+    root_path: &Path,
+    dir_exists: impl Fn(&str) -> bool,
 ) {
     for group in groups {
         // 1. Xóa các file/thư mục không còn tồn tại
-        group.paths.retain(|p| {
-            new_metadata_cache.contains_key(p)
-                || path_map
-                    .get(&root_path.join(p))
-                    .map_or(false, |is_dir| *is_dir)
-        });
+        group.paths.retain(|p| new_metadata_cache.contains_key(p) || dir_exists(p));
 
         // 2. Luôn tính toán lại stats sau khi đã cập nhật `paths`
         group.stats = recalculate_stats_for_paths(&group.paths, new_metadata_cache, root_path);
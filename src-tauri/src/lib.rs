@@ -1,13 +1,31 @@
 // src-tauri/src/lib.rs
 
 // Khai báo các module
+// Lõi xử lý (không phụ thuộc Tauri): project_scanner, context_generator,
+// group_updater, file_cache, progress, models. Có thể dùng trực tiếp từ
+// các công cụ Rust khác, chỉ cần truyền `ProgressReporter` và thư mục lưu trữ.
+pub mod checkpoint;
+pub mod cli;
 pub mod commands;
+pub mod comment_stripper;
+pub mod context_format;
+pub mod context_generator;
+pub mod context_template;
+pub mod dependency_analyzer;
+pub mod edit_applier;
+pub mod file_cache;
+pub mod file_writer;
 pub mod git_utils;
 pub mod group_updater;
-mod context_generator;
-mod file_cache;
-mod models;
-mod project_scanner;
+pub mod mcp_server;
+pub mod models;
+pub mod outline;
+pub mod path_guard;
+pub mod progress;
+pub mod project_scanner;
+pub mod secret_redactor;
+pub mod symbol_index;
+pub mod tokenizer;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -17,6 +35,12 @@ pub fn run() {
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_fs::init())
+        .on_window_event(|window, event| {
+            // Watcher của cửa sổ đã đóng không còn nơi nhận sự kiện
+            if let tauri::WindowEvent::Destroyed = event {
+                commands::stop_window_watchers(window.label());
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // THAY THẾ DÒNG NÀY
             // commands::open_project,
@@ -24,6 +48,10 @@ pub fn run() {
             commands::delete_project_data,
             // ... (các command còn lại)
             commands::get_file_content,
+            commands::read_file_snapshot,
+            commands::apply_search_replace,
+            commands::apply_unified_diff,
+            commands::apply_change_set,
             commands::read_file_with_lines,
             commands::save_file_content,
             commands::create_file,
@@ -39,18 +67,35 @@ pub fn run() {
             commands::update_sync_settings,
             commands::generate_group_context,
             commands::generate_project_context,
+            commands::generate_project_context_chunks,
+            commands::get_project_context_size,
+            commands::scan_project_for_secrets,
+            commands::get_group_context_size,
+            commands::build_symbol_index,
+            commands::generate_repo_map,
+            commands::get_dependents,
             commands::update_custom_ignore_patterns,
             commands::set_file_watching_setting,
+            commands::set_watcher_debounce_setting,
             commands::start_file_watching,
             commands::stop_file_watching,
+            commands::list_active_watchers,
             commands::set_export_use_full_tree_setting,
             commands::set_export_with_line_numbers_setting,
+            commands::set_export_format_setting,
             commands::set_export_without_comments_setting, // <-- COMMAND MỚI
             commands::set_export_remove_debug_logs_setting, // <-- COMMAND MỚI
             commands::set_export_super_compressed_setting,
+            commands::set_export_outline_setting,
+            commands::set_export_repo_map_setting,
             commands::get_expanded_files_for_group,
             commands::update_group_paths_from_ai,
+            commands::expand_group_dependencies,
+            commands::add_group_dependents,
             commands::set_export_exclude_extensions_setting, // <-- COMMAND MỚI
+            commands::set_secret_redaction_setting,
+            commands::set_export_chunk_token_limit_setting,
+            commands::set_export_template_setting,
             commands::set_always_apply_text_setting,
             commands::get_app_settings,
             commands::set_recent_paths,
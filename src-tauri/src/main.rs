@@ -2,5 +2,31 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `master-context scan|groups|export ...` chạy ở chế độ CLI, không mở cửa sổ
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if master_context_lib::cli::is_cli_invocation(&args) {
+        attach_parent_console();
+        std::process::exit(master_context_lib::cli::run(args));
+    }
     master_context_lib::run()
 }
+
+// Bản release trên Windows dùng subsystem "windows" nên không có console: gắn vào console
+// của tiến trình cha (terminal đã gọi lệnh) để println!/eprintln! hiển thị được. Khi stdio đã
+// được chuyển hướng (pipe của client MCP, `> out.txt`) thì các handle kế thừa vẫn được giữ.
+// Tiến trình GUI không được shell chờ, nên dấu nhắc lệnh có thể xuất hiện trước kết quả.
+#[cfg(windows)]
+fn attach_parent_console() {
+    const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn AttachConsole(process_id: u32) -> i32;
+    }
+    // Thất bại khi đã có console (bản debug) hoặc tiến trình cha không có console: bỏ qua
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(not(windows))]
+fn attach_parent_console() {}
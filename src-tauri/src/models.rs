@@ -117,9 +117,22 @@ pub struct AppSettings {
     pub top_p: Option<f64>,
     pub top_k: Option<u32>,
     pub max_tokens: Option<u32>,
+    // Tokenizer dùng để đếm token (None = cl100k)
+    pub tokenizer: Option<Tokenizer>,
 }
 // --- KẾT THÚC PHẦN THÊM MỚI ---
 
+// --- TOKENIZER THEO MÔ HÌNH ĐÍCH ---
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Tokenizer {
+    #[default]
+    Cl100k, // GPT-4, GPT-3.5
+    O200k,       // GPT-4o, o1
+    P50k,        // Codex, text-davinci
+    Approximate, // Ước lượng cho các mô hình khác (Claude, Gemini, ...)
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
 pub struct GroupStats {
     pub total_files: u64,
@@ -134,6 +147,9 @@ pub struct ProjectStats {
     pub total_dirs: u64,
     pub total_size: u64,
     pub total_tokens: usize,
+    // Tokenizer đã dùng để tính `total_tokens`
+    #[serde(default)]
+    pub tokenizer: Tokenizer,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -143,6 +159,38 @@ pub struct FileNode {
     pub children: Option<Vec<FileNode>>,
 }
 
+// Một watcher đang chạy, gắn với một dự án trong một cửa sổ
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveWatcher {
+    pub project_path: String,
+    pub window_label: String,
+}
+
+// Thay đổi của dự án sau một lần cập nhật tăng dần từ watcher (đường dẫn tương đối)
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDiff {
+    pub added: Vec<String>,
+    // File hoặc thư mục đã bị xóa (xóa thư mục kéo theo toàn bộ file bên trong)
+    pub removed: Vec<String>,
+    pub modified: Vec<String>,
+    // Thư mục mới xuất hiện trong cây (kể cả thư mục rỗng)
+    pub added_dirs: Vec<String>,
+    // File chỉ bị "touch": nội dung không đổi, chỉ mtime trong cache được cập nhật.
+    // Không tính vào `is_empty` nhưng dữ liệu dự án vẫn phải được lưu lại
+    pub touched: Vec<String>,
+}
+
+impl ProjectDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.added_dirs.is_empty()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Group {
@@ -151,14 +199,72 @@ pub struct Group {
     pub paths: Vec<String>,
     pub stats: GroupStats,
     pub token_limit: Option<usize>,
+    // Template riêng của nhóm, ưu tiên hơn template của dự án
+    pub template_path: Option<String>,
+    // Chế độ dàn ý riêng của nhóm (None = theo cài đặt của dự án)
+    pub outline: Option<bool>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+// Trạng thái phân tích nội dung của một file trong cache
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalysisState {
+    #[default]
+    Pending, // Chưa phân tích (cache cũ hoặc file mới)
+    Analyzed,   // Đã đếm token và phân tích import
+    Skipped,    // Loại file không phân tích nội dung (theo cài đặt)
+    Unreadable, // Không đọc được hoặc không phải văn bản UTF-8
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
 pub struct FileMetadata {
     pub size: u64,
-    pub mtime: u64,
-    pub token_count: usize,
+    pub mtime: u64, // Giây kể từ UNIX_EPOCH
+    // Phần nano giây của mtime, để phát hiện sửa đổi trong cùng một giây
+    pub mtime_nanos: Option<u32>,
+    pub token_count: usize, // Theo tokenizer đang chọn
+    // Số token theo từng tokenizer đã từng đếm, để đổi tokenizer không cần đọc lại file
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub token_counts: BTreeMap<Tokenizer, usize>,
     pub excluded_ranges: Option<Vec<(usize, usize)>>,
+    // Các file trong dự án mà file này import (None = chưa phân tích)
+    pub dependencies: Option<Vec<String>>,
+    // Câu lệnh import thô; được phân giải lại thành `dependencies` mỗi khi tập file thay đổi
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub imports: Option<Vec<String>>,
+    // SHA-256 của nội dung; file chỉ bị "touch" vẫn giữ được cache
+    pub content_hash: Option<String>,
+    #[serde(default)]
+    pub analysis_state: AnalysisState,
+}
+
+impl FileMetadata {
+    /// Số token đã cache cho `tokenizer`, None nếu chưa từng đếm.
+    pub fn token_count_for(&self, tokenizer: Tokenizer) -> Option<usize> {
+        if self.analysis_state != AnalysisState::Analyzed {
+            return Some(self.token_count); // Không phân tích nội dung: luôn là 0
+        }
+        match self.token_counts.get(&tokenizer) {
+            Some(count) => Some(*count),
+            // Cache cũ chỉ có `token_count`, luôn được đếm bằng cl100k
+            None if self.token_counts.is_empty() && tokenizer == Tokenizer::Cl100k => {
+                Some(self.token_count)
+            }
+            None => None,
+        }
+    }
+
+    /// Hai phiên bản có cùng nội dung: so hash nếu có, nếu không thì so size/mtime.
+    pub fn same_content(&self, other: &FileMetadata) -> bool {
+        match (&self.content_hash, &other.content_hash) {
+            (Some(a), Some(b)) => self.size == other.size && a == b,
+            _ => {
+                self.size == other.size
+                    && self.mtime == other.mtime
+                    && self.mtime_nanos == other.mtime_nanos
+            }
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -172,14 +278,212 @@ pub struct CachedProjectData {
     pub data_hash: Option<String>,
     pub custom_ignore_patterns: Option<Vec<String>>, // <-- THÊM TRƯỜNG NÀY
     pub is_watching_files: Option<bool>, // <-- THÊM TRƯỜNG MỚI
+    // Khoảng yên lặng (ms) trước khi watcher xử lý một loạt thay đổi (None = mặc định)
+    pub watcher_debounce_ms: Option<u64>,
     pub export_use_full_tree: Option<bool>, // <-- THÊM TRƯỜNG MỚI NÀY
     pub export_with_line_numbers: Option<bool>, // <-- THÊM TRƯỜNG MỚI
     pub export_without_comments: Option<bool>, // <-- THÊM TRƯỜNG MỚI
     pub export_remove_debug_logs: Option<bool>, // <-- THÊM TRƯỜNG MỚI
     pub export_super_compressed: Option<bool>,
+    pub export_outline: Option<bool>,
     pub always_apply_text: Option<String>,
     pub export_exclude_extensions: Option<Vec<String>>,
     pub git_export_mode_is_context: Option<bool>,
+    // Số token tối đa mỗi phần khi tự động xuất ngữ cảnh dự án (None = không chia)
+    pub export_chunk_token_limit: Option<usize>,
+    pub export_format: Option<ExportFormat>,
+    // Đường dẫn tới file template (tuyệt đối hoặc tương đối so với thư mục dự án)
+    pub export_template_path: Option<String>,
+    // Chỉ mục ký hiệu theo file, làm mới dựa trên size/mtime trong file_metadata_cache
+    pub symbol_index: Option<BTreeMap<String, FileSymbols>>,
+    // Chèn bản đồ ký hiệu của phần còn lại của dự án vào đầu ngữ cảnh nhóm
+    pub export_repo_map: Option<bool>,
+    // Che các bí mật khi xuất ngữ cảnh (None = bật)
+    pub export_redact_secrets: Option<bool>,
+    // Biểu thức chính quy bổ sung của người dùng cho bí mật cần che
+    pub secret_patterns: Option<Vec<String>>,
+}
+
+// --- ĐỊNH DẠNG ĐẦU RA CỦA NGỮ CẢNH ---
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Plain, // ====\nFILE: ...\n==== (mặc định)
+    Xml,      // <file path="...">...</file>
+    Markdown, // ## path + code block có gắn ngôn ngữ
+    Json,     // Một tài liệu JSON với danh sách file
+    Jsonl,    // Mỗi dòng một đối tượng JSON
+}
+
+// --- GIỚI HẠN TOKEN KHI XUẤT NHÓM ---
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetAction {
+    Outlined,   // Chỉ giữ các khai báo (dàn ý)
+    Compressed, // Thu gọn khoảng trắng thành một dòng
+    Truncated,  // Chỉ giữ phần đầu của file
+    Omitted,    // Bỏ hẳn nội dung file
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetCutFile {
+    pub path: String,
+    pub action: BudgetAction,
+    pub original_tokens: usize,
+    pub final_tokens: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenBudgetReport {
+    pub token_limit: usize,
+    pub original_token_count: usize,
+    pub final_token_count: usize,
+    pub cut_files: Vec<BudgetCutFile>,
+}
+
+// --- KÍCH THƯỚC THỰC CỦA NGỮ CẢNH KHI XUẤT ---
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileContextMeasurement {
+    pub path: String,
+    // Phần đóng góp của file vào ngữ cảnh, sau khi xử lý và định dạng
+    pub tokens: usize,
+    pub bytes: usize,
+    pub budget_action: Option<BudgetAction>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextMeasurement {
+    pub tokenizer: Tokenizer,
+    pub total_tokens: usize,
+    pub total_bytes: usize,
+    // Cây thư mục, tiêu đề, văn bản ALWAYS APPLY... (không thuộc file nào)
+    pub overhead_tokens: usize,
+    pub overhead_bytes: usize,
+    pub files: Vec<FileContextMeasurement>,
+}
+
+// --- ĐỌC/GHI FILE TỪ TRÌNH SOẠN THẢO VÀ CÔNG CỤ AI ---
+// mtime của file lúc đọc, gửi lại khi ghi để phát hiện thay đổi từ bên ngoài
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileMtime {
+    pub secs: u64,
+    pub nanos: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FileSnapshot {
+    pub content: String,
+    pub mtime: FileMtime,
+}
+
+// Kết quả một chỉnh sửa đã ghi xuống đĩa. None nghĩa là file chưa tồn tại (tạo mới) hoặc đã bị xóa.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FileEdit {
+    pub path: String,
+    pub original_content: Option<String>,
+    pub new_content: Option<String>,
+}
+
+// Một thao tác trong change set, áp dụng theo kiểu tất cả hoặc không gì cả
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase", rename_all_fields = "camelCase")]
+pub enum FileOperation {
+    Create { path: String, content: String },
+    Modify {
+        path: String,
+        content: String,
+        #[serde(default)]
+        expected_mtime: Option<FileMtime>,
+    },
+    Delete { path: String },
+    Rename { from: String, to: String },
+}
+
+impl FileOperation {
+    /// Các đường dẫn tương đối mà thao tác chạm tới (với đổi tên: nguồn rồi đích).
+    pub fn paths(&self) -> Vec<&str> {
+        match self {
+            FileOperation::Create { path, .. }
+            | FileOperation::Modify { path, .. }
+            | FileOperation::Delete { path } => vec![path],
+            FileOperation::Rename { from, to } => vec![from, to],
+        }
+    }
+}
+
+// --- CHE BÍ MẬT KHI XUẤT ---
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactedSecret {
+    pub path: String,
+    pub line: usize, // Bắt đầu từ 1
+    pub rule: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RedactionReport {
+    pub secrets: Vec<RedactedSecret>,
+}
+
+// Nội dung xuất (diff hoặc ngữ cảnh commit) kèm báo cáo các bí mật đã bị che
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ExportedContext {
+    pub context: String,
+    pub redactions: RedactionReport,
+}
+
+// Mức độ nghiêm trọng của bí mật tìm thấy khi quét (sắp xếp từ cao xuống thấp)
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretSeverity {
+    High,   // Khóa riêng, khóa truy cập đám mây, token
+    Medium, // Giá trị trong `.env`, mẫu của người dùng
+    Low,    // Chuỗi ngẫu nhiên, có thể là dương tính giả
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretFinding {
+    pub path: String,
+    pub line: usize, // Bắt đầu từ 1
+    pub rule: String,
+    pub severity: SecretSeverity,
+}
+
+// --- CHỈ MỤC KÝ HIỆU (REPO MAP) ---
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SymbolKind {
+    Function,
+    Method,
+    Type, // struct, enum, trait, class, interface...
+    Const,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: SymbolKind,
+    pub line: usize, // Bắt đầu từ 1
+    // Kiểu chứa ký hiệu (class/impl/struct...), nếu có
+    pub parent: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileSymbols {
+    // size/mtime và hash nội dung của file lúc trích xuất, dùng để biết khi nào cần trích xuất lại
+    pub size: u64,
+    pub mtime: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<String>,
+    pub symbols: Vec<Symbol>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -0,0 +1,325 @@
+// src-tauri/src/outline.rs
+// Chế độ dàn ý (outline): chỉ giữ các khai báo của file nguồn — import, định nghĩa kiểu
+// (struct/trait/class...) và chữ ký hàm, còn thân hàm được thay bằng `...`.
+// Nhờ đó cả một dự án lớn có thể nằm gọn trong ngữ cảnh như một bản đồ API.
+use crate::comment_stripper::{code_mask, strip_comments};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::path::Path;
+
+// Thân khối ngắn trên một dòng được giữ nguyên, ví dụ `fn id(&self) -> u32 { self.id }`
+const MAX_INLINE_BODY: usize = 80;
+
+// Từ khóa mở một khối chứa khai báo: giữ nội dung bên trong, chỉ lược thân hàm
+const CONTAINER_KEYWORDS: &[&str] = &[
+    "class", "struct", "trait", "interface", "impl", "enum", "namespace", "mod", "module",
+    "object", "extension", "protocol", "union", "record", "type", "extern",
+];
+const FUNCTION_KEYWORDS: &[&str] = &["fn", "function", "func", "fun", "def"];
+// Khối lệnh không có điều kiện, không phải khai báo
+const STATEMENT_KEYWORDS: &[&str] = &[
+    "else", "try", "finally", "do", "loop", "unsafe", "async", "defer", "get", "set", "init",
+    "static",
+];
+
+lazy_static! {
+    // Hàm một dòng của Ruby: `def name = value` hoặc `def name(args) = value`
+    static ref RUBY_ENDLESS_DEF: Regex =
+        Regex::new(r"^def\s+[\w.]+[?!]?(\([^)]*\))?\s*=[^=~]").unwrap();
+}
+
+enum Style {
+    Braces,
+    // Khối theo thụt lề (Python, GDScript) với các từ khóa mở hàm
+    Indented(&'static [&'static str]),
+    Ruby,
+}
+
+/// Trả về dàn ý của file (đã bỏ comment), hoặc None nếu ngôn ngữ chưa được hỗ trợ.
+pub fn outline_content(content: &str, file_rel_path: &str) -> Option<String> {
+    let extension = Path::new(file_rel_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let style = match extension.as_str() {
+        "rs" | "go" | "c" | "cpp" | "h" | "java" | "cs" | "swift" | "kt" | "js" | "jsx" | "ts"
+        | "tsx" | "dart" | "php" | "glsl" => Style::Braces,
+        "py" => Style::Indented(&["def ", "async def "]),
+        "gd" => Style::Indented(&["func ", "static func "]),
+        "rb" => Style::Ruby,
+        _ => return None,
+    };
+    let source = strip_comments(content, file_rel_path);
+    let mask = code_mask(&source, file_rel_path)?;
+    let mut output = String::with_capacity(source.len() / 2);
+    match style {
+        Style::Braces => outline_block(&source, &mask, 0, source.len(), &mut output),
+        Style::Indented(def_keywords) => outline_indented(&source, &mask, def_keywords, &mut output),
+        Style::Ruby => outline_ruby(&source, &mask, &mut output),
+    }
+    Some(output)
+}
+
+// --- NGÔN NGỮ DÙNG DẤU NGOẶC NHỌN ---
+
+// Chép nội dung một khối chứa khai báo trong khoảng [start, end), lược thân của các khối con
+fn outline_block(src: &str, mask: &[bool], start: usize, end: usize, output: &mut String) {
+    let bytes = src.as_bytes();
+    let mut copied = start;
+    let mut header_start = start;
+    let mut i = start;
+    while i < end {
+        if !mask[i] {
+            i += 1;
+            continue;
+        }
+        match bytes[i] {
+            b';' | b'}' => header_start = i + 1,
+            b'{' => {
+                let close = matching_brace(bytes, mask, i, end);
+                output.push_str(&src[copied..=i]);
+                if is_container(&src[header_start..i]) {
+                    outline_block(src, mask, i + 1, close, output);
+                } else {
+                    let body = &src[i + 1..close];
+                    if body.contains('\n') || body.len() > MAX_INLINE_BODY {
+                        output.push_str(" ... ");
+                    } else {
+                        output.push_str(body);
+                    }
+                }
+                if close < end {
+                    output.push('}');
+                }
+                i = close + 1;
+                copied = i.min(end);
+                header_start = copied;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if copied < end {
+        output.push_str(&src[copied..end]);
+    }
+}
+
+// Vị trí dấu `}` khớp với dấu `{` tại `open`, hoặc `end` nếu khối chưa được đóng
+fn matching_brace(bytes: &[u8], mask: &[bool], open: usize, end: usize) -> usize {
+    let mut depth = 0;
+    for i in open..end {
+        if !mask[i] {
+            continue;
+        }
+        match bytes[i] {
+            b'{' => depth += 1,
+            b'}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+    }
+    end
+}
+
+// Các từ nằm ngoài mọi cặp ngoặc (), [] và <>; bỏ qua tên biến ngay trước dấu `=`
+fn top_level_words(header: &str) -> Vec<&str> {
+    let bytes = header.as_bytes();
+    let mut words = Vec::new();
+    let mut depth = 0i32;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        match b {
+            b'(' | b'[' | b'<' => depth += 1,
+            b')' | b']' => depth -= 1,
+            // `->` và `=>` không đóng ngoặc nhọn
+            b'>' if i == 0 || !matches!(bytes[i - 1], b'-' | b'=') => depth -= 1,
+            _ if b.is_ascii_alphabetic() || b == b'_' => {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                let rest = header[i..].trim_start();
+                let is_assigned = rest.starts_with('=') && !rest.starts_with("==") && !rest.starts_with("=>");
+                if depth <= 0 && !is_assigned {
+                    words.push(&header[start..i]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    words
+}
+
+// Khối có giữ lại nội dung hay không: class/struct/impl... có, thân hàm và biểu thức thì không
+fn is_container(header: &str) -> bool {
+    let words = top_level_words(header);
+    if words.iter().any(|w| FUNCTION_KEYWORDS.contains(w)) {
+        return false;
+    }
+    if words.iter().any(|w| CONTAINER_KEYWORDS.contains(w)) {
+        return true;
+    }
+    // Khối không phải lời gọi hay biểu thức: biến thể struct của enum Rust, `export default {`...
+    !header.trim().is_empty()
+        && !header.contains(['(', '='])
+        && !words.last().is_some_and(|w| STATEMENT_KEYWORDS.contains(w))
+}
+
+// --- NGÔN NGỮ DÙNG THỤT LỀ ---
+
+// Dòng bắt đầu bên trong một chuỗi nhiều dòng (ký tự xuống dòng trước đó thuộc chuỗi)
+fn continues_literal(mask: &[bool], line_start: usize) -> bool {
+    line_start > 0 && !mask[line_start - 1]
+}
+
+fn line_end(src: &str, start: usize) -> usize {
+    src[start..].find('\n').map_or(src.len(), |p| start + p)
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+// Dấu `:` kết thúc chữ ký hàm Python (bỏ qua `:` trong ngoặc và chú thích kiểu)
+fn signature_colon(src: &str, mask: &[bool], start: usize) -> Option<usize> {
+    let mut depth = 0i32;
+    for (i, b) in src.bytes().enumerate().skip(start) {
+        if !mask[i] {
+            continue;
+        }
+        match b {
+            b'(' | b'[' | b'{' => depth += 1,
+            b')' | b']' | b'}' => depth -= 1,
+            b':' if depth <= 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn push_line(output: &mut String, line: &str) {
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output.push_str(line);
+}
+
+// Thụt lề của thân hàm: lấy theo dòng kế tiếp nếu sâu hơn, nếu không thì thêm 4 dấu cách
+fn body_indent(src: &str, after: usize, indent: &str) -> String {
+    src.get(after..)
+        .and_then(|rest| rest.lines().find(|line| !line.trim().is_empty()))
+        .map(|line| &line[..indent_of(line)])
+        .filter(|next| next.len() > indent.len())
+        .map_or_else(|| format!("{}    ", indent), str::to_string)
+}
+
+fn outline_indented(src: &str, mask: &[bool], def_keywords: &[&str], output: &mut String) {
+    let mut pos = 0;
+    // Đang bỏ qua thân hàm: các dòng thụt sâu hơn mức này
+    let mut skip_deeper_than: Option<usize> = None;
+    while pos < src.len() {
+        let end = line_end(src, pos);
+        let line = &src[pos..end];
+        let continuation = continues_literal(mask, pos);
+        let indent = indent_of(line);
+        if let Some(level) = skip_deeper_than {
+            if continuation || line.trim().is_empty() || indent > level {
+                pos = end + 1;
+                continue;
+            }
+            skip_deeper_than = None;
+        }
+
+        let trimmed = line.trim_start();
+        if !continuation && def_keywords.iter().any(|k| trimmed.starts_with(k)) {
+            if let Some(colon) = signature_colon(src, mask, pos + indent) {
+                let signature_end = line_end(src, colon);
+                push_line(output, &src[pos..signature_end]);
+                // Hàm một dòng (`def f(): return 1`) được giữ nguyên
+                if src[colon + 1..signature_end].trim().is_empty() {
+                    let body = body_indent(src, signature_end + 1, &line[..indent]);
+                    push_line(output, &format!("{}...", body));
+                    skip_deeper_than = Some(indent);
+                }
+                pos = signature_end + 1;
+                continue;
+            }
+        }
+        push_line(output, line);
+        pos = end + 1;
+    }
+}
+
+// Ruby: thân của `def ... end` được lược, giữ lại dòng `end` cùng mức thụt lề
+fn outline_ruby(src: &str, mask: &[bool], output: &mut String) {
+    let mut pos = 0;
+    let mut skipping: Option<usize> = None;
+    while pos < src.len() {
+        let end = line_end(src, pos);
+        let line = &src[pos..end];
+        let continuation = continues_literal(mask, pos);
+        let indent = indent_of(line);
+        let trimmed = line.trim();
+        if let Some(level) = skipping {
+            let closes = !continuation
+                && indent == level
+                && (trimmed == "end" || trimmed.starts_with("end ") || trimmed.starts_with("end;"));
+            if closes {
+                push_line(output, line);
+                skipping = None;
+            }
+            pos = end + 1;
+            continue;
+        }
+        push_line(output, line);
+        let is_one_liner = trimmed.ends_with(" end") || trimmed.ends_with(";end") || RUBY_ENDLESS_DEF.is_match(trimmed);
+        if !continuation && trimmed.starts_with("def ") && !is_one_liner {
+            push_line(output, &format!("{}  ...", &line[..indent]));
+            skipping = Some(indent);
+        }
+        pos = end + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_declarations_and_elides_bodies() {
+        let rust = "use std::fmt;\n\n/// Doc\n#[derive(Debug)]\npub struct A {\n    pub id: u32,\n}\n\nimpl A {\n    pub fn new(id: u32) -> Self {\n        let s = \"{ not a brace\";\n        Self { id }\n    }\n    fn id(&self) -> u32 { self.id }\n}\n\nenum E {\n    V { x: u32 },\n}\n\nfn run() -> impl Iterator<Item = u32> {\n    (0..3).map(|x| {\n        x * 2\n    })\n}\n";
+        assert_eq!(
+            outline_content(rust, "src/lib.rs").unwrap(),
+            "use std::fmt;\n#[derive(Debug)]\npub struct A {\n    pub id: u32,\n}\nimpl A {\n    pub fn new(id: u32) -> Self { ... }\n    fn id(&self) -> u32 { self.id }\n}\nenum E {\n    V { x: u32 },\n}\nfn run() -> impl Iterator<Item = u32> { ... }"
+        );
+
+        let ts = "import { a } from \"./a\";\nexport interface P {\n  name: string;\n}\nexport class C extends B {\n  private x = 1;\n  constructor(p: P) {\n    super(p);\n  }\n}\nexport const f = (x: number): number => {\n  return x + 1;\n};\n";
+        assert_eq!(
+            outline_content(ts, "a.ts").unwrap(),
+            "import { a } from \"./a\";\nexport interface P {\n  name: string;\n}\nexport class C extends B {\n  private x = 1;\n  constructor(p: P) { ... }\n}\nexport const f = (x: number): number => { ... };"
+        );
+
+        let python = "import os\n\n@dataclass\nclass A:\n    name: str = \"x\"\n\n    def greet(self,\n              other: \"A\") -> str:\n        text = \"\"\"\nnot code\n\"\"\"\n        return text\n\n    def short(self): return 1\n\ndef main():\n    pass\n";
+        assert_eq!(
+            outline_content(python, "app.py").unwrap(),
+            "import os\n@dataclass\nclass A:\n    name: str = \"x\"\n    def greet(self,\n              other: \"A\") -> str:\n        ...\n    def short(self): return 1\ndef main():\n    ..."
+        );
+
+        let ruby = "class A\n  def run(x)\n    x + 1\n  end\n  def name = \"a\"\nend\n";
+        assert_eq!(
+            outline_content(ruby, "a.rb").unwrap(),
+            "class A\n  def run(x)\n    ...\n  end\n  def name = \"a\"\nend"
+        );
+
+        assert!(outline_content("{}", "data.json").is_none());
+    }
+}
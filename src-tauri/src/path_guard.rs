@@ -0,0 +1,134 @@
+// src-tauri/src/path_guard.rs
+// Kiểm tra đường dẫn tương đối do frontend hoặc công cụ AI gửi tới, để các lệnh đọc/ghi file
+// không thể thoát khỏi thư mục dự án (qua `..`, đường dẫn tuyệt đối hoặc symlink)
+// hay chạm vào các file bị loại trừ khỏi dự án.
+use crate::project_scanner::PathFilter;
+use std::path::{Component, Path, PathBuf};
+
+/// Mã lỗi khi đường dẫn nằm ngoài thư mục dự án.
+pub const OUTSIDE_PROJECT: &str = "path.outside_project";
+/// Mã lỗi khi đường dẫn bị loại trừ bởi `.gitignore`, file ẩn hoặc mẫu loại trừ tùy chỉnh.
+pub const IGNORED: &str = "path.ignored";
+
+/// Trả về đường dẫn tuyệt đối (đã giải quyết symlink) của `file_rel_path` bên trong `root_path`.
+/// File chưa tồn tại (sắp được tạo) vẫn hợp lệ nếu thư mục cha gần nhất đã tồn tại nằm trong dự án.
+pub fn resolve_project_path(
+    root_path: &Path,
+    file_rel_path: &str,
+    custom_ignore_patterns: Option<&[String]>,
+) -> Result<PathBuf, String> {
+    let canonical_root = root_path
+        .canonicalize()
+        .map_err(|e| format!("Không thể truy cập thư mục dự án: {}", e))?;
+
+    // Chuẩn hóa theo từ vựng: không cho phép đường dẫn tuyệt đối hoặc `..` vượt khỏi gốc
+    let mut relative = PathBuf::new();
+    for component in Path::new(&file_rel_path.replace('\\', "/")).components() {
+        match component {
+            Component::Normal(part) => relative.push(part),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !relative.pop() {
+                    return Err(OUTSIDE_PROJECT.to_string());
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(OUTSIDE_PROJECT.to_string()),
+        }
+    }
+
+    // Phần đã tồn tại được hệ điều hành giải quyết symlink, phần chưa tồn tại được nối lại sau
+    let mut existing = canonical_root.join(&relative);
+    let mut missing = Vec::new();
+    let canonical_existing = loop {
+        match existing.canonicalize() {
+            Ok(canonical) => break canonical,
+            // Symlink trỏ tới nơi không tồn tại: không thể biết việc ghi sẽ đi đâu
+            Err(_) if existing.symlink_metadata().is_ok() => return Err(OUTSIDE_PROJECT.to_string()),
+            Err(_) => {
+                let name = existing.file_name().ok_or(OUTSIDE_PROJECT)?.to_os_string();
+                missing.push(name);
+                existing.pop();
+            }
+        }
+    };
+    let resolved = missing.iter().rev().fold(canonical_existing, |path, name| path.join(name));
+    let canonical_relative = resolved
+        .strip_prefix(&canonical_root)
+        .map_err(|_| OUTSIDE_PROJECT.to_string())?
+        .to_path_buf();
+
+    // Kiểm tra cả đường dẫn được yêu cầu lẫn file thật mà nó trỏ tới
+    let mut filter = PathFilter::new(&canonical_root, custom_ignore_patterns)?;
+    let is_dir = resolved.is_dir();
+    for candidate in [&relative, &canonical_relative] {
+        let candidate = candidate.to_string_lossy().replace('\\', "/");
+        if !candidate.is_empty() && !filter.is_included(&candidate, is_dir) {
+            return Err(IGNORED.to_string());
+        }
+    }
+    Ok(resolved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn rejects_parent_and_absolute_escapes_and_ignored_paths() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path().join("project");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(root.join(".ignore"), "build/\n").unwrap();
+        fs::write(workspace.path().join("id_rsa"), "secret").unwrap();
+        let custom = vec!["*.log".to_string()];
+        let resolve = |rel: &str| resolve_project_path(&root, rel, Some(&custom));
+
+        let canonical_root = root.canonicalize().unwrap();
+        assert_eq!(resolve("src/main.rs").unwrap(), canonical_root.join("src/main.rs"));
+        assert_eq!(resolve("./src/../src/main.rs").unwrap(), canonical_root.join("src/main.rs"));
+        // File sắp tạo trong thư mục chưa tồn tại
+        assert_eq!(resolve("src/new/mod.rs").unwrap(), canonical_root.join("src/new/mod.rs"));
+
+        assert_eq!(resolve("../id_rsa"), Err(OUTSIDE_PROJECT.to_string()));
+        assert_eq!(resolve("src/../../id_rsa"), Err(OUTSIDE_PROJECT.to_string()));
+        assert_eq!(resolve("src\\..\\..\\id_rsa"), Err(OUTSIDE_PROJECT.to_string()));
+        let absolute = workspace.path().join("id_rsa");
+        assert_eq!(resolve(absolute.to_str().unwrap()), Err(OUTSIDE_PROJECT.to_string()));
+
+        assert_eq!(resolve("build/out.js"), Err(IGNORED.to_string()));
+        assert_eq!(resolve("debug.log"), Err(IGNORED.to_string()));
+        assert_eq!(resolve(".env"), Err(IGNORED.to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlink_escapes() {
+        use std::os::unix::fs::symlink;
+
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path().join("project");
+        let outside = workspace.path().join("outside");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("id_rsa"), "secret").unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+
+        symlink(&outside, root.join("linked_dir")).unwrap();
+        symlink(outside.join("id_rsa"), root.join("key")).unwrap();
+        symlink(outside.join("missing"), root.join("dangling")).unwrap();
+        symlink(root.join("src/lib.rs"), root.join("alias.rs")).unwrap();
+        let resolve = |rel: &str| resolve_project_path(&root, rel, None);
+
+        assert_eq!(resolve("linked_dir/id_rsa"), Err(OUTSIDE_PROJECT.to_string()));
+        assert_eq!(resolve("linked_dir/new_file.txt"), Err(OUTSIDE_PROJECT.to_string()));
+        assert_eq!(resolve("key"), Err(OUTSIDE_PROJECT.to_string()));
+        assert_eq!(resolve("dangling"), Err(OUTSIDE_PROJECT.to_string()));
+        // Symlink trỏ vào bên trong dự án vẫn được phép
+        assert_eq!(
+            resolve("alias.rs").unwrap(),
+            root.canonicalize().unwrap().join("src/lib.rs")
+        );
+    }
+}
@@ -0,0 +1,26 @@
+// src-tauri/src/progress.rs
+// Báo cáo tiến trình cho lõi xử lý mà không phụ thuộc vào Tauri.
+// Bản GUI dùng `Window` (xem commands/utils.rs), CLI và các công cụ khác
+// có thể truyền một closure hoặc `NoopReporter`.
+
+/// Nhận các sự kiện tiến trình dạng `(event, payload)`, ví dụ
+/// `("scan_progress", "src/main.rs")`. Có thể được gọi từ nhiều luồng.
+pub trait ProgressReporter: Sync {
+    fn report(&self, event: &str, payload: &str);
+}
+
+/// Bỏ qua mọi sự kiện tiến trình.
+pub struct NoopReporter;
+
+impl ProgressReporter for NoopReporter {
+    fn report(&self, _event: &str, _payload: &str) {}
+}
+
+impl<F> ProgressReporter for F
+where
+    F: Fn(&str, &str) + Sync,
+{
+    fn report(&self, event: &str, payload: &str) {
+        self(event, payload)
+    }
+}
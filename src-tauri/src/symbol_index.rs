@@ -0,0 +1,520 @@
+// src-tauri/src/symbol_index.rs
+// Chỉ mục ký hiệu kiểu ctags: hàm, phương thức, kiểu và hằng số của từng file kèm số dòng.
+// Được hiển thị thành "bản đồ dự án" (repo map) gọn nhẹ để chèn vào đầu ngữ cảnh của nhóm,
+// giúp mô hình biết những gì tồn tại bên ngoài nhóm.
+use crate::comment_stripper::code_mask;
+use crate::models::{FileMetadata, FileSymbols, Symbol, SymbolKind};
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Lang {
+    Rust,
+    Go,
+    Js,
+    // Java, C#, Kotlin, Swift, Dart, C/C++: khai báo kiểu có dạng `class X {`
+    CLike,
+    Php,
+    Python,
+    GdScript,
+    Ruby,
+}
+
+fn lang_for_path(file_rel_path: &str) -> Option<Lang> {
+    let extension = Path::new(file_rel_path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    Some(match extension.as_str() {
+        "rs" => Lang::Rust,
+        "go" => Lang::Go,
+        "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" => Lang::Js,
+        "java" | "cs" | "kt" | "swift" | "dart" | "c" | "h" | "cpp" | "hpp" | "cc" => Lang::CLike,
+        "php" => Lang::Php,
+        "py" => Lang::Python,
+        "gd" => Lang::GdScript,
+        "rb" => Lang::Ruby,
+        _ => return None,
+    })
+}
+
+// Một mẫu nhận diện khai báo: nhóm bắt thứ nhất là tên ký hiệu.
+// `container` = khối đi sau khai báo chứa các khai báo con (class, struct, trait...).
+struct Pattern {
+    regex: Regex,
+    kind: SymbolKind,
+    container: bool,
+}
+
+fn pattern(regex: &str, kind: SymbolKind, container: bool) -> Pattern {
+    Pattern { regex: Regex::new(regex).unwrap(), kind, container }
+}
+
+const JVM_MODIFIERS: &str = r"(?:(?:public|private|protected|internal|static|final|abstract|sealed|open|data|partial|readonly|override|virtual|async|inline|extern|export|fileprivate|unsafe|const|mutating|suspend|operator|external|factory)\s+)*";
+
+lazy_static! {
+    static ref RUST_PATTERNS: Vec<Pattern> = vec![
+        pattern(r#"^(?:pub(?:\([^)]*\))?\s+)?(?:(?:const|async|unsafe|extern(?:\s+"[^"]*")?)\s+)*fn\s+([A-Za-z_]\w*)"#, SymbolKind::Function, false),
+        pattern(r"^(?:pub(?:\([^)]*\))?\s+)?(?:struct|enum|trait|union)\s+([A-Za-z_]\w*)", SymbolKind::Type, true),
+        pattern(r"^(?:pub(?:\([^)]*\))?\s+)?type\s+([A-Za-z_]\w*)", SymbolKind::Type, false),
+        pattern(r"^(?:pub(?:\([^)]*\))?\s+)?(?:const|static)\s+(?:mut\s+)?([A-Za-z_]\w*)\s*:", SymbolKind::Const, false),
+    ];
+    // `impl<T> Trait for Type` và `impl Type`: không phải ký hiệu, chỉ là phạm vi của phương thức
+    static ref RUST_IMPL_FOR: Regex = Regex::new(r"^(?:unsafe\s+)?impl\b.*?\bfor\s+&?(?:\w+::)*(\w+)").unwrap();
+    static ref RUST_IMPL: Regex = Regex::new(r"^(?:unsafe\s+)?impl(?:\s*<.*?>)?\s+&?(?:\w+::)*(\w+)").unwrap();
+    static ref GO_METHOD: Regex = Regex::new(r"^func\s*\(\s*\w*\s*\*?\s*([A-Za-z_]\w*)(?:\[[^\]]*\])?\s*\)\s*([A-Za-z_]\w*)").unwrap();
+    static ref GO_PATTERNS: Vec<Pattern> = vec![
+        pattern(r"^func\s+([A-Za-z_]\w*)", SymbolKind::Function, false),
+        pattern(r"^type\s+([A-Za-z_]\w*)", SymbolKind::Type, false),
+        pattern(r"^const\s+([A-Za-z_]\w*)", SymbolKind::Const, false),
+    ];
+    static ref JS_PATTERNS: Vec<Pattern> = vec![
+        pattern(r"^(?:export\s+)?(?:default\s+)?(?:async\s+)?function\s*\*?\s*([A-Za-z_$][\w$]*)", SymbolKind::Function, false),
+        pattern(r"^(?:export\s+)?(?:default\s+)?(?:declare\s+)?(?:abstract\s+)?class\s+([A-Za-z_$][\w$]*)", SymbolKind::Type, true),
+        pattern(r"^(?:export\s+)?(?:declare\s+)?(?:const\s+)?(?:interface|enum|type)\s+([A-Za-z_$][\w$]*)", SymbolKind::Type, false),
+        pattern(r"^(?:export\s+)?(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*(?::[^=]+)?=\s*(?:async\s+)?(?:function\b|\(|[A-Za-z_$][\w$]*\s*=>)", SymbolKind::Function, false),
+        pattern(r"^(?:export\s+)?const\s+([A-Za-z_$][\w$]*)", SymbolKind::Const, false),
+    ];
+    static ref JS_METHOD: Regex = Regex::new(r"^(?:(?:public|private|protected|static|async|readonly|abstract|override|get|set)\s+)*\*?\s*(#?[A-Za-z_$][\w$]*)\s*(?:<[^>]*>)?\s*\(").unwrap();
+    static ref C_LIKE_PATTERNS: Vec<Pattern> = vec![
+        pattern(&format!(r"^{}(?:enum\s+|annotation\s+|value\s+)?(?:class|interface|enum|struct|record|object|protocol|extension|trait|mixin)\s+([A-Za-z_]\w*)", JVM_MODIFIERS), SymbolKind::Type, true),
+        pattern(&format!(r"^{}(?:fun|func)\s+(?:<[^>]*>\s*)?(?:[\w.]+\.)?([A-Za-z_]\w*)", JVM_MODIFIERS), SymbolKind::Function, false),
+        pattern(&format!(r"^{}(?:static\s+final|final\s+static|const|static\s+let)\s+(?:[\w<>\[\],.?]+\s+)?([A-Za-z_]\w*)\s*[=:]", JVM_MODIFIERS), SymbolKind::Const, false),
+        // Kiểu trả về + tên + `(`, không có `=` phía trước (khác với khởi tạo trường)
+        pattern(r"^[\w<>\[\],.?*&\s]+?[\s*&]((?:\w+::)*~?[A-Za-z_]\w*)\s*\(", SymbolKind::Function, false),
+    ];
+    static ref PHP_PATTERNS: Vec<Pattern> = vec![
+        pattern(r"^(?:(?:abstract|final|readonly)\s+)*(?:class|interface|trait|enum)\s+([A-Za-z_]\w*)", SymbolKind::Type, true),
+        pattern(r"^(?:(?:public|private|protected|static|abstract|final)\s+)*function\s+&?([A-Za-z_]\w*)", SymbolKind::Function, false),
+        pattern(r"^(?:(?:public|private|protected|final)\s+)*const\s+([A-Za-z_]\w*)", SymbolKind::Const, false),
+    ];
+    static ref PYTHON_PATTERNS: Vec<Pattern> = vec![
+        pattern(r"^(?:async\s+)?def\s+([A-Za-z_]\w*)", SymbolKind::Function, false),
+        pattern(r"^class\s+([A-Za-z_]\w*)", SymbolKind::Type, true),
+        pattern(r"^([A-Z][A-Z0-9_]*)\s*(?::[^=]+)?=[^=]", SymbolKind::Const, false),
+    ];
+    static ref GDSCRIPT_PATTERNS: Vec<Pattern> = vec![
+        pattern(r"^(?:static\s+)?func\s+([A-Za-z_]\w*)", SymbolKind::Function, false),
+        pattern(r"^class_name\s+([A-Za-z_]\w*)", SymbolKind::Type, false),
+        pattern(r"^class\s+([A-Za-z_]\w*)", SymbolKind::Type, true),
+        pattern(r"^const\s+([A-Za-z_]\w*)", SymbolKind::Const, false),
+        pattern(r"^signal\s+([A-Za-z_]\w*)", SymbolKind::Function, false),
+    ];
+    static ref RUBY_PATTERNS: Vec<Pattern> = vec![
+        pattern(r"^def\s+(?:self\.)?([A-Za-z_]\w*[?!=]?)", SymbolKind::Function, false),
+        pattern(r"^(?:class|module)\s+(?:\w+::)*([A-Z]\w*)", SymbolKind::Type, true),
+        pattern(r"^([A-Z][A-Z0-9_]*)\s*=[^=]", SymbolKind::Const, false),
+    ];
+}
+
+// Từ khóa có thể đứng trước `(` nhưng không phải tên hàm
+const CALL_LIKE_KEYWORDS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "new", "throw", "else", "do", "await",
+    "typeof", "sizeof", "using", "lock", "foreach", "super", "this", "yield", "delete", "case",
+    "when", "elif", "with", "assert", "synchronized", "defined",
+];
+
+// Một khai báo tìm được trên một dòng (trước khi gắn kiểu chứa)
+struct Declaration {
+    name: String,
+    kind: SymbolKind,
+    // Tên phạm vi được mở bởi khối của khai báo này (class, impl, namespace...)
+    opens_scope: Option<Option<String>>,
+    parent: Option<String>,
+}
+
+fn match_patterns(patterns: &[Pattern], line: &str) -> Option<Declaration> {
+    patterns.iter().find_map(|p| {
+        let name = p.regex.captures(line)?.get(1)?.as_str().to_string();
+        Some(Declaration {
+            opens_scope: p.container.then(|| Some(name.clone())),
+            name,
+            kind: p.kind,
+            parent: None,
+        })
+    })
+}
+
+// Khối chỉ là phạm vi, không phải ký hiệu: namespace, mod, extern "C"...
+fn opens_plain_scope(lang: Lang, line: &str) -> bool {
+    let first = line.split(|c: char| !c.is_alphanumeric() && c != '_').next().unwrap_or("");
+    match lang {
+        Lang::Rust => line.starts_with("mod ") || line.starts_with("pub mod ") || line.starts_with("extern "),
+        Lang::Js => matches!(first, "namespace" | "module") || line.starts_with("declare "),
+        Lang::CLike | Lang::Php => first == "namespace" || line.starts_with("extern \"C\""),
+        _ => false,
+    }
+}
+
+fn declaration_on_line(lang: Lang, line: &str, in_type_scope: bool) -> Option<Declaration> {
+    match lang {
+        Lang::Rust => {
+            if let Some(captures) = RUST_IMPL_FOR.captures(line).or_else(|| RUST_IMPL.captures(line)) {
+                let target = captures[1].to_string();
+                return Some(Declaration {
+                    name: String::new(),
+                    kind: SymbolKind::Type,
+                    opens_scope: Some(Some(target)),
+                    parent: None,
+                });
+            }
+            match_patterns(&RUST_PATTERNS, line)
+        }
+        Lang::Go => {
+            if let Some(captures) = GO_METHOD.captures(line) {
+                return Some(Declaration {
+                    name: captures[2].to_string(),
+                    kind: SymbolKind::Method,
+                    opens_scope: None,
+                    parent: Some(captures[1].to_string()),
+                });
+            }
+            match_patterns(&GO_PATTERNS, line)
+        }
+        Lang::Js => {
+            let declaration = match_patterns(&JS_PATTERNS, line);
+            if declaration.is_none() && in_type_scope {
+                let name = JS_METHOD.captures(line)?.get(1)?.as_str();
+                if CALL_LIKE_KEYWORDS.contains(&name) {
+                    return None;
+                }
+                return Some(Declaration {
+                    name: name.to_string(),
+                    kind: SymbolKind::Method,
+                    opens_scope: None,
+                    parent: None,
+                });
+            }
+            declaration
+        }
+        Lang::CLike => {
+            // Bỏ qua chỉ thị tiền xử lý, annotation/attribute và lời gọi hàm đứng riêng một dòng
+            if line.starts_with(['#', '@', '[']) || line.starts_with("return ") {
+                return None;
+            }
+            match_patterns(&C_LIKE_PATTERNS, line)
+                .filter(|declaration| !CALL_LIKE_KEYWORDS.contains(&declaration.name.as_str()))
+        }
+        Lang::Php => match_patterns(&PHP_PATTERNS, line),
+        Lang::Python => match_patterns(&PYTHON_PATTERNS, line),
+        Lang::GdScript => match_patterns(&GDSCRIPT_PATTERNS, line),
+        Lang::Ruby => match_patterns(&RUBY_PATTERNS, line),
+    }
+}
+
+// Phạm vi chứa khai báo đang mở. `name` = None với namespace/mod (không đổi kiểu chứa).
+struct Scope {
+    // Độ sâu ngoặc nhọn bên trong khối, hoặc mức thụt lề của thân khối (Python, Ruby)
+    level: usize,
+    name: Option<String>,
+}
+
+fn parent_of(scopes: &[Scope]) -> Option<String> {
+    scopes.iter().rev().find_map(|scope| scope.name.clone())
+}
+
+fn push_symbol(symbols: &mut Vec<Symbol>, declaration: Declaration, line: usize, scopes: &[Scope]) {
+    if declaration.name.is_empty() {
+        return;
+    }
+    let parent = declaration.parent.or_else(|| parent_of(scopes));
+    let kind = match declaration.kind {
+        SymbolKind::Function if parent.is_some() => SymbolKind::Method,
+        kind => kind,
+    };
+    symbols.push(Symbol { name: declaration.name, kind, line, parent });
+}
+
+/// Trích xuất các ký hiệu khai báo của một file. Trả về danh sách rỗng nếu ngôn ngữ chưa được hỗ trợ.
+pub fn extract_symbols(content: &str, file_rel_path: &str) -> Vec<Symbol> {
+    let Some(lang) = lang_for_path(file_rel_path) else {
+        return Vec::new();
+    };
+    let Some(mask) = code_mask(content, file_rel_path) else {
+        return Vec::new();
+    };
+    match lang {
+        Lang::Python | Lang::GdScript | Lang::Ruby => extract_indented(content, &mask, lang),
+        _ => extract_braced(content, &mask, lang),
+    }
+}
+
+// Các dòng không trống kèm số dòng, vị trí byte đầu dòng và cờ "dòng bắt đầu bằng code"
+// (không nằm trong chuỗi/comment)
+fn code_lines<'a>(content: &'a str, mask: &'a [bool]) -> impl Iterator<Item = (usize, usize, &'a str, bool)> + 'a {
+    let mut start = 0;
+    content.split('\n').enumerate().filter_map(move |(index, line)| {
+        let line_start = start;
+        start += line.len() + 1;
+        let indent = line.len() - line.trim_start().len();
+        (indent < line.len()).then(|| (index + 1, line_start, line, mask[line_start + indent]))
+    })
+}
+
+fn extract_braced(content: &str, mask: &[bool], lang: Lang) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    let mut scopes: Vec<Scope> = Vec::new();
+    let mut depth = 0usize;
+    // Phạm vi sẽ được mở ở dấu `{` kế tiếp
+    let mut pending_scope: Option<Option<String>> = None;
+
+    for (line_number, line_start, line, starts_in_code) in code_lines(content, mask) {
+        let trimmed = line.trim();
+        // Chỉ nhận khai báo ở cấp cao nhất hoặc ngay trong thân của class/impl/namespace
+        let in_declaration_scope =
+            starts_in_code && (depth == 0 || scopes.last().is_some_and(|s| s.level == depth));
+        if in_declaration_scope {
+            let in_type_scope = depth > 0 && scopes.last().is_some_and(|s| s.name.is_some());
+            if opens_plain_scope(lang, trimmed) {
+                pending_scope = Some(None);
+            } else if let Some(mut declaration) = declaration_on_line(lang, trimmed, in_type_scope) {
+                pending_scope = declaration.opens_scope.take();
+                push_symbol(&mut symbols, declaration, line_number, &scopes);
+            }
+        }
+
+        for (offset, b) in line.bytes().enumerate() {
+            if !mask[line_start + offset] {
+                continue;
+            }
+            match b {
+                b'{' => {
+                    depth += 1;
+                    if let Some(name) = pending_scope.take() {
+                        scopes.push(Scope { level: depth, name });
+                    }
+                }
+                b'}' => {
+                    depth = depth.saturating_sub(1);
+                    while scopes.last().is_some_and(|s| s.level > depth) {
+                        scopes.pop();
+                    }
+                }
+                // Khai báo không có thân: `struct Unit;`, `class Forward;`
+                b';' => pending_scope = None,
+                _ => {}
+            }
+        }
+    }
+    symbols
+}
+
+fn extract_indented(content: &str, mask: &[bool], lang: Lang) -> Vec<Symbol> {
+    let mut symbols = Vec::new();
+    // level = mức thụt lề của thân class (usize::MAX khi chưa gặp dòng nào trong thân)
+    let mut scopes: Vec<Scope> = Vec::new();
+    // Mức thụt lề của dòng khai báo class tương ứng với từng phạm vi
+    let mut scope_indents: Vec<usize> = Vec::new();
+
+    for (line_number, line_start, line, starts_in_code) in code_lines(content, mask) {
+        // Dòng tiếp nối của một biểu thức/chuỗi nhiều dòng
+        if !starts_in_code || (line_start > 0 && !mask[line_start - 1]) {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        while scope_indents.last().is_some_and(|&i| indent <= i) {
+            scope_indents.pop();
+            scopes.pop();
+        }
+        if let Some(scope) = scopes.last_mut() {
+            if scope.level == usize::MAX {
+                scope.level = indent;
+            }
+        }
+        let in_declaration_scope = if indent == 0 { scopes.is_empty() } else { scopes.last().is_some_and(|s| s.level == indent) };
+        if !in_declaration_scope {
+            continue;
+        }
+        let trimmed = line.trim();
+        if let Some(mut declaration) = declaration_on_line(lang, trimmed, !scopes.is_empty()) {
+            // Hằng số chỉ tính ở cấp cao nhất
+            if declaration.kind == SymbolKind::Const && indent > 0 && lang != Lang::GdScript {
+                continue;
+            }
+            let opens_scope = declaration.opens_scope.take();
+            push_symbol(&mut symbols, declaration, line_number, &scopes);
+            if let Some(name) = opens_scope {
+                scopes.push(Scope { level: usize::MAX, name });
+                scope_indents.push(indent);
+            }
+        }
+    }
+    symbols
+}
+
+/// Làm mới chỉ mục ký hiệu: dùng lại kết quả cũ của file có hash nội dung không đổi
+/// (hoặc size/mtime nếu file chưa có hash),
+/// trích xuất lại file đã thay đổi và bỏ các file không còn trong dự án.
+pub fn refresh_index(
+    root_path: &Path,
+    metadata_cache: &BTreeMap<String, FileMetadata>,
+    previous: Option<&BTreeMap<String, FileSymbols>>,
+) -> BTreeMap<String, FileSymbols> {
+    let mut index = BTreeMap::new();
+    for (path, metadata) in metadata_cache {
+        if lang_for_path(path).is_none() {
+            continue;
+        }
+        let cached = previous
+            .and_then(|previous| previous.get(path))
+            .filter(|cached| match &metadata.content_hash {
+                // Sửa trong cùng một giây mà không đổi size vẫn được phát hiện qua hash
+                Some(hash) => cached.content_hash.as_ref() == Some(hash),
+                None => cached.size == metadata.size && cached.mtime == metadata.mtime,
+            });
+        let entry = match cached {
+            Some(cached) => cached.clone(),
+            None => {
+                let Ok(content) = fs::read_to_string(root_path.join(path)) else {
+                    continue;
+                };
+                FileSymbols {
+                    size: metadata.size,
+                    mtime: metadata.mtime,
+                    content_hash: metadata.content_hash.clone(),
+                    symbols: extract_symbols(&content, path),
+                }
+            }
+        };
+        index.insert(path.clone(), entry);
+    }
+    index
+}
+
+fn kind_label(kind: SymbolKind) -> &'static str {
+    match kind {
+        SymbolKind::Function => "fn",
+        SymbolKind::Method => "method",
+        SymbolKind::Type => "type",
+        SymbolKind::Const => "const",
+    }
+}
+
+/// Trình bày chỉ mục thành bản đồ dự án gọn nhẹ: mỗi file một dòng tiêu đề,
+/// mỗi ký hiệu một dòng `số_dòng: loại tên`. Các file trong `skip_paths` được bỏ qua.
+pub fn render_repo_map(index: &BTreeMap<String, FileSymbols>, skip_paths: &HashSet<&str>) -> String {
+    let mut output = String::new();
+    for (path, file) in index {
+        if file.symbols.is_empty() || skip_paths.contains(path.as_str()) {
+            continue;
+        }
+        let _ = writeln!(output, "{}", path);
+        for symbol in &file.symbols {
+            let _ = match &symbol.parent {
+                Some(parent) => writeln!(output, "  {}: {} {}.{}", symbol.line, kind_label(symbol.kind), parent, symbol.name),
+                None => writeln!(output, "  {}: {} {}", symbol.line, kind_label(symbol.kind), symbol.name),
+            };
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(content: &str, path: &str) -> Vec<String> {
+        extract_symbols(content, path)
+            .into_iter()
+            .map(|s| match s.parent {
+                Some(parent) => format!("{} {} {}.{}", s.line, kind_label(s.kind), parent, s.name),
+                None => format!("{} {} {}", s.line, kind_label(s.kind), s.name),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn extracts_declarations_with_lines_and_parents() {
+        let rust = "use std::fmt;\n\npub const MAX: usize = 3;\n\n/// fn not_a_symbol()\npub struct Point {\n    x: i32,\n}\n\nimpl<T: Into<i32>> From<T> for Point {\n    fn from(value: T) -> Self {\n        fn helper() {}\n        let s = \"fn fake() {\";\n        Point { x: value.into() }\n    }\n}\n\nmod inner {\n    pub(crate) async fn run() {}\n}\n\ntrait Shape {\n    fn area(&self) -> f64;\n}\nfn route(name: &str) -> u8 {\n    match name {\n        \"a\" => {\n            1\n        }\n        _ => 0,\n    }\n}\nimpl Point {\n    pub fn new() -> Self { Point { x: 0 } }\n}\n";
+        assert_eq!(
+            summary(rust, "src/lib.rs"),
+            vec![
+                "3 const MAX",
+                "6 type Point",
+                "11 method Point.from",
+                "19 fn run",
+                "22 type Shape",
+                "23 method Shape.area",
+                "25 fn route",
+                "34 method Point.new",
+            ]
+        );
+
+        let ts = "import { a } from \"./a\";\nexport interface Props { id: number }\nexport const LIMIT = 10;\nexport const useThing = (x: number) => {\n  if (x) {\n    helper(x);\n  }\n};\nexport default class Store extends Base {\n  private items = [];\n  constructor(p: Props) {\n    super(p);\n  }\n  async load(id: string): Promise<void> {\n    for (const i of this.items) {}\n  }\n}\nfunction helper(x) {}\n";
+        assert_eq!(
+            summary(ts, "store.ts"),
+            vec![
+                "2 type Props",
+                "3 const LIMIT",
+                "4 fn useThing",
+                "9 type Store",
+                "11 method Store.constructor",
+                "14 method Store.load",
+                "18 fn helper",
+            ]
+        );
+
+        let python = "import os\n\nDEFAULT_NAME = \"x\"\n\nclass Greeter(Base):\n    \"\"\"\n    def not_a_method(self):\n    \"\"\"\n    def greet(self):\n        def inner():\n            pass\n        return 1\n\nasync def main():\n    pass\n";
+        assert_eq!(
+            summary(python, "app.py"),
+            vec!["3 const DEFAULT_NAME", "5 type Greeter", "9 method Greeter.greet", "14 fn main"]
+        );
+
+        let go = "package main\n\ntype Server struct {\n\tport int\n}\n\nfunc (s *Server) Start() error {\n\treturn nil\n}\n\nfunc main() {}\n";
+        assert_eq!(
+            summary(go, "main.go"),
+            vec!["3 type Server", "7 method Server.Start", "11 fn main"]
+        );
+
+        let java = "package a;\n\n@Service\npublic class UserService implements Api {\n    private static final int LIMIT = 5;\n    private final Repo repo = new Repo();\n\n    public UserService(Repo repo) {\n        this.repo = repo;\n    }\n\n    @Override\n    public List<User> findAll(int page) {\n        if (page > 0) {\n            return repo.all();\n        }\n        return List.of();\n    }\n}\n";
+        assert_eq!(
+            summary(java, "UserService.java"),
+            vec![
+                "4 type UserService",
+                "5 const UserService.LIMIT",
+                "8 method UserService.UserService",
+                "13 method UserService.findAll",
+            ]
+        );
+    }
+
+    #[test]
+    fn refresh_reuses_unchanged_entries_and_renders_map() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.rs"), "fn a() {}\n").unwrap();
+        fs::write(dir.path().join("b.py"), "def b():\n    pass\n").unwrap();
+        let metadata = |size| FileMetadata { size, mtime: 1, ..Default::default() };
+        let mut cache = BTreeMap::from([
+            ("a.rs".to_string(), metadata(10)),
+            ("b.py".to_string(), metadata(18)),
+            ("notes.txt".to_string(), metadata(5)),
+        ]);
+        let index = refresh_index(dir.path(), &cache, None);
+        assert_eq!(index.keys().collect::<Vec<_>>(), vec!["a.rs", "b.py"]);
+
+        // Nội dung đổi nhưng size/mtime giữ nguyên: dùng lại kết quả cũ
+        fs::write(dir.path().join("a.rs"), "fn z() {}\n").unwrap();
+        cache.remove("b.py");
+        let index = refresh_index(dir.path(), &cache, Some(&index));
+        assert_eq!(index["a.rs"].symbols[0].name, "a");
+        assert!(!index.contains_key("b.py"));
+
+        cache.get_mut("a.rs").unwrap().mtime = 2;
+        let index = refresh_index(dir.path(), &cache, Some(&index));
+        assert_eq!(render_repo_map(&index, &HashSet::new()), "a.rs\n  1: fn z\n");
+
+        // Có hash nội dung: size/mtime giữ nguyên nhưng hash đổi thì trích xuất lại
+        cache.get_mut("a.rs").unwrap().content_hash = Some("h1".to_string());
+        let index = refresh_index(dir.path(), &cache, Some(&index));
+        fs::write(dir.path().join("a.rs"), "fn y() {}\n").unwrap();
+        cache.get_mut("a.rs").unwrap().content_hash = Some("h2".to_string());
+        let index = refresh_index(dir.path(), &cache, Some(&index));
+        assert_eq!(index["a.rs"].symbols[0].name, "y");
+        assert_eq!(render_repo_map(&index, &HashSet::from(["a.rs"])), "");
+    }
+}
@@ -0,0 +1,81 @@
+// src-tauri/src/tokenizer.rs
+// Bộ đếm token theo mô hình đích. Tokenizer được chọn trong AppSettings; phần quét dự án
+// nhận tokenizer qua `ScanOptions`, phần tạo ngữ cảnh qua `ExportOptions`.
+use crate::models::Tokenizer;
+use tiktoken_rs::{cl100k_base_singleton, o200k_base_singleton, p50k_base_singleton};
+
+// Số ký tự chữ/số trung bình của một token với các tokenizer BPE phổ biến
+// (đã đối chiếu với cl100k trên mã nguồn và văn bản tiếng Anh)
+const APPROX_CHARS_PER_TOKEN: usize = 4;
+
+/// Đếm số token của `text` theo `tokenizer`.
+pub fn count(tokenizer: Tokenizer, text: &str) -> usize {
+    match tokenizer {
+        Tokenizer::Cl100k => cl100k_base_singleton().encode_with_special_tokens(text).len(),
+        Tokenizer::O200k => o200k_base_singleton().encode_with_special_tokens(text).len(),
+        Tokenizer::P50k => p50k_base_singleton().encode_with_special_tokens(text).len(),
+        Tokenizer::Approximate => approximate_count(text),
+    }
+}
+
+// Ước lượng cho các mô hình không dùng tokenizer của OpenAI: mỗi cụm chữ/số tính
+// ceil(độ dài / 4) token, mỗi ký tự dấu câu một token, mỗi cụm khoảng trắng có xuống dòng
+// một token (khoảng trắng thường được gộp vào từ phía sau).
+fn approximate_count(text: &str) -> usize {
+    let mut total = 0;
+    let mut word_len: usize = 0;
+    let mut in_newline_run = false;
+    for c in text.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            word_len += 1;
+            in_newline_run = false;
+            continue;
+        }
+        total += word_len.div_ceil(APPROX_CHARS_PER_TOKEN);
+        word_len = 0;
+        if c.is_whitespace() {
+            if c == '\n' && !in_newline_run {
+                total += 1;
+                in_newline_run = true;
+            }
+        } else {
+            total += 1;
+            in_newline_run = false;
+        }
+    }
+    total + word_len.div_ceil(APPROX_CHARS_PER_TOKEN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"// Tính tổng các phần tử
+pub fn sum_values(values: &[i64]) -> i64 {
+    let mut total = 0;
+    for value in values {
+        total += value;
+    }
+    total
+}
+
+fn main() {
+    let numbers = vec![1, 2, 3, 4, 5];
+    println!("The total is {}", sum_values(&numbers));
+}
+"#;
+
+    #[test]
+    fn tokenizers_count_differently_and_approximation_stays_close() {
+        let cl100k = count(Tokenizer::Cl100k, SAMPLE);
+        let p50k = count(Tokenizer::P50k, SAMPLE);
+        let approximate = count(Tokenizer::Approximate, SAMPLE);
+
+        // p50k không gộp khoảng trắng thụt lề nên luôn tốn nhiều token hơn với mã nguồn
+        assert!(p50k > cl100k);
+        assert!(count(Tokenizer::O200k, SAMPLE) > 0);
+        let error = approximate.abs_diff(cl100k) as f64 / cl100k as f64;
+        assert!(error < 0.25, "approximate={} cl100k={}", approximate, cl100k);
+        assert_eq!(count(Tokenizer::Approximate, ""), 0);
+    }
+}
@@ -1,6 +1,7 @@
 // src/App.tsx
 import { useEffect, useMemo, useRef } from "react";
 import { listen } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { useTranslation } from "react-i18next";
 import {
   Menu,
@@ -23,7 +24,9 @@ import {
   type GroupStats,
   type AppSettings,
   type ScanCompletePayload,
+  type ProjectFilesChangedPayload,
   type AIModel,
+  type RedactionReport,
 } from "./store/types";
 import { useShallow } from "zustand/react/shallow"; // <-- THÊM IMPORT NÀY
 import { WelcomeScene } from "./scenes/WelcomeScene";
@@ -79,11 +82,13 @@ function App() {
     _setScanComplete,
     _setScanError,
     _setGroupUpdateComplete,
+    _applyProjectDiff,
     rescanProject,
     openFolderFromMenu,
     showSettingsScene,
     exportProject,
     copyProjectToClipboard,
+    copyRepoMapToClipboard,
     toggleProjectPanelVisibility,
     toggleGitPanelVisibility,
     toggleEditorPanelVisibility,
@@ -223,6 +228,7 @@ function App() {
         // Dùng set thay vì updateAppSettings để không ghi lại file
         useAppStore.setState({
           nonAnalyzableExtensions: settings.nonAnalyzableExtensions ?? [],
+          tokenizer: settings.tokenizer ?? "cl100k",
           openRouterApiKey: settings.openRouterApiKey ?? "",
           googleApiKey: settings.googleApiKey ?? "",
           allAvailableModels,
@@ -292,6 +298,12 @@ function App() {
           action: copyProjectToClipboard,
         });
 
+        const copyRepoMapItem = await MenuItem.new({
+          id: "copy_repo_map",
+          text: t("appMenu.file.copyRepoMap"),
+          action: copyRepoMapToClipboard,
+        });
+
         const closeProjectItem = await MenuItem.new({
           id: "close_project",
           text: t("appMenu.file.closeProject"),
@@ -305,6 +317,7 @@ function App() {
             rescanFolderItem,
             exportProjectItem,
             copyProjectItem,
+            copyRepoMapItem,
             await PredefinedMenuItem.new({ item: "Separator" }),
             closeProjectItem,
           ],
@@ -397,6 +410,7 @@ function App() {
     rescanProject,
     exportProject,
     copyProjectToClipboard,
+    copyRepoMapToClipboard,
     toggleProjectPanelVisibility,
     toggleGitPanelVisibility,
     toggleEditorPanelVisibility,
@@ -452,19 +466,21 @@ function App() {
   // --- LẮNG NGHE SỰ KIỆN TỪ RUST ---
   useEffect(() => {
     const unlistenFuncs: Promise<() => void>[] = [];
+    // Sự kiện quét và watcher được gửi riêng cho từng cửa sổ dự án
+    const currentWindow = getCurrentWebviewWindow();
 
     unlistenFuncs.push(
-      listen<string>("scan_progress", (event) => {
+      currentWindow.listen<string>("scan_progress", (event) => {
         throttledSetScanProgress(event.payload);
       })
     );
     unlistenFuncs.push(
-      listen<string>("analysis_progress", (event) => {
+      currentWindow.listen<string>("analysis_progress", (event) => {
         throttledSetAnalysisProgress(event.payload);
       })
     );
     unlistenFuncs.push(
-      listen<ScanCompletePayload>("scan_complete", async (event) => {
+      currentWindow.listen<ScanCompletePayload>("scan_complete", async (event) => {
         const { projectData, isFirstScan } = event.payload;
         _setScanComplete(projectData);
 
@@ -484,7 +500,7 @@ function App() {
       })
     );
     unlistenFuncs.push(
-      listen<string>("scan_error", async (event) => {
+      currentWindow.listen<string>("scan_error", async (event) => {
         _setScanError(event.payload);
         const errorKey = `errors.${event.payload}`;
         const translatedError = t(errorKey);
@@ -517,36 +533,57 @@ function App() {
       })
     );
     unlistenFuncs.push(
-      listen<void>("file_change_detected", () => {
+      currentWindow.listen<void>("file_change_detected", () => {
         if (!useAppStore.getState().isScanning) {
           rescanProject();
         }
       })
     );
+    unlistenFuncs.push(
+      currentWindow.listen<ProjectFilesChangedPayload>(
+        "project_files_changed",
+        (event) => {
+          if (!useAppStore.getState().isScanning) {
+            _applyProjectDiff(event.payload);
+          }
+        }
+      )
+    );
     // Listener cho sự kiện xuất dự án (để hiển thị toast)
     unlistenFuncs.push(
-      listen<string>("project_export_complete", async (event) => {
-        try {
-          const filePath = await save({
-            title: t("dialogs.saveProjectContext.title"),
-            defaultPath: "project_context.txt",
-            filters: [{ name: t("dialogs.filters.text"), extensions: ["txt"] }],
-          });
-          if (filePath) {
-            await writeTextFile(filePath, event.payload);
-            await message(t("dialogs.saveSuccess.body"), {
-              title: t("common.success"),
-              kind: "info",
+      listen<{ context: string; redactions: RedactionReport }>(
+        "project_export_complete",
+        async (event) => {
+          try {
+            const filePath = await save({
+              title: t("dialogs.saveProjectContext.title"),
+              defaultPath: "project_context.txt",
+              filters: [{ name: t("dialogs.filters.text"), extensions: ["txt"] }],
+            });
+            if (filePath) {
+              await writeTextFile(filePath, event.payload.context);
+              const redactedCount = event.payload.redactions.secrets.length;
+              const successBody =
+                redactedCount > 0
+                  ? `${t("dialogs.saveSuccess.body")}\n${t(
+                      "dialogs.saveSuccess.secretsRedacted",
+                      { count: redactedCount }
+                    )}`
+                  : t("dialogs.saveSuccess.body");
+              await message(successBody, {
+                title: t("common.success"),
+                kind: "info",
+              });
+            }
+          } catch (error) {
+            console.error("Lỗi khi lưu file ngữ cảnh dự án:", error);
+            await message(t("errors.fileSaveFailed"), {
+              title: t("common.error"),
+              kind: "error",
             });
           }
-        } catch (error) {
-          console.error("Lỗi khi lưu file ngữ cảnh dự án:", error);
-          await message(t("errors.fileSaveFailed"), {
-            title: t("common.error"),
-            kind: "error",
-          });
         }
-      })
+      )
     );
     unlistenFuncs.push(
       listen<string>("project_export_error", async (event) => {
@@ -578,6 +615,7 @@ function App() {
     throttledSetScanProgress,
     throttledSetAnalysisProgress,
     _setGroupUpdateComplete,
+    _applyProjectDiff,
     rescanProject,
     _setRecentPaths,
     t,
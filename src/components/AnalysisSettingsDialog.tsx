@@ -12,28 +12,42 @@ import {
 import { Button } from "@/components/ui/button";
 import { Label } from "@/components/ui/label";
 import { Input } from "@/components/ui/input";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { type Tokenizer } from "@/store/types";
+
+const TOKENIZERS: Tokenizer[] = ["cl100k", "o200k", "p50k", "approximate"];
 
 interface AnalysisSettingsDialogProps {
   isOpen: boolean;
   onClose: () => void;
   initialExtensions: string[];
-  onSave: (extensions: string[]) => void;
+  initialTokenizer: Tokenizer;
+  onSave: (extensions: string[], tokenizer: Tokenizer) => void;
 }
 
 export function AnalysisSettingsDialog({
   isOpen,
   onClose,
   initialExtensions,
+  initialTokenizer,
   onSave,
 }: AnalysisSettingsDialogProps) {
   const { t } = useTranslation();
   const [extensionsText, setExtensionsText] = useState("");
+  const [tokenizer, setTokenizer] = useState<Tokenizer>(initialTokenizer);
 
   useEffect(() => {
     if (isOpen) {
       setExtensionsText(initialExtensions.join(", "));
+      setTokenizer(initialTokenizer);
     }
-  }, [isOpen, initialExtensions]);
+  }, [isOpen, initialExtensions, initialTokenizer]);
 
   const handleSave = () => {
     const extensions = extensionsText
@@ -41,7 +55,7 @@ export function AnalysisSettingsDialog({
       .map((s) => s.trim().toLowerCase())
       .filter(Boolean); // Lọc bỏ các chuỗi rỗng
     // Sử dụng Set để loại bỏ các extension trùng lặp
-    onSave([...new Set(extensions)]);
+    onSave([...new Set(extensions)], tokenizer);
     onClose();
   };
 
@@ -69,6 +83,29 @@ export function AnalysisSettingsDialog({
               {t("analysisSettings.extensionsHint")}
             </p>
           </div>
+          <div className="grid w-full gap-1.5">
+            <Label htmlFor="tokenizer-select">
+              {t("analysisSettings.tokenizer.label")}
+            </Label>
+            <Select
+              value={tokenizer}
+              onValueChange={(value) => setTokenizer(value as Tokenizer)}
+            >
+              <SelectTrigger id="tokenizer-select">
+                <SelectValue />
+              </SelectTrigger>
+              <SelectContent>
+                {TOKENIZERS.map((option) => (
+                  <SelectItem key={option} value={option}>
+                    {t(`analysisSettings.tokenizer.options.${option}`)}
+                  </SelectItem>
+                ))}
+              </SelectContent>
+            </Select>
+            <p className="text-sm text-muted-foreground">
+              {t("analysisSettings.tokenizer.hint")}
+            </p>
+          </div>
         </div>
         <DialogFooter>
           <Button type="button" variant="outline" onClick={onClose}>
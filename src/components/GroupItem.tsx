@@ -14,11 +14,17 @@ import {
   ClipboardCopy,
   BrainCircuit,
   Tag,
+  FileCode,
+  ListTree,
+  GitFork,
+  GitMerge,
+  Ruler,
 } from "lucide-react";
 import {
   DropdownMenu,
   DropdownMenuContent,
   DropdownMenuItem,
+  DropdownMenuCheckboxItem,
   DropdownMenuSeparator,
   DropdownMenuTrigger,
   DropdownMenuSub,
@@ -38,11 +44,13 @@ import {
 } from "@/components/ui/alert-dialog";
 import { InlineGroupInput } from "./InlineEditingInputs";
 import { TokenLimitEditor } from "./TokenLimitEditor";
+import { TemplatePathEditor } from "./TemplatePathEditor";
 
 interface GroupItemProps {
   group: Group;
   isLoading: boolean;
   isEditing: boolean;
+  isOutline: boolean;
   onEditContent: (group: Group) => void;
   onStartRename: (group: Group) => void;
   onConfirmRename: (newName: string) => void;
@@ -50,7 +58,12 @@ interface GroupItemProps {
   onCopyContext: (group: Group) => void;
   onAttachToAi: (group: Group) => void;
   onExport: (group: Group) => void;
+  onMeasureContext: (group: Group) => void;
   onSaveTokenLimit: (group: Group, limit?: number) => void;
+  onSaveTemplatePath: (group: Group, templatePath?: string) => void;
+  onToggleOutline: (group: Group, enabled: boolean) => void;
+  onExpandDependencies: (group: Group, depth: number | null) => void;
+  onAddDependents: (group: Group, depth: number | null) => void;
   onDelete: (group: Group) => void;
 }
 
@@ -58,6 +71,7 @@ export function GroupItem({
   group,
   isLoading,
   isEditing,
+  isOutline,
   onEditContent,
   onStartRename,
   onConfirmRename,
@@ -65,7 +79,12 @@ export function GroupItem({
   onCopyContext,
   onAttachToAi,
   onExport,
+  onMeasureContext,
   onSaveTokenLimit,
+  onSaveTemplatePath,
+  onToggleOutline,
+  onExpandDependencies,
+  onAddDependents,
   onDelete,
 }: GroupItemProps) {
   const { t } = useTranslation();
@@ -139,6 +158,63 @@ export function GroupItem({
               />
             </DropdownMenuSubContent>
           </DropdownMenuSub>
+          <DropdownMenuSub>
+            <DropdownMenuSubTrigger>
+              <FileCode className="mr-2 h-4 w-4" />
+              <span>{t("groupItem.menu.editTemplate")}</span>
+            </DropdownMenuSubTrigger>
+            <DropdownMenuSubContent className="p-0">
+              <TemplatePathEditor
+                group={group}
+                onSave={(templatePath?: string) =>
+                  onSaveTemplatePath(group, templatePath)
+                }
+              />
+            </DropdownMenuSubContent>
+          </DropdownMenuSub>
+          <DropdownMenuCheckboxItem
+            checked={isOutline}
+            onCheckedChange={(checked) => onToggleOutline(group, checked)}
+          >
+            <ListTree className="mr-2 h-4 w-4" />
+            <span>{t("groupItem.menu.outlineMode")}</span>
+          </DropdownMenuCheckboxItem>
+          <DropdownMenuSub>
+            <DropdownMenuSubTrigger>
+              <GitFork className="mr-2 h-4 w-4" />
+              <span>{t("groupItem.menu.addDependencies")}</span>
+            </DropdownMenuSubTrigger>
+            <DropdownMenuSubContent>
+              <DropdownMenuItem onClick={() => onExpandDependencies(group, 1)}>
+                {t("groupItem.menu.dependencyDepth", { count: 1 })}
+              </DropdownMenuItem>
+              <DropdownMenuItem onClick={() => onExpandDependencies(group, 2)}>
+                {t("groupItem.menu.dependencyDepth", { count: 2 })}
+              </DropdownMenuItem>
+              <DropdownMenuItem
+                onClick={() => onExpandDependencies(group, null)}
+              >
+                {t("groupItem.menu.dependencyAll")}
+              </DropdownMenuItem>
+            </DropdownMenuSubContent>
+          </DropdownMenuSub>
+          <DropdownMenuSub>
+            <DropdownMenuSubTrigger>
+              <GitMerge className="mr-2 h-4 w-4" />
+              <span>{t("groupItem.menu.addDependents")}</span>
+            </DropdownMenuSubTrigger>
+            <DropdownMenuSubContent>
+              <DropdownMenuItem onClick={() => onAddDependents(group, 1)}>
+                {t("groupItem.menu.dependencyDepth", { count: 1 })}
+              </DropdownMenuItem>
+              <DropdownMenuItem onClick={() => onAddDependents(group, 2)}>
+                {t("groupItem.menu.dependencyDepth", { count: 2 })}
+              </DropdownMenuItem>
+              <DropdownMenuItem onClick={() => onAddDependents(group, null)}>
+                {t("groupItem.menu.dependencyAll")}
+              </DropdownMenuItem>
+            </DropdownMenuSubContent>
+          </DropdownMenuSub>
           <DropdownMenuSeparator />
           <DropdownMenuItem onClick={() => onCopyContext(group)}>
             <ClipboardCopy className="mr-2 h-4 w-4" />
@@ -148,6 +224,10 @@ export function GroupItem({
             <Download className="mr-2 h-4 w-4" />
             <span>{t("groupItem.menu.exportContext")}</span>
           </DropdownMenuItem>
+          <DropdownMenuItem onClick={() => onMeasureContext(group)}>
+            <Ruler className="mr-2 h-4 w-4" />
+            <span>{t("groupItem.menu.measureContext")}</span>
+          </DropdownMenuItem>
           <DropdownMenuSeparator />
           <DropdownMenuItem onClick={() => onAttachToAi(group)}>
             <Paperclip className="mr-2 h-4 w-4" />
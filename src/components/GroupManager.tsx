@@ -3,7 +3,12 @@ import { useState, useEffect } from "react";
 import { useTranslation } from "react-i18next";
 import { listen } from "@tauri-apps/api/event";
 import { useAppStore, useAppActions } from "@/store/appStore";
-import { type Group } from "@/store/types";
+import {
+  type ContextMeasurement,
+  type Group,
+  type RedactionReport,
+  type TokenBudgetReport,
+} from "@/store/types";
 import { invoke } from "@tauri-apps/api/core";
 import { save, message } from "@tauri-apps/plugin-dialog";
 import { writeTextFile } from "@tauri-apps/plugin-fs";
@@ -34,6 +39,7 @@ export function GroupManager({
     exportWithLineNumbers,
     exportWithoutComments,
     exportRemoveDebugLogs,
+    exportOutline,
   } = useAppStore(
     useShallow((state) => ({
       groups: state.groups,
@@ -42,10 +48,17 @@ export function GroupManager({
       // Lấy thêm các cài đặt export khác
       exportWithoutComments: state.exportWithoutComments,
       exportRemoveDebugLogs: state.exportRemoveDebugLogs,
+      exportOutline: state.exportOutline,
     }))
   );
-  const { deleteGroup, editGroupContent, attachItemToAi, updateGroup } =
-    useAppActions();
+  const {
+    deleteGroup,
+    editGroupContent,
+    attachItemToAi,
+    updateGroup,
+    expandGroupDependencies,
+    addGroupDependents,
+  } = useAppActions();
 
   // ... (state và effects cho việc export/copy giữ nguyên)
   const [exportingGroupId, setExportingGroupId] = useState<string | null>(null);
@@ -53,10 +66,17 @@ export function GroupManager({
   const [pendingExportData, setPendingExportData] = useState<{
     context: string;
     group: Group;
+    budget: TokenBudgetReport | null;
+    redactions: RedactionReport;
   } | null>(null);
 
   useEffect(() => {
-    const unlistenComplete = listen<{ groupId: string; context: string }>(
+    const unlistenComplete = listen<{
+      groupId: string;
+      context: string;
+      budget: TokenBudgetReport | null;
+      redactions: RedactionReport;
+    }>(
       "group_export_complete",
       (event) => {
         const targetGroup = groups.find((g) => g.id === event.payload.groupId);
@@ -64,6 +84,8 @@ export function GroupManager({
           setPendingExportData({
             context: event.payload.context,
             group: targetGroup,
+            budget: event.payload.budget,
+            redactions: event.payload.redactions,
           });
         }
       }
@@ -102,7 +124,26 @@ export function GroupManager({
           });
           if (filePath) {
             await writeTextFile(filePath, pendingExportData.context);
-            await message(t("dialogs.saveSuccess.body"), {
+            const budget = pendingExportData.budget;
+            const redactedCount = pendingExportData.redactions.secrets.length;
+            const bodyLines = [t("dialogs.saveSuccess.body")];
+            if (budget && budget.cutFiles.length > 0) {
+              bodyLines.push(
+                t("dialogs.saveSuccess.budgetApplied", {
+                  count: budget.cutFiles.length,
+                  tokens: budget.finalTokenCount.toLocaleString(),
+                  limit: budget.tokenLimit.toLocaleString(),
+                })
+              );
+            }
+            if (redactedCount > 0) {
+              bodyLines.push(
+                t("dialogs.saveSuccess.secretsRedacted", {
+                  count: redactedCount,
+                })
+              );
+            }
+            await message(bodyLines.join("\n"), {
               title: t("common.success"),
               kind: "info",
             });
@@ -173,6 +214,43 @@ export function GroupManager({
         setCopyingGroupId(null);
       });
   };
+  const handleMeasureContext = (group: Group) => {
+    if (!rootPath) return;
+    invoke<ContextMeasurement>("get_group_context_size", {
+      path: rootPath,
+      groupId: group.id,
+    })
+      .then((measurement) => {
+        // Chỉ liệt kê các file đóng góp nhiều token nhất
+        const largestFiles = [...measurement.files]
+          .sort((a, b) => b.tokens - a.tokens)
+          .slice(0, 10)
+          .map((file) =>
+            t("dialogs.contextSize.file", {
+              path: file.path,
+              tokens: file.tokens.toLocaleString(),
+            })
+          );
+        const body = [
+          t("dialogs.contextSize.total", {
+            tokens: measurement.totalTokens.toLocaleString(),
+            bytes: measurement.totalBytes.toLocaleString(),
+            tokenizer: measurement.tokenizer,
+          }),
+          t("dialogs.contextSize.overhead", {
+            tokens: measurement.overheadTokens.toLocaleString(),
+          }),
+          ...largestFiles,
+        ].join("\n");
+        message(body, {
+          title: t("dialogs.contextSize.title", { name: group.name }),
+          kind: "info",
+        });
+      })
+      .catch((error) => {
+        message(String(error), { title: t("common.error"), kind: "error" });
+      });
+  };
   const handleDeleteGroup = (group: Group) => {
     deleteGroup(group.id);
   };
@@ -180,6 +258,21 @@ export function GroupManager({
     // Chỉ gửi ID và trường cần cập nhật
     updateGroup({ id: group.id, tokenLimit: limit });
   };
+  const handleSaveTemplatePath = (group: Group, templatePath?: string) => {
+    updateGroup({ id: group.id, templatePath });
+  };
+
+  const handleToggleOutline = (group: Group, enabled: boolean) => {
+    updateGroup({ id: group.id, outline: enabled });
+  };
+
+  const handleExpandDependencies = (group: Group, depth: number | null) => {
+    expandGroupDependencies(group.id, depth);
+  };
+
+  const handleAddDependents = (group: Group, depth: number | null) => {
+    addGroupDependents(group.id, depth);
+  };
 
   return (
     <>
@@ -205,6 +298,7 @@ export function GroupManager({
                 group={group}
                 isLoading={isLoading}
                 isEditing={isEditing}
+                isOutline={group.outline ?? exportOutline}
                 onEditContent={handleEditContentClick}
                 onStartRename={onStartRename}
                 onConfirmRename={onConfirmRename}
@@ -212,7 +306,12 @@ export function GroupManager({
                 onCopyContext={handleCopyContext}
                 onAttachToAi={handleAttachToAi}
                 onExport={handleExport}
+                onMeasureContext={handleMeasureContext}
                 onSaveTokenLimit={handleSaveTokenLimit}
+                onSaveTemplatePath={handleSaveTemplatePath}
+                onToggleOutline={handleToggleOutline}
+                onExpandDependencies={handleExpandDependencies}
+                onAddDependents={handleAddDependents}
                 onDelete={handleDeleteGroup}
               />
             );
@@ -0,0 +1,55 @@
+// src/components/TemplatePathEditor.tsx
+import { useState, useEffect, useRef, type KeyboardEvent } from "react";
+import { useTranslation } from "react-i18next";
+import { type Group } from "@/store/types";
+import { Input } from "@/components/ui/input";
+import { Button } from "@/components/ui/button";
+import { Save } from "lucide-react";
+
+interface TemplatePathEditorProps {
+  group: Group;
+  onSave: (templatePath?: string) => void;
+}
+
+export function TemplatePathEditor({ group, onSave }: TemplatePathEditorProps) {
+  const { t } = useTranslation();
+  const [templatePath, setTemplatePath] = useState(group.templatePath ?? "");
+  const inputRef = useRef<HTMLInputElement>(null);
+
+  useEffect(() => {
+    setTimeout(() => inputRef.current?.focus(), 100);
+  }, []);
+
+  const handleSave = () => {
+    const trimmed = templatePath.trim();
+    onSave(trimmed === "" ? undefined : trimmed);
+  };
+
+  const handleKeyDown = (e: KeyboardEvent<HTMLInputElement>) => {
+    if (e.key === "Enter") {
+      handleSave();
+      e.preventDefault();
+    }
+  };
+
+  return (
+    <div className="p-2 space-y-2 w-72">
+      <p className="text-xs font-medium text-muted-foreground px-1">
+        {t("templatePathEditor.title")}
+      </p>
+      <div className="flex items-center gap-2">
+        <Input
+          ref={inputRef}
+          placeholder={t("templatePathEditor.placeholder")}
+          value={templatePath}
+          onChange={(e) => setTemplatePath(e.target.value)}
+          onKeyDown={handleKeyDown}
+          className="h-8"
+        />
+        <Button size="icon" className="h-8 w-8" onClick={handleSave}>
+          <Save className="h-4 w-4" />
+        </Button>
+      </div>
+    </div>
+  );
+}
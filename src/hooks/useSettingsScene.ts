@@ -2,24 +2,39 @@
 import { useState, useEffect } from "react";
 import { useAppStore, useAppActions } from "@/store/appStore";
 import { useShallow } from "zustand/react/shallow";
+import { useTranslation } from "react-i18next";
+import { invoke } from "@tauri-apps/api/core";
 import { open, message } from "@tauri-apps/plugin-dialog";
+import { type SecretFinding } from "@/store/types";
+
+// Số bí mật tối đa liệt kê trong hộp thoại kết quả quét
+const MAX_LISTED_SECRET_FINDINGS = 20;
 
 export type SettingsTab = "appearance" | "project" | "export" | "ai";
 
 export function useSettingsScene() {
+  const { t } = useTranslation();
   const {
     syncEnabled,
     syncPath,
     customIgnorePatterns,
     isWatchingFiles,
+    watcherDebounceMs,
     rootPath,
     exportUseFullTree,
     exportWithLineNumbers,
+    exportFormat,
+    exportTemplatePath,
     exportWithoutComments,
     exportRemoveDebugLogs,
     exportSuperCompressed,
+    exportOutline,
+    exportRepoMap,
     alwaysApplyText,
+    exportChunkTokenLimit,
     exportExcludeExtensions,
+    exportRedactSecrets,
+    secretPatterns,
     gitExportModeIsContext,
     googleApiKey,
     openRouterApiKey,
@@ -36,14 +51,22 @@ export function useSettingsScene() {
       syncPath: state.syncPath,
       customIgnorePatterns: state.customIgnorePatterns,
       isWatchingFiles: state.isWatchingFiles,
+      watcherDebounceMs: state.watcherDebounceMs,
       rootPath: state.rootPath,
       exportUseFullTree: state.exportUseFullTree,
       exportWithLineNumbers: state.exportWithLineNumbers,
+      exportFormat: state.exportFormat,
+      exportTemplatePath: state.exportTemplatePath,
       exportWithoutComments: state.exportWithoutComments,
       exportRemoveDebugLogs: state.exportRemoveDebugLogs,
       exportSuperCompressed: state.exportSuperCompressed,
+      exportOutline: state.exportOutline,
+      exportRepoMap: state.exportRepoMap,
       alwaysApplyText: state.alwaysApplyText,
+      exportChunkTokenLimit: state.exportChunkTokenLimit,
       exportExcludeExtensions: state.exportExcludeExtensions,
+      exportRedactSecrets: state.exportRedactSecrets,
+      secretPatterns: state.secretPatterns,
       gitExportModeIsContext: state.gitExportModeIsContext,
       googleApiKey: state.googleApiKey,
       openRouterApiKey: state.openRouterApiKey,
@@ -61,14 +84,21 @@ export function useSettingsScene() {
     setSyncSettings,
     setCustomIgnorePatterns,
     setFileWatching,
+    setWatcherDebounceMs,
     showDashboard,
     setExportUseFullTree,
     setExportWithLineNumbers,
+    setExportFormat,
+    setExportTemplatePath,
     setExportWithoutComments,
     setExportRemoveDebugLogs,
     setExportSuperCompressed,
+    setExportOutline,
+    setExportRepoMap,
     setAlwaysApplyText,
+    setExportChunkTokenLimit,
     setExportExcludeExtensions,
+    setSecretRedaction,
     setGitExportMode,
     deleteCurrentProjectData,
     updateAppSettings,
@@ -79,6 +109,7 @@ export function useSettingsScene() {
   const [isSaving, setIsSaving] = useState(false);
   const [isDeleteProjectDialogOpen, setIsDeleteProjectDialogOpen] =
     useState(false);
+  const [isScanningSecrets, setIsScanningSecrets] = useState(false);
 
   useEffect(() => {
     setIgnoreText((customIgnorePatterns || []).join("\n"));
@@ -124,6 +155,51 @@ export function useSettingsScene() {
     }
   };
 
+  const handleScanSecrets = async () => {
+    if (!rootPath) return;
+    setIsScanningSecrets(true);
+    try {
+      const findings = await invoke<SecretFinding[]>(
+        "scan_project_for_secrets",
+        { path: rootPath }
+      );
+      if (findings.length === 0) {
+        await message(t("dialogs.secretScan.none"), {
+          title: t("dialogs.secretScan.title"),
+          kind: "info",
+        });
+        return;
+      }
+      const lines = findings.slice(0, MAX_LISTED_SECRET_FINDINGS).map((f) =>
+        t("dialogs.secretScan.finding", {
+          severity: t(`dialogs.secretScan.severity.${f.severity}`),
+          path: f.path,
+          line: f.line,
+          rule: f.rule,
+        })
+      );
+      if (findings.length > MAX_LISTED_SECRET_FINDINGS) {
+        lines.push(
+          t("dialogs.secretScan.more", {
+            count: findings.length - MAX_LISTED_SECRET_FINDINGS,
+          })
+        );
+      }
+      const body = [
+        t("dialogs.secretScan.found", { count: findings.length }),
+        ...lines,
+      ].join("\n");
+      await message(body, {
+        title: t("dialogs.secretScan.title"),
+        kind: "warning",
+      });
+    } catch (error) {
+      await message(String(error), { title: t("common.error"), kind: "error" });
+    } finally {
+      setIsScanningSecrets(false);
+    }
+  };
+
   const handleConfirmDeleteProjectData = async () => {
     await deleteCurrentProjectData();
   };
@@ -135,14 +211,22 @@ export function useSettingsScene() {
     syncPath,
     customIgnorePatterns,
     isWatchingFiles,
+    watcherDebounceMs,
     rootPath,
     exportUseFullTree,
     exportWithLineNumbers,
+    exportFormat,
+    exportTemplatePath,
     exportWithoutComments,
     exportRemoveDebugLogs,
     exportSuperCompressed,
+    exportOutline,
+    exportRepoMap,
     alwaysApplyText,
+    exportChunkTokenLimit,
     exportExcludeExtensions,
+    exportRedactSecrets,
+    secretPatterns,
     showDashboard,
     googleApiKey,
     openRouterApiKey,
@@ -157,13 +241,20 @@ export function useSettingsScene() {
     setSyncSettings,
     setCustomIgnorePatterns,
     setFileWatching,
+    setWatcherDebounceMs,
     setExportUseFullTree,
     setExportWithLineNumbers,
+    setExportFormat,
+    setExportTemplatePath,
     setExportWithoutComments,
     setExportRemoveDebugLogs,
     setExportSuperCompressed,
+    setExportOutline,
+    setExportRepoMap,
     setAlwaysApplyText,
+    setExportChunkTokenLimit,
     setExportExcludeExtensions,
+    setSecretRedaction,
     gitExportModeIsContext,
     setGitExportMode,
     handleToggleSync,
@@ -172,6 +263,8 @@ export function useSettingsScene() {
     setIgnoreText,
     isSaving,
     handleSaveIgnorePatterns,
+    isScanningSecrets,
+    handleScanSecrets,
     isDeleteProjectDialogOpen,
     setIsDeleteProjectDialogOpen,
     handleConfirmDeleteProjectData,
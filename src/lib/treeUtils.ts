@@ -1,5 +1,5 @@
 // src/lib/treeUtils.ts
-import type { FileNode } from "@/store/types";
+import type { FileNode, ProjectDiff } from "@/store/types";
 
 /**
  * Lấy tất cả đường dẫn của chính node và các con cháu của nó.
@@ -127,3 +127,65 @@ export const expandPaths = (
 
   return expanded;
 };
+
+// Thứ tự giống backend: thư mục trước, sau đó theo tên
+const compareNodes = (a: FileNode, b: FileNode): number => {
+  const aIsDir = Array.isArray(a.children);
+  const bIsDir = Array.isArray(b.children);
+  if (aIsDir !== bIsDir) return aIsDir ? -1 : 1;
+  return a.name < b.name ? -1 : a.name > b.name ? 1 : 0;
+};
+
+/**
+ * Áp dụng diff từ watcher lên cây thư mục mà không cần quét lại toàn bộ.
+ * @param rootNode - Node gốc của cây thư mục hiện tại.
+ * @param diff - Danh sách file/thư mục được thêm và bị xóa.
+ * @returns Cây thư mục mới (cây cũ không bị thay đổi).
+ */
+export const applyTreeDiff = (
+  rootNode: FileNode,
+  diff: ProjectDiff
+): FileNode => {
+  const root: FileNode = structuredClone(rootNode);
+
+  const findNode = (path: string): FileNode | undefined => {
+    let node: FileNode | undefined = root;
+    for (const name of path.split("/").filter(Boolean)) {
+      node = node?.children?.find((c) => c.name === name);
+    }
+    return node;
+  };
+
+  for (const path of diff.removed) {
+    const index = path.lastIndexOf("/");
+    const parent = findNode(index === -1 ? "" : path.substring(0, index));
+    if (parent && Array.isArray(parent.children)) {
+      const name = path.substring(index + 1);
+      parent.children = parent.children.filter((c) => c.name !== name);
+    }
+  }
+
+  const insert = (path: string, isDir: boolean) => {
+    const names = path.split("/");
+    let node = root;
+    names.forEach((name, i) => {
+      const isLast = i === names.length - 1;
+      if (!Array.isArray(node.children)) node.children = [];
+      let child = node.children.find((c) => c.name === name);
+      if (!child) {
+        child = {
+          name,
+          path: names.slice(0, i + 1).join("/"),
+          children: isLast && !isDir ? null : [],
+        };
+        node.children.push(child);
+        node.children.sort(compareNodes);
+      }
+      node = child;
+    });
+  };
+
+  diff.addedDirs.forEach((path) => insert(path, true));
+  diff.added.forEach((path) => insert(path, false));
+  return root;
+};
@@ -20,14 +20,22 @@ export function SettingsScene() {
     syncEnabled,
     syncPath,
     isWatchingFiles,
+    watcherDebounceMs,
     rootPath,
     exportUseFullTree,
     exportWithLineNumbers,
+    exportFormat,
+    exportTemplatePath,
     exportWithoutComments,
     exportRemoveDebugLogs,
     exportSuperCompressed,
+    exportOutline,
+    exportRepoMap,
     alwaysApplyText,
+    exportChunkTokenLimit,
     exportExcludeExtensions,
+    exportRedactSecrets,
+    secretPatterns,
     gitExportModeIsContext,
     googleApiKey,
     openRouterApiKey, // This should be apiKey
@@ -41,13 +49,20 @@ export function SettingsScene() {
     updateAppSettings,
     showDashboard,
     setFileWatching,
+    setWatcherDebounceMs,
     setExportUseFullTree,
     setExportWithLineNumbers,
+    setExportFormat,
+    setExportTemplatePath,
     setExportWithoutComments,
     setExportRemoveDebugLogs,
     setExportSuperCompressed,
+    setExportOutline,
+    setExportRepoMap,
     setAlwaysApplyText,
+    setExportChunkTokenLimit,
     setExportExcludeExtensions,
+    setSecretRedaction,
     setGitExportMode,
     handleToggleSync,
     handleChooseSyncPath,
@@ -55,6 +70,8 @@ export function SettingsScene() {
     setIgnoreText,
     isSaving,
     handleSaveIgnorePatterns,
+    isScanningSecrets,
+    handleScanSecrets,
     isDeleteProjectDialogOpen,
     setIsDeleteProjectDialogOpen,
     handleConfirmDeleteProjectData,
@@ -86,6 +103,8 @@ export function SettingsScene() {
             gitExportModeIsContext={gitExportModeIsContext}
             setGitExportMode={setGitExportMode}
             setFileWatching={setFileWatching}
+            watcherDebounceMs={watcherDebounceMs}
+            setWatcherDebounceMs={setWatcherDebounceMs}
             rootPath={rootPath}
             ignoreText={ignoreText}
             setIgnoreText={setIgnoreText}
@@ -103,16 +122,31 @@ export function SettingsScene() {
             setExportUseFullTree={setExportUseFullTree}
             exportWithLineNumbers={exportWithLineNumbers}
             setExportWithLineNumbers={setExportWithLineNumbers}
+            exportFormat={exportFormat}
+            setExportFormat={setExportFormat}
+            exportTemplatePath={exportTemplatePath}
+            setExportTemplatePath={setExportTemplatePath}
             exportWithoutComments={exportWithoutComments}
             setExportWithoutComments={setExportWithoutComments}
             exportSuperCompressed={exportSuperCompressed}
             setExportSuperCompressed={setExportSuperCompressed}
+            exportOutline={exportOutline}
+            setExportOutline={setExportOutline}
+            exportRepoMap={exportRepoMap}
+            setExportRepoMap={setExportRepoMap}
             exportRemoveDebugLogs={exportRemoveDebugLogs}
             setExportRemoveDebugLogs={setExportRemoveDebugLogs}
             exportExcludeExtensions={exportExcludeExtensions}
             setExportExcludeExtensions={setExportExcludeExtensions}
+            exportRedactSecrets={exportRedactSecrets}
+            secretPatterns={secretPatterns}
+            setSecretRedaction={setSecretRedaction}
+            isScanningSecrets={isScanningSecrets}
+            onScanSecrets={handleScanSecrets}
             alwaysApplyText={alwaysApplyText}
             setAlwaysApplyText={setAlwaysApplyText}
+            exportChunkTokenLimit={exportChunkTokenLimit}
+            setExportChunkTokenLimit={setExportChunkTokenLimit}
           />
         );
       case "ai":
@@ -10,6 +10,7 @@ import { ScrollArea } from "@/components/ui/scroll-area";
 import { Input } from "@/components/ui/input";
 import { AnalysisSettingsDialog } from "@/components/AnalysisSettingsDialog";
 import { useShallow } from "zustand/react/shallow";
+import { type Tokenizer } from "@/store/types";
 import iconSrc from "@/assets/icon.png";
 
 // Helper to get the last part of the path
@@ -22,10 +23,11 @@ export function WelcomeScene() {
   const { t } = useTranslation();
   const { selectRootPath, cloneAndOpenProject, updateAppSettings } =
     useAppActions();
-  const { recentPaths, nonAnalyzableExtensions } = useAppStore(
+  const { recentPaths, nonAnalyzableExtensions, tokenizer } = useAppStore(
     useShallow((state) => ({
       recentPaths: state.recentPaths,
       nonAnalyzableExtensions: state.nonAnalyzableExtensions,
+      tokenizer: state.tokenizer,
     }))
   );
   const isScanning = useAppStore((state) => state.isScanning);
@@ -54,8 +56,8 @@ export function WelcomeScene() {
     }
   };
 
-  const handleSaveSettings = (extensions: string[]) => {
-    updateAppSettings({ nonAnalyzableExtensions: extensions });
+  const handleSaveSettings = (extensions: string[], tokenizer: Tokenizer) => {
+    updateAppSettings({ nonAnalyzableExtensions: extensions, tokenizer });
   };
 
   return (
@@ -187,6 +189,7 @@ export function WelcomeScene() {
         isOpen={isSettingsOpen}
         onClose={() => setIsSettingsOpen(false)}
         initialExtensions={nonAnalyzableExtensions}
+        initialTokenizer={tokenizer}
         onSave={handleSaveSettings}
       />
     </div>
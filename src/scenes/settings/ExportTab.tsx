@@ -7,23 +7,57 @@ import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { Switch } from "@/components/ui/switch";
 import { Textarea } from "@/components/ui/textarea";
-import { Save, Loader2 } from "lucide-react";
+import {
+  Select,
+  SelectContent,
+  SelectItem,
+  SelectTrigger,
+  SelectValue,
+} from "@/components/ui/select";
+import { Save, Loader2, ShieldAlert } from "lucide-react";
+import { type ExportFormat } from "@/store/types";
+
+const EXPORT_FORMATS: ExportFormat[] = [
+  "plain",
+  "xml",
+  "markdown",
+  "json",
+  "jsonl",
+];
+
+const TEMPLATE_VARIABLES_HINT =
+  "{{tree}}, {{repo_map}}, {{#each files}}{{path}} {{content}}{{/each}}, {{group.name}}, {{git.branch}}, {{stats.tokens}}";
 
 interface ExportTabProps {
   exportUseFullTree: boolean;
   setExportUseFullTree: (enabled: boolean) => void;
   exportWithLineNumbers: boolean;
   setExportWithLineNumbers: (enabled: boolean) => void;
+  exportFormat: ExportFormat;
+  setExportFormat: (format: ExportFormat) => void;
+  exportTemplatePath: string | null;
+  setExportTemplatePath: (templatePath: string | null) => Promise<void>;
   exportWithoutComments: boolean;
   setExportWithoutComments: (enabled: boolean) => void;
   exportSuperCompressed: boolean;
   setExportSuperCompressed: (enabled: boolean) => void;
+  exportOutline: boolean;
+  setExportOutline: (enabled: boolean) => void;
+  exportRepoMap: boolean;
+  setExportRepoMap: (enabled: boolean) => void;
   exportRemoveDebugLogs: boolean;
   setExportRemoveDebugLogs: (enabled: boolean) => void;
   exportExcludeExtensions: string[];
   setExportExcludeExtensions: (extensions: string[]) => Promise<void>;
+  exportRedactSecrets: boolean;
+  secretPatterns: string[];
+  setSecretRedaction: (enabled: boolean, patterns: string[]) => Promise<void>;
+  isScanningSecrets: boolean;
+  onScanSecrets: () => void;
   alwaysApplyText: string | null;
   setAlwaysApplyText: (text: string) => Promise<void>;
+  exportChunkTokenLimit: number | null;
+  setExportChunkTokenLimit: (limit: number | null) => Promise<void>;
 }
 
 export function ExportTab({
@@ -31,22 +65,43 @@ export function ExportTab({
   setExportUseFullTree,
   exportWithLineNumbers,
   setExportWithLineNumbers,
+  exportFormat,
+  setExportFormat,
+  exportTemplatePath,
+  setExportTemplatePath,
   exportWithoutComments,
   setExportWithoutComments,
   exportSuperCompressed,
   setExportSuperCompressed,
+  exportOutline,
+  setExportOutline,
+  exportRepoMap,
+  setExportRepoMap,
   exportRemoveDebugLogs,
   setExportRemoveDebugLogs,
   exportExcludeExtensions,
   setExportExcludeExtensions,
+  exportRedactSecrets,
+  secretPatterns,
+  setSecretRedaction,
+  isScanningSecrets,
+  onScanSecrets,
   alwaysApplyText,
   setAlwaysApplyText,
+  exportChunkTokenLimit,
+  setExportChunkTokenLimit,
 }: ExportTabProps) {
   const { t } = useTranslation();
   const [localExcludeText, setLocalExcludeText] = useState("");
   const [isSaving, setIsSaving] = useState(false);
   const [localAlwaysApplyText, setLocalAlwaysApplyText] = useState("");
   const [isSavingText, setIsSavingText] = useState(false);
+  const [localTemplatePath, setLocalTemplatePath] = useState("");
+  const [isSavingTemplate, setIsSavingTemplate] = useState(false);
+  const [localChunkLimit, setLocalChunkLimit] = useState("");
+  const [isSavingChunkLimit, setIsSavingChunkLimit] = useState(false);
+  const [localSecretPatterns, setLocalSecretPatterns] = useState("");
+  const [isSavingPatterns, setIsSavingPatterns] = useState(false);
 
   useEffect(() => {
     setLocalExcludeText(exportExcludeExtensions.join(", "));
@@ -56,6 +111,18 @@ export function ExportTab({
     setLocalAlwaysApplyText(alwaysApplyText || "");
   }, [alwaysApplyText]);
 
+  useEffect(() => {
+    setLocalTemplatePath(exportTemplatePath ?? "");
+  }, [exportTemplatePath]);
+
+  useEffect(() => {
+    setLocalSecretPatterns(secretPatterns.join("\n"));
+  }, [secretPatterns]);
+
+  useEffect(() => {
+    setLocalChunkLimit(exportChunkTokenLimit?.toString() ?? "");
+  }, [exportChunkTokenLimit]);
+
   const handleSave = async () => {
     setIsSaving(true);
     const extensions = localExcludeText
@@ -66,6 +133,32 @@ export function ExportTab({
     setIsSaving(false);
   };
 
+  const handleSaveTemplatePath = async () => {
+    setIsSavingTemplate(true);
+    const trimmed = localTemplatePath.trim();
+    await setExportTemplatePath(trimmed === "" ? null : trimmed);
+    setIsSavingTemplate(false);
+  };
+
+  const handleSaveChunkLimit = async () => {
+    setIsSavingChunkLimit(true);
+    const parsed = parseInt(localChunkLimit, 10);
+    await setExportChunkTokenLimit(
+      !isNaN(parsed) && parsed > 0 ? parsed : null
+    );
+    setIsSavingChunkLimit(false);
+  };
+
+  const handleSavePatterns = async () => {
+    setIsSavingPatterns(true);
+    const patterns = localSecretPatterns
+      .split("\n")
+      .map((s) => s.trim())
+      .filter(Boolean);
+    await setSecretRedaction(exportRedactSecrets, patterns);
+    setIsSavingPatterns(false);
+  };
+
   const handleSaveText = async () => {
     setIsSavingText(true);
     await setAlwaysApplyText(localAlwaysApplyText);
@@ -91,6 +184,32 @@ export function ExportTab({
             onCheckedChange={setExportUseFullTree}
           />
         </div>
+        <div className="flex items-center justify-between pt-4 border-t">
+          <Label
+            htmlFor="export-format-select"
+            className="flex flex-col items-start gap-1"
+          >
+            <span>{t("settings.export.format.label")}</span>
+            <span className="text-xs text-muted-foreground">
+              {t("settings.export.format.description")}
+            </span>
+          </Label>
+          <Select
+            value={exportFormat}
+            onValueChange={(value) => setExportFormat(value as ExportFormat)}
+          >
+            <SelectTrigger id="export-format-select" className="w-[180px]">
+              <SelectValue />
+            </SelectTrigger>
+            <SelectContent>
+              {EXPORT_FORMATS.map((format) => (
+                <SelectItem key={format} value={format}>
+                  {t(`settings.export.format.options.${format}`)}
+                </SelectItem>
+              ))}
+            </SelectContent>
+          </Select>
+        </div>
         <div className="flex items-center justify-between pt-4 border-t">
           <Label
             htmlFor="export-lines-toggle"
@@ -131,6 +250,38 @@ export function ExportTab({
             disabled={exportWithLineNumbers}
           />
         </div>
+        <div className="flex items-center justify-between pt-4 border-t">
+          <Label
+            htmlFor="export-outline-toggle"
+            className="flex flex-col items-start gap-1"
+          >
+            <span>{t("settings.export.outline.label")}</span>
+            <span className="text-xs text-muted-foreground">
+              {t("settings.export.outline.description")}
+            </span>
+          </Label>
+          <Switch
+            id="export-outline-toggle"
+            checked={exportOutline}
+            onCheckedChange={setExportOutline}
+          />
+        </div>
+        <div className="flex items-center justify-between pt-4 border-t">
+          <Label
+            htmlFor="export-repo-map-toggle"
+            className="flex flex-col items-start gap-1"
+          >
+            <span>{t("settings.export.repoMap.label")}</span>
+            <span className="text-xs text-muted-foreground">
+              {t("settings.export.repoMap.description")}
+            </span>
+          </Label>
+          <Switch
+            id="export-repo-map-toggle"
+            checked={exportRepoMap}
+            onCheckedChange={setExportRepoMap}
+          />
+        </div>
         <div className="flex items-center justify-between pt-4 border-t">
           <Label
             htmlFor="export-comments-toggle"
@@ -197,6 +348,142 @@ export function ExportTab({
             {t("settings.export.excludeExtensions.saveButton")}
           </Button>
         </div>
+        <div className="flex items-center justify-between pt-4 border-t">
+          <Label
+            htmlFor="export-redact-secrets-toggle"
+            className="flex flex-col items-start gap-1"
+          >
+            <span>{t("settings.export.redactSecrets.label")}</span>
+            <span className="text-xs text-muted-foreground">
+              {t("settings.export.redactSecrets.description")}
+            </span>
+          </Label>
+          <Switch
+            id="export-redact-secrets-toggle"
+            checked={exportRedactSecrets}
+            onCheckedChange={(checked) =>
+              setSecretRedaction(checked, secretPatterns)
+            }
+          />
+        </div>
+        <div className="flex flex-col space-y-3">
+          <div className="flex flex-col items-start gap-1">
+            <Label htmlFor="export-secret-patterns">
+              {t("settings.export.redactSecrets.patternsLabel")}
+            </Label>
+            <span className="text-xs text-muted-foreground">
+              {t("settings.export.redactSecrets.patternsDescription")}
+            </span>
+          </div>
+          <Textarea
+            id="export-secret-patterns"
+            placeholder={t("settings.export.redactSecrets.patternsPlaceholder")}
+            className="resize-y min-h-[80px] font-mono text-xs"
+            value={localSecretPatterns}
+            onChange={(e) => setLocalSecretPatterns(e.target.value)}
+            disabled={!exportRedactSecrets || isSavingPatterns}
+          />
+          <Button
+            onClick={handleSavePatterns}
+            disabled={
+              !exportRedactSecrets ||
+              isSavingPatterns ||
+              localSecretPatterns === secretPatterns.join("\n")
+            }
+            className="w-full mt-2"
+          >
+            {isSavingPatterns ? (
+              <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+            ) : (
+              <Save className="mr-2 h-4 w-4" />
+            )}
+            {t("settings.export.redactSecrets.saveButton")}
+          </Button>
+          <Button
+            variant="outline"
+            onClick={onScanSecrets}
+            disabled={isScanningSecrets}
+            className="w-full"
+          >
+            {isScanningSecrets ? (
+              <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+            ) : (
+              <ShieldAlert className="mr-2 h-4 w-4" />
+            )}
+            {t("settings.export.redactSecrets.scanButton")}
+          </Button>
+          <p className="text-sm text-muted-foreground">
+            {t("settings.export.redactSecrets.scanHint")}
+          </p>
+        </div>
+        <div className="flex flex-col space-y-3 pt-4 border-t">
+          <div className="flex flex-col items-start gap-1">
+            <Label htmlFor="export-template-path">
+              {t("settings.export.template.label")}
+            </Label>
+            <span className="text-xs text-muted-foreground">
+              {t("settings.export.template.description")}
+            </span>
+          </div>
+          <Input
+            id="export-template-path"
+            placeholder={t("settings.export.template.placeholder")}
+            value={localTemplatePath}
+            onChange={(e) => setLocalTemplatePath(e.target.value)}
+          />
+          <p className="text-sm text-muted-foreground">
+            {t("settings.export.template.hint")}{" "}
+            <code className="text-xs">{TEMPLATE_VARIABLES_HINT}</code>
+          </p>
+          <Button
+            onClick={handleSaveTemplatePath}
+            disabled={
+              isSavingTemplate ||
+              localTemplatePath === (exportTemplatePath ?? "")
+            }
+            className="w-full mt-2"
+          >
+            {isSavingTemplate ? (
+              <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+            ) : (
+              <Save className="mr-2 h-4 w-4" />
+            )}
+            {t("settings.export.template.saveButton")}
+          </Button>
+        </div>
+        <div className="flex flex-col space-y-3 pt-4 border-t">
+          <div className="flex flex-col items-start gap-1">
+            <Label htmlFor="export-chunk-token-limit">
+              {t("settings.export.chunkTokenLimit.label")}
+            </Label>
+            <span className="text-xs text-muted-foreground">
+              {t("settings.export.chunkTokenLimit.description")}
+            </span>
+          </div>
+          <Input
+            id="export-chunk-token-limit"
+            type="number"
+            min={0}
+            placeholder={t("settings.export.chunkTokenLimit.placeholder")}
+            value={localChunkLimit}
+            onChange={(e) => setLocalChunkLimit(e.target.value)}
+          />
+          <Button
+            onClick={handleSaveChunkLimit}
+            disabled={
+              isSavingChunkLimit ||
+              localChunkLimit === (exportChunkTokenLimit?.toString() ?? "")
+            }
+            className="w-full mt-2"
+          >
+            {isSavingChunkLimit ? (
+              <Loader2 className="mr-2 h-4 w-4 animate-spin" />
+            ) : (
+              <Save className="mr-2 h-4 w-4" />
+            )}
+            {t("settings.export.chunkTokenLimit.saveButton")}
+          </Button>
+        </div>
         <div className="space-y-4 rounded-lg border p-4 flex flex-col pt-4 border-t">
           <h3 className="font-semibold">{t("settings.alwaysApply.title")}</h3>
           <div className="space-y-2 flex-grow flex flex-col">
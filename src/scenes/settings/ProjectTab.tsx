@@ -1,11 +1,19 @@
 // src/scenes/settings/ProjectTab.tsx
+import { useEffect, useState } from "react";
 import { useTranslation } from "react-i18next";
 import { Button } from "@/components/ui/button";
+import { Input } from "@/components/ui/input";
 import { Label } from "@/components/ui/label";
 import { Switch } from "@/components/ui/switch";
 import { Textarea } from "@/components/ui/textarea";
 import { Card, CardContent, CardHeader, CardTitle } from "@/components/ui/card";
-import { Loader2, AlertTriangle, FolderUp, GitBranch } from "lucide-react";
+import {
+  Loader2,
+  AlertTriangle,
+  FolderUp,
+  GitBranch,
+  Save,
+} from "lucide-react";
 import {
   AlertDialog,
   AlertDialogAction,
@@ -20,6 +28,8 @@ import {
 interface ProjectTabProps {
   isWatchingFiles: boolean;
   setFileWatching: (enabled: boolean) => void;
+  watcherDebounceMs: number | null;
+  setWatcherDebounceMs: (debounceMs: number | null) => Promise<void>;
   rootPath: string | null;
   ignoreText: string;
   setIgnoreText: (text: string) => void;
@@ -39,6 +49,8 @@ interface ProjectTabProps {
 export function ProjectTab({
   isWatchingFiles,
   setFileWatching,
+  watcherDebounceMs,
+  setWatcherDebounceMs,
   rootPath,
   ignoreText,
   setIgnoreText,
@@ -55,6 +67,22 @@ export function ProjectTab({
   setGitExportMode,
 }: ProjectTabProps) {
   const { t } = useTranslation();
+  const [localDebounce, setLocalDebounce] = useState(
+    watcherDebounceMs?.toString() ?? ""
+  );
+  const [isSavingDebounce, setIsSavingDebounce] = useState(false);
+
+  useEffect(() => {
+    setLocalDebounce(watcherDebounceMs?.toString() ?? "");
+  }, [watcherDebounceMs]);
+
+  const handleSaveDebounce = async () => {
+    setIsSavingDebounce(true);
+    const parsed = parseInt(localDebounce, 10);
+    await setWatcherDebounceMs(!isNaN(parsed) && parsed > 0 ? parsed : null);
+    setIsSavingDebounce(false);
+  };
+
   return (
     <div className="space-y-6">
       <h2 className="text-xl font-semibold">{t("settings.project.title")}</h2>
@@ -80,6 +108,44 @@ export function ProjectTab({
               disabled={!rootPath}
             />
           </div>
+          <div className="flex flex-col space-y-2 pt-4 mt-4 border-t">
+            <Label
+              htmlFor="watcher-debounce"
+              className="flex flex-col items-start"
+            >
+              <span>{t("settings.project.watching.debounce.label")}</span>
+              <span className="text-xs text-muted-foreground">
+                {t("settings.project.watching.debounce.description")}
+              </span>
+            </Label>
+            <div className="flex items-center gap-2">
+              <Input
+                id="watcher-debounce"
+                type="number"
+                min={0}
+                placeholder={t("settings.project.watching.debounce.placeholder")}
+                value={localDebounce}
+                onChange={(e) => setLocalDebounce(e.target.value)}
+                disabled={!rootPath}
+              />
+              <Button
+                variant="outline"
+                size="icon"
+                onClick={handleSaveDebounce}
+                disabled={
+                  !rootPath ||
+                  isSavingDebounce ||
+                  localDebounce === (watcherDebounceMs?.toString() ?? "")
+                }
+              >
+                {isSavingDebounce ? (
+                  <Loader2 className="h-4 w-4 animate-spin" />
+                ) : (
+                  <Save className="h-4 w-4" />
+                )}
+              </Button>
+            </div>
+          </div>
         </CardContent>
       </Card>
 
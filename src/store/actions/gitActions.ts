@@ -5,12 +5,14 @@ import {
   type GitRepositoryInfo,
   type GitCommit,
   type GitStatus,
+  type ExportedContext,
 } from "../types";
 import { invoke } from "@tauri-apps/api/core";
 import { save } from "@tauri-apps/plugin-dialog";
 import { writeText } from "@tauri-apps/plugin-clipboard-manager";
 import { writeTextFile } from "@tauri-apps/plugin-fs";
 import { message } from "@tauri-apps/plugin-dialog";
+import i18n from "@/i18n";
 
 const COMMITS_PER_PAGE = 20;
 
@@ -112,10 +114,13 @@ export const createGitActions: StateCreator<AppState, [], [], GitActions> = (
       const command = gitExportModeIsContext
         ? "generate_commit_context"
         : "get_commit_diff";
-      const content = await invoke<string>(command, {
-        path: rootPath,
-        commitSha,
-      });
+      const { context: content, redactions } = await invoke<ExportedContext>(
+        command,
+        {
+          path: rootPath,
+          commitSha,
+        }
+      );
 
       const fileType = gitExportModeIsContext ? "ngữ cảnh" : "diff";
       const defaultPath = gitExportModeIsContext
@@ -133,11 +138,17 @@ export const createGitActions: StateCreator<AppState, [], [], GitActions> = (
 
       if (filePath) {
         await writeTextFile(filePath, content);
+        const savedBody = `Đã lưu ${fileType} của commit ${commitSha.substring(
+          0,
+          7
+        )} vào file.`;
+        const redactedCount = redactions.secrets.length;
         await message(
-          `Đã lưu ${fileType} của commit ${commitSha.substring(
-            0,
-            7
-          )} vào file.`,
+          redactedCount > 0
+            ? `${savedBody}\n${i18n.t("dialogs.saveSuccess.secretsRedacted", {
+                count: redactedCount,
+              })}`
+            : savedBody,
           { title: "Thành công", kind: "info" }
         );
       }
@@ -158,11 +169,11 @@ export const createGitActions: StateCreator<AppState, [], [], GitActions> = (
       const command = gitExportModeIsContext
         ? "generate_commit_context"
         : "get_commit_diff";
-      const content = await invoke<string>(command, {
+      const { context } = await invoke<ExportedContext>(command, {
         path: rootPath,
         commitSha,
       });
-      await writeText(content);
+      await writeText(context);
       return true;
     } catch (e) {
       const fileType = gitExportModeIsContext ? "ngữ cảnh" : "diff";
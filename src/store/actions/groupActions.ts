@@ -1,7 +1,12 @@
 // src/store/actions/groupActions.ts
 import { StateCreator } from "zustand";
 import { AppState } from "../appStore";
-import { type Group, type GroupStats, type FileNode } from "../types";
+import {
+  type Group,
+  type GroupStats,
+  type FileNode,
+  type AIGroupUpdateResult,
+} from "../types";
 import { invoke } from "@tauri-apps/api/core";
 import {
   getDescendantAndSelfPaths,
@@ -28,6 +33,11 @@ export interface GroupActions {
   selectAllFiles: () => void;
   deselectAllFiles: () => void;
   _updateGroupFromAi: (updatedGroup: Group) => void;
+  expandGroupDependencies: (
+    groupId: string,
+    depth: number | null
+  ) => Promise<void>;
+  addGroupDependents: (groupId: string, depth: number | null) => Promise<void>;
 }
 
 export const createGroupActions: StateCreator<
@@ -195,5 +205,31 @@ export const createGroupActions: StateCreator<
         set({ tempSelectedPaths: expanded });
       }
     },
+    expandGroupDependencies: async (groupId, depth) => {
+      const { rootPath } = get();
+      if (!rootPath) return;
+      try {
+        const result = await invoke<AIGroupUpdateResult>(
+          "expand_group_dependencies",
+          { path: rootPath, groupId, depth }
+        );
+        get().actions._updateGroupFromAi(result.updatedGroup);
+      } catch (error) {
+        console.error("Lỗi khi thêm các file phụ thuộc vào nhóm:", error);
+      }
+    },
+    addGroupDependents: async (groupId, depth) => {
+      const { rootPath } = get();
+      if (!rootPath) return;
+      try {
+        const result = await invoke<AIGroupUpdateResult>(
+          "add_group_dependents",
+          { path: rootPath, groupId, depth }
+        );
+        get().actions._updateGroupFromAi(result.updatedGroup);
+      } catch (error) {
+        console.error("Lỗi khi thêm các file sử dụng nhóm vào nhóm:", error);
+      }
+    },
   };
 };
@@ -1,7 +1,12 @@
 // src/store/actions/projectActions.ts
 import { StateCreator } from "zustand";
 import { AppState } from "../appStore";
-import { type CachedProjectData, type FileMetadata } from "../types";
+import {
+  type CachedProjectData,
+  type FileMetadata,
+  type ProjectFilesChangedPayload,
+} from "../types";
+import { applyTreeDiff } from "@/lib/treeUtils";
 import { invoke } from "@tauri-apps/api/core";
 import { open, message } from "@tauri-apps/plugin-dialog";
 import { writeText } from "@tauri-apps/plugin-clipboard-manager";
@@ -16,9 +21,11 @@ export interface ProjectActions {
   _setAnalysisProgress: (file: string) => void;
   _setScanComplete: (payload: CachedProjectData) => void;
   _setScanError: (error: string) => void;
+  _applyProjectDiff: (payload: ProjectFilesChangedPayload) => Promise<void>;
   _updateFileMetadata: (filePath: string, newMetadata: FileMetadata) => void;
   exportProject: () => void;
   copyProjectToClipboard: () => Promise<void>;
+  copyRepoMapToClipboard: () => Promise<void>;
   deleteCurrentProjectData: () => Promise<void>;
 }
 
@@ -159,13 +166,21 @@ export const createProjectActions: StateCreator<
       syncPath: payload.sync_path ?? null,
       customIgnorePatterns: payload.custom_ignore_patterns ?? [],
       isWatchingFiles: payload.is_watching_files ?? false,
+      watcherDebounceMs: payload.watcher_debounce_ms ?? null,
       exportUseFullTree: payload.export_use_full_tree ?? false,
       exportWithLineNumbers: payload.export_with_line_numbers ?? true,
+      exportFormat: payload.export_format ?? "plain",
+      exportTemplatePath: payload.export_template_path ?? null,
       exportWithoutComments: payload.export_without_comments ?? false,
       exportRemoveDebugLogs: payload.export_remove_debug_logs ?? false,
       exportSuperCompressed: payload.export_super_compressed ?? false,
+      exportOutline: payload.export_outline ?? false,
+      exportRepoMap: payload.export_repo_map ?? false,
       alwaysApplyText: payload.always_apply_text ?? null,
+      exportChunkTokenLimit: payload.export_chunk_token_limit ?? null,
       exportExcludeExtensions: payload.export_exclude_extensions ?? [],
+      exportRedactSecrets: payload.export_redact_secrets ?? true,
+      secretPatterns: payload.secret_patterns ?? [],
       gitExportModeIsContext: payload.git_export_mode_is_context ?? false,
     });
 
@@ -182,6 +197,38 @@ export const createProjectActions: StateCreator<
     console.error("Scan error from Rust:", error);
     set({ isScanning: false, isRescanning: false });
   },
+  _applyProjectDiff: async ({ diff, metadata, stats, groups }) => {
+    const { fileTree, fileMetadataCache, activeEditorFile, actions } = get();
+    if (!fileTree) return;
+
+    // Xóa file bị xóa (và mọi file bên trong thư mục bị xóa) khỏi cache
+    const newCache = { ...(fileMetadataCache ?? {}) };
+    for (const removed of diff.removed) {
+      for (const key of Object.keys(newCache)) {
+        if (key === removed || key.startsWith(`${removed}/`)) {
+          delete newCache[key];
+        }
+      }
+    }
+    Object.assign(newCache, metadata);
+
+    const groupUpdates = new Map(groups.map((g) => [g.id, g]));
+    set((state) => ({
+      fileTree: applyTreeDiff(fileTree, diff),
+      fileMetadataCache: newCache,
+      projectStats: stats,
+      groups: state.groups.map((g) => {
+        const update = groupUpdates.get(g.id);
+        return update ? { ...g, paths: update.paths, stats: update.stats } : g;
+      }),
+    }));
+
+    await actions.fetchGitStatus();
+    // Làm mới file đang mở nếu nó vừa bị sửa từ bên ngoài
+    if (activeEditorFile && diff.modified.includes(activeEditorFile)) {
+      await actions.openFileInEditor(activeEditorFile);
+    }
+  },
   _updateFileMetadata: (filePath, newMetadata) => {
     set((state) => {
       if (!state.fileMetadataCache) return {};
@@ -230,6 +277,26 @@ export const createProjectActions: StateCreator<
       });
     }
   },
+  copyRepoMapToClipboard: async () => {
+    const { rootPath } = get();
+    if (!rootPath) return;
+    try {
+      const repoMap = await invoke<string>("generate_repo_map", {
+        path: rootPath,
+      });
+      await writeText(repoMap);
+      await message("Đã sao chép bản đồ dự án vào clipboard!", {
+        title: "Thành công",
+        kind: "info",
+      });
+    } catch (error) {
+      console.error("Lỗi khi tạo bản đồ dự án:", error);
+      await message(`Không thể sao chép: ${error}`, {
+        title: "Lỗi",
+        kind: "error",
+      });
+    }
+  },
   deleteCurrentProjectData: async () => {
     const { rootPath } = get();
     if (!rootPath) return;
@@ -2,7 +2,7 @@
 import { StateCreator } from "zustand";
 import { AppState } from "../appStore";
 import { invoke } from "@tauri-apps/api/core";
-import { type AppSettings } from "../types";
+import { type AppSettings, type ExportFormat } from "../types";
 import { message } from "@tauri-apps/plugin-dialog";
 
 export interface SettingsActions {
@@ -12,13 +12,20 @@ export interface SettingsActions {
   }) => Promise<void>;
   setCustomIgnorePatterns: (patterns: string[]) => Promise<void>;
   setFileWatching: (enabled: boolean) => Promise<void>;
+  setWatcherDebounceMs: (debounceMs: number | null) => Promise<void>;
   setExportUseFullTree: (enabled: boolean) => Promise<void>;
   setExportWithLineNumbers: (enabled: boolean) => Promise<void>;
+  setExportFormat: (format: ExportFormat) => Promise<void>;
+  setExportTemplatePath: (templatePath: string | null) => Promise<void>;
   setExportWithoutComments: (enabled: boolean) => Promise<void>;
   setExportRemoveDebugLogs: (enabled: boolean) => Promise<void>;
   setExportSuperCompressed: (enabled: boolean) => Promise<void>;
+  setExportOutline: (enabled: boolean) => Promise<void>;
+  setExportRepoMap: (enabled: boolean) => Promise<void>;
   setAlwaysApplyText: (text: string) => Promise<void>;
+  setExportChunkTokenLimit: (limit: number | null) => Promise<void>;
   setExportExcludeExtensions: (extensions: string[]) => Promise<void>;
+  setSecretRedaction: (enabled: boolean, patterns: string[]) => Promise<void>;
   setGitExportMode: (enabled: boolean) => Promise<void>;
   updateAppSettings: (settings: Partial<AppSettings>) => Promise<void>;
 }
@@ -92,7 +99,7 @@ export const createSettingsActions: StateCreator<
       if (enabled) {
         await invoke("start_file_watching", { path: rootPath });
       } else {
-        await invoke("stop_file_watching");
+        await invoke("stop_file_watching", { path: rootPath });
       }
     } catch (error) {
       await message(`Không thể ${enabled ? "bật" : "tắt"} theo dõi: ${error}`, {
@@ -102,6 +109,26 @@ export const createSettingsActions: StateCreator<
       set({ isWatchingFiles: !enabled });
     }
   },
+  setWatcherDebounceMs: async (debounceMs: number | null) => {
+    const { rootPath, isWatchingFiles } = get();
+    if (!rootPath) return;
+    set({ watcherDebounceMs: debounceMs });
+    try {
+      await invoke("set_watcher_debounce_setting", {
+        path: rootPath,
+        debounceMs,
+      });
+      // Khởi động lại watcher để áp dụng khoảng chờ mới
+      if (isWatchingFiles) {
+        await invoke("start_file_watching", { path: rootPath });
+      }
+    } catch (error) {
+      message(`Không thể lưu cài đặt khoảng chờ theo dõi: ${error}`, {
+        title: "Lỗi",
+        kind: "error",
+      });
+    }
+  },
   setExportUseFullTree: async (enabled: boolean) => {
     const { rootPath } = get();
     if (!rootPath) return;
@@ -136,6 +163,39 @@ export const createSettingsActions: StateCreator<
       set((state) => ({ exportWithLineNumbers: !state.exportWithLineNumbers }));
     }
   },
+  setExportFormat: async (format: ExportFormat) => {
+    const { rootPath, exportFormat: previousFormat } = get();
+    if (!rootPath) return;
+    set({ exportFormat: format });
+    try {
+      await invoke("set_export_format_setting", {
+        path: rootPath,
+        format,
+      });
+    } catch (error) {
+      message(`Không thể lưu cài đặt định dạng xuất: ${error}`, {
+        title: "Lỗi",
+        kind: "error",
+      });
+      set({ exportFormat: previousFormat });
+    }
+  },
+  setExportTemplatePath: async (templatePath: string | null) => {
+    const { rootPath } = get();
+    if (!rootPath) return;
+    set({ exportTemplatePath: templatePath });
+    try {
+      await invoke("set_export_template_setting", {
+        path: rootPath,
+        templatePath,
+      });
+    } catch (error) {
+      message(`Không thể lưu cài đặt template: ${error}`, {
+        title: "Lỗi",
+        kind: "error",
+      });
+    }
+  },
   setExportWithoutComments: async (enabled: boolean) => {
     const { rootPath } = get();
     if (!rootPath) return;
@@ -189,6 +249,40 @@ export const createSettingsActions: StateCreator<
       set((state) => ({ exportSuperCompressed: !state.exportSuperCompressed }));
     }
   },
+  setExportOutline: async (enabled: boolean) => {
+    const { rootPath } = get();
+    if (!rootPath) return;
+    set({ exportOutline: enabled });
+    try {
+      await invoke("set_export_outline_setting", {
+        path: rootPath,
+        enabled,
+      });
+    } catch (error) {
+      message(`Không thể lưu cài đặt xuất dàn ý: ${error}`, {
+        title: "Lỗi",
+        kind: "error",
+      });
+      set((state) => ({ exportOutline: !state.exportOutline }));
+    }
+  },
+  setExportRepoMap: async (enabled: boolean) => {
+    const { rootPath } = get();
+    if (!rootPath) return;
+    set({ exportRepoMap: enabled });
+    try {
+      await invoke("set_export_repo_map_setting", {
+        path: rootPath,
+        enabled,
+      });
+    } catch (error) {
+      message(`Không thể lưu cài đặt bản đồ dự án: ${error}`, {
+        title: "Lỗi",
+        kind: "error",
+      });
+      set((state) => ({ exportRepoMap: !state.exportRepoMap }));
+    }
+  },
   setAlwaysApplyText: async (text: string) => {
     const { rootPath } = get();
     if (!rootPath) return;
@@ -205,6 +299,22 @@ export const createSettingsActions: StateCreator<
       });
     }
   },
+  setExportChunkTokenLimit: async (limit: number | null) => {
+    const { rootPath } = get();
+    if (!rootPath) return;
+    set({ exportChunkTokenLimit: limit });
+    try {
+      await invoke("set_export_chunk_token_limit_setting", {
+        path: rootPath,
+        limit,
+      });
+    } catch (error) {
+      message(`Không thể lưu cài đặt chia nhỏ ngữ cảnh: ${error}`, {
+        title: "Lỗi",
+        kind: "error",
+      });
+    }
+  },
   setExportExcludeExtensions: async (extensions: string[]) => {
     const { rootPath } = get();
     if (!rootPath) return;
@@ -222,6 +332,24 @@ export const createSettingsActions: StateCreator<
       // Revert on error? Or just log it. Let's just log and show message.
     }
   },
+  setSecretRedaction: async (enabled: boolean, patterns: string[]) => {
+    const { rootPath, exportRedactSecrets, secretPatterns } = get();
+    if (!rootPath) return;
+    set({ exportRedactSecrets: enabled, secretPatterns: patterns });
+    try {
+      await invoke("set_secret_redaction_setting", {
+        path: rootPath,
+        enabled,
+        patterns,
+      });
+    } catch (error) {
+      message(`Không thể lưu cài đặt che bí mật: ${error}`, {
+        title: "Lỗi",
+        kind: "error",
+      });
+      set({ exportRedactSecrets, secretPatterns });
+    }
+  },
   setGitExportMode: async (enabled: boolean) => {
     const { rootPath } = get();
     if (!rootPath) return;
@@ -245,6 +373,7 @@ export const createSettingsActions: StateCreator<
     const {
       recentPaths,
       nonAnalyzableExtensions,
+      tokenizer,
       openRouterApiKey,
       googleApiKey,
       aiModels,
@@ -260,6 +389,7 @@ export const createSettingsActions: StateCreator<
       recentPaths: newSettings.recentPaths ?? recentPaths,
       nonAnalyzableExtensions:
         newSettings.nonAnalyzableExtensions ?? nonAnalyzableExtensions,
+      tokenizer: newSettings.tokenizer ?? tokenizer,
       openRouterApiKey: newSettings.openRouterApiKey ?? openRouterApiKey,
       googleApiKey: newSettings.googleApiKey ?? googleApiKey,
       aiModels: newSettings.aiModels ?? aiModels.map((m) => m.id),
@@ -283,6 +413,7 @@ export const createSettingsActions: StateCreator<
       set({
         recentPaths: fullSettings.recentPaths,
         nonAnalyzableExtensions: fullSettings.nonAnalyzableExtensions,
+        tokenizer: fullSettings.tokenizer ?? "cl100k",
         openRouterApiKey: fullSettings.openRouterApiKey ?? "",
         googleApiKey: fullSettings.googleApiKey ?? "",
         aiModels: projectAiModels.length
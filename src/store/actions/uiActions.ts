@@ -3,7 +3,11 @@ import { StateCreator } from "zustand";
 import { applyPatch, createPatch } from "diff";
 import { invoke } from "@tauri-apps/api/core";
 import { message } from "@tauri-apps/plugin-dialog";
-import { type FileMetadata } from "../types";
+import {
+  type FileMetadata,
+  type FileMtime,
+  type FileSnapshot,
+} from "../types";
 import { AppState } from "../appStore";
 
 export interface UIActions {
@@ -316,6 +320,8 @@ export const createUIActions: StateCreator<AppState, [], [], UIActions> = (
       let finalPatchedContent: string | false;
       let originalContentForStaging: string;
       let changeTypeForStaging: "create" | "modify" | "delete";
+      // Từ chối ghi nếu file bị sửa từ bên ngoài kể từ lúc đọc
+      let expectedMtime: FileMtime | null = null;
 
       const existingChange = stagedFileChanges.get(file_path);
 
@@ -365,10 +371,12 @@ export const createUIActions: StateCreator<AppState, [], [], UIActions> = (
           changeTypeForStaging = "create";
         } else {
           // delete or modify
-          originalContentForStaging = await invoke<string>("get_file_content", {
+          const snapshot = await invoke<FileSnapshot>("read_file_snapshot", {
             rootPathStr: rootPath,
             fileRelPath: file_path,
           });
+          originalContentForStaging = snapshot.content;
+          expectedMtime = snapshot.mtime;
           if (toolName === "delete_file") {
             finalPatchedContent = ""; // Represent deletion with empty content
             changeTypeForStaging = "delete";
@@ -406,6 +414,7 @@ export const createUIActions: StateCreator<AppState, [], [], UIActions> = (
           rootPathStr: rootPath,
           fileRelPath: file_path,
           content: finalPatchedContent as string, // It won't be false here
+          expectedMtime,
         });
       }
 
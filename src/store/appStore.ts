@@ -16,6 +16,8 @@ import {
   type AttachedItem,
   type ChatMessage,
   type AiFileActions,
+  type ExportFormat,
+  type Tokenizer,
 } from "./types";
 import { initialState } from "./initialState";
 import {
@@ -87,18 +89,27 @@ export interface AppState {
   syncPath: string | null;
   customIgnorePatterns: string[];
   isWatchingFiles: boolean;
+  watcherDebounceMs: number | null;
   exportUseFullTree: boolean;
   exportWithLineNumbers: boolean;
+  exportFormat: ExportFormat;
+  exportTemplatePath: string | null;
   exportWithoutComments: boolean;
   exportRemoveDebugLogs: boolean;
   exportSuperCompressed: boolean;
+  exportOutline: boolean;
+  exportRepoMap: boolean;
   alwaysApplyText: string | null;
+  exportChunkTokenLimit: number | null;
   exportExcludeExtensions: string[];
+  exportRedactSecrets: boolean;
+  secretPatterns: string[];
   gitExportModeIsContext: boolean;
 
   isSidebarVisible: boolean;
   recentPaths: string[];
   nonAnalyzableExtensions: string[];
+  tokenizer: Tokenizer;
 
   // Git Panel
   isGitPanelVisible: boolean;
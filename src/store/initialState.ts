@@ -34,17 +34,26 @@ export const initialState = {
   syncPath: null,
   customIgnorePatterns: [],
   isWatchingFiles: false,
+  watcherDebounceMs: null,
   exportUseFullTree: false,
   exportWithLineNumbers: true,
+  exportFormat: "plain",
+  exportTemplatePath: null,
   exportWithoutComments: false,
   exportRemoveDebugLogs: false,
   exportSuperCompressed: false,
+  exportOutline: false,
+  exportRepoMap: false,
   alwaysApplyText: null,
+  exportChunkTokenLimit: null,
   exportExcludeExtensions: [],
+  exportRedactSecrets: true,
+  secretPatterns: [],
   gitExportModeIsContext: false,
   isSidebarVisible: true,
   recentPaths: [],
   nonAnalyzableExtensions: [],
+  tokenizer: "cl100k",
   isGitPanelVisible: false,
   gitRepoInfo: null,
   gitStatus: null,
@@ -10,16 +10,29 @@ export interface CachedProjectData {
   data_hash?: string | null;
   custom_ignore_patterns?: string[]; // <-- Sửa thành snake_case
   is_watching_files?: boolean | null; // <-- THÊM TRƯỜNG MỚI
+  watcher_debounce_ms?: number | null;
   export_use_full_tree?: boolean | null; // <-- THÊM TRƯỜNG MỚI NÀY
   export_with_line_numbers?: boolean | null; // <-- THÊM TRƯỜNG MỚI
+  export_format?: ExportFormat | null;
+  export_template_path?: string | null;
   export_without_comments?: boolean | null; // <-- THÊM TRƯỜNG MỚI
   export_remove_debug_logs?: boolean | null; // <-- THÊM TRƯỜNG MỚI
   export_super_compressed?: boolean | null;
+  export_outline?: boolean | null;
+  export_repo_map?: boolean | null;
   always_apply_text?: string | null;
   export_exclude_extensions?: string[];
   git_export_mode_is_context?: boolean | null;
+  export_chunk_token_limit?: number | null;
+  export_redact_secrets?: boolean | null; // null = bật
+  secret_patterns?: string[] | null;
 }
 
+export type ExportFormat = "plain" | "xml" | "markdown" | "json" | "jsonl";
+
+// Tokenizer dùng để đếm token, theo mô hình đích
+export type Tokenizer = "cl100k" | "o200k" | "p50k" | "approximate";
+
 export interface FileNode {
   name: string;
   path: string;
@@ -33,11 +46,19 @@ export interface GroupStats {
   token_count: number;
 }
 
+export type AnalysisState = "pending" | "analyzed" | "skipped" | "unreadable";
+
 export interface FileMetadata {
   size: number;
   mtime: number;
+  mtime_nanos?: number | null;
   token_count: number;
+  token_counts?: Partial<Record<Tokenizer, number>>;
   excluded_ranges?: [number, number][];
+  dependencies?: string[];
+  imports?: string[];
+  content_hash?: string | null;
+  analysis_state?: AnalysisState;
 }
 
 export interface ProjectStats {
@@ -45,6 +66,7 @@ export interface ProjectStats {
   total_dirs: number;
   total_size: number;
   total_tokens: number;
+  tokenizer?: Tokenizer;
 }
 
 export interface ScanProgress {
@@ -58,6 +80,81 @@ export interface Group {
   paths: string[];
   stats: GroupStats;
   tokenLimit?: number; // <-- THÊM TRƯỜNG NÀY
+  templatePath?: string;
+  outline?: boolean; // undefined = theo cài đặt của dự án
+}
+
+export interface TokenBudgetReport {
+  tokenLimit: number;
+  originalTokenCount: number;
+  finalTokenCount: number;
+  cutFiles: {
+    path: string;
+    action: "outlined" | "compressed" | "truncated" | "omitted";
+    originalTokens: number;
+    finalTokens: number;
+  }[];
+}
+
+export interface RedactionReport {
+  secrets: { path: string; line: number; rule: string }[];
+}
+
+// Diff hoặc ngữ cảnh commit kèm báo cáo các bí mật đã bị che
+export interface ExportedContext {
+  context: string;
+  redactions: RedactionReport;
+}
+
+// mtime của file lúc đọc, gửi lại khi ghi để phát hiện thay đổi từ bên ngoài
+export interface FileMtime {
+  secs: number;
+  nanos: number;
+}
+
+export interface FileSnapshot {
+  content: string;
+  mtime: FileMtime;
+}
+
+// Kết quả của apply_search_replace / apply_unified_diff (null: file chưa tồn tại hoặc đã bị xóa)
+export interface FileEdit {
+  path: string;
+  originalContent: string | null;
+  newContent: string | null;
+}
+
+// Một thao tác của apply_change_set (áp dụng tất cả hoặc không gì cả, trả về id checkpoint)
+export type FileOperation =
+  | { type: "create"; path: string; content: string }
+  | {
+      type: "modify";
+      path: string;
+      content: string;
+      expectedMtime?: FileMtime | null;
+    }
+  | { type: "delete"; path: string }
+  | { type: "rename"; from: string; to: string };
+
+export interface SecretFinding {
+  path: string;
+  line: number;
+  rule: string;
+  severity: "high" | "medium" | "low";
+}
+
+export interface ContextMeasurement {
+  tokenizer: Tokenizer;
+  totalTokens: number;
+  totalBytes: number;
+  overheadTokens: number;
+  overheadBytes: number;
+  files: {
+    path: string;
+    tokens: number;
+    bytes: number;
+    budgetAction: TokenBudgetReport["cutFiles"][number]["action"] | null;
+  }[];
 }
 
 export interface AIGroupUpdateResult {
@@ -70,6 +167,22 @@ export interface ScanCompletePayload {
   isFirstScan: boolean;
 }
 
+export interface ProjectDiff {
+  added: string[];
+  removed: string[];
+  modified: string[];
+  addedDirs: string[];
+  touched: string[];
+}
+
+// Payload của sự kiện `project_files_changed` (cập nhật tăng dần từ watcher)
+export interface ProjectFilesChangedPayload {
+  diff: ProjectDiff;
+  metadata: Record<string, FileMetadata>;
+  stats: ProjectStats;
+  groups: { id: string; paths: string[]; stats: GroupStats }[];
+}
+
 export type AiChatMode = "ask" | "context" | "agent";
 
 export interface AIModel {
@@ -95,6 +208,7 @@ export interface AppSettings {
   topP?: number;
   topK?: number;
   maxTokens?: number;
+  tokenizer?: Tokenizer;
 }
 
 export interface GitRepositoryInfo {
@@ -216,18 +330,27 @@ export interface AppState {
   syncPath: string | null;
   customIgnorePatterns: string[];
   isWatchingFiles: boolean;
+  watcherDebounceMs: number | null;
   exportUseFullTree: boolean;
   exportWithLineNumbers: boolean;
+  exportFormat: ExportFormat;
+  exportTemplatePath: string | null;
   exportWithoutComments: boolean;
   exportRemoveDebugLogs: boolean;
   exportSuperCompressed: boolean;
+  exportOutline: boolean;
+  exportRepoMap: boolean;
   alwaysApplyText: string | null;
+  exportChunkTokenLimit: number | null;
   exportExcludeExtensions: string[];
+  exportRedactSecrets: boolean;
+  secretPatterns: string[];
   gitExportModeIsContext: boolean;
 
   isSidebarVisible: boolean;
   recentPaths: string[];
   nonAnalyzableExtensions: string[];
+  tokenizer: Tokenizer;
 
   // Git Panel
   isGitPanelVisible: boolean;